@@ -25,17 +25,56 @@ declare module "*.vue" {
 }
 
 declare module "*rta_metadata_wasm.js" {
-  export function parse_metadata(bytes: Uint8Array): unknown;
+  import type {
+    CoverPalette,
+    MetadataByteRange,
+    MetadataCueSheet,
+    MetadataInspection,
+    MetadataResult,
+    MpegFrameScan,
+  } from "@/types/metadata";
+
+  export function parse_metadata(bytes: Uint8Array): MetadataResult;
   export function parse_metadata_with_limits(
     bytes: Uint8Array,
     maxTextBytes: number,
     maxCoverBytes: number,
-  ): unknown;
+  ): MetadataResult;
   export function parse_metadata_batch(
     buffers: Array<unknown>,
     maxTextBytes: number,
     maxCoverBytes: number,
-  ): Array<unknown>;
+  ): Array<MetadataResult | null>;
+  export function parse_metadata_with_options(
+    bytes: Uint8Array,
+    options: unknown,
+  ): MetadataResult;
+  export function parse_metadata_batch_with_options(
+    buffers: Array<unknown>,
+    options: unknown,
+  ): Array<MetadataResult | null>;
+  export function extract_cover_palette(
+    bytes: Uint8Array,
+  ): CoverPalette | null;
+  export function extract_palette_rgba(
+    pixels: Uint8Array,
+    width: number,
+    height: number,
+  ): CoverPalette | null;
+  export function scan_mpeg_frames(bytes: Uint8Array): MpegFrameScan;
+  export function inspect(bytes: Uint8Array): MetadataInspection;
+  export function parse_cue_sheet(
+    bytes: Uint8Array,
+    options: unknown,
+  ): MetadataCueSheet | null;
+  export function write_cue_sheet(sheet: unknown): string;
+  export class MetadataStreamParser {
+    constructor(fileSize: number, options: unknown);
+    request(): MetadataByteRange | null;
+    feed(offset: number, chunk: Uint8Array): MetadataByteRange | null;
+    finish(): MetadataResult;
+    free(): void;
+  }
   const init: () => Promise<unknown>;
   export default init;
 }
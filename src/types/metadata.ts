@@ -0,0 +1,374 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type BitrateMode = "cbr" | "abr" | "vbr";
+
+export type CoverPalette = { dominant: PaletteColor, vibrant: PaletteColor, muted: PaletteColor, 
+/**
+ * Noir ou blanc, celui qui contraste le plus avec la couleur dominante
+ */
+text: PaletteColor, 
+/**
+ * Teintes triées par population décroissante
+ */
+swatches: Array<PaletteSwatch>, };
+
+/**
+ * Format du flux audio
+ */
+export type MetadataAudioProperties = { sampleRate?: number, bitDepth?: number, 
+/**
+ * Débit moyen en kbps, sinon celui de la première trame
+ */
+bitrate?: number, channels?: number, durationMs?: number, bitrateMode?: BitrateMode, };
+
+/**
+ * Plage d'octets `[start, end)` demandée par l'analyse incrémentale
+ */
+export type MetadataByteRange = { start: number, end: number, };
+
+export type MetadataCueIndex = { 
+/**
+ * 0 : début du pre-gap, 1 : début du morceau
+ */
+number: number, 
+/**
+ * Position en échantillons depuis le début du flux
+ */
+sample: number, offsetMs: number, };
+
+/**
+ * Feuille de montage (bloc FLAC CUESHEET ou commentaire CUESHEET)
+ */
+export type MetadataCueSheet = { catalog?: string, title?: string, performer?: string, file?: string, genre?: string, date?: string, comment?: string, albumGain?: number, albumPeak?: number, leadInSamples?: number, isCd: boolean, tracks: Array<MetadataCueTrack>, 
+/**
+ * Fichier .cue externe : encodage détecté du texte
+ */
+encoding?: string, };
+
+/**
+ * Morceau virtuel d'une image d'album
+ */
+export type MetadataCueTrack = { number: number, isrc?: string, title?: string, performer?: string, 
+/**
+ * Fichier contenant la piste (feuilles multi-fichiers)
+ */
+file?: string, audio: boolean, preEmphasis: boolean, 
+/**
+ * Drapeaux CUE autres que PRE : "DCP", "4CH", "SCMS"
+ */
+flags?: Array<string>, 
+/**
+ * ReplayGain de la piste, en dB et en amplitude crête
+ */
+trackGain?: number, trackPeak?: number, startSample?: number, startMs?: number, 
+/**
+ * Début du morceau suivant s'il est dans le même fichier, fin du flux pour le dernier
+ */
+endSample?: number, endMs?: number, preGapMs?: number, indices: Array<MetadataCueIndex>, };
+
+/**
+ * Octets d'une image désignés dans le tampon analysé (ou le fichier, pour une
+ * analyse par morceaux) plutôt que copiés
+ */
+export type MetadataDataRef = { offset: number, length: number, 
+/**
+ * Octets désynchronisés (ID3v2) : retirer le 0x00 qui suit chaque 0xFF après lecture
+ */
+unsynchronised: boolean, };
+
+export type MetadataEncoder = { 
+/**
+ * Chaîne vendeur FLAC / Vorbis / Opus, ou version LAME ("LAME3.100")
+ */
+name?: string, 
+/**
+ * Méthode de débit LAME : "cbr", "abr", "vbr-rh", "vbr-mtrh"…
+ */
+vbrMethod?: string, 
+/**
+ * Préréglage LAME ("V2", "extreme", "ABR 192")
+ */
+preset?: string, 
+/**
+ * Fréquence de coupure du passe-bas, en Hz
+ */
+lowpass?: number, 
+/**
+ * Qualité de l'en-tête Xing (0 = meilleure … 100)
+ */
+quality?: number, 
+/**
+ * Réglages de l'encodeur (TSSE, ENCODER)
+ */
+settings?: string, 
+/**
+ * TENC, ENCODED-BY
+ */
+encodedBy?: string, };
+
+/**
+ * Champs du bloc STREAMINFO FLAC au-delà du format audio
+ */
+export type MetadataFlac = { 
+/**
+ * Tailles de bloc, en échantillons
+ */
+minBlockSize: number, maxBlockSize: number, 
+/**
+ * Tailles de trame en octets, absentes si l'encodeur ne les connaissait pas
+ */
+minFrameSize?: number, maxFrameSize?: number, 
+/**
+ * Échantillons par canal
+ */
+totalSamples?: number, 
+/**
+ * Signature MD5 de l'audio décodé, en hexadécimal
+ */
+md5?: string, 
+/**
+ * Points de la table SEEKTABLE ; `offset` est relatif à la première trame audio
+ */
+seekTable?: Array<MetadataFlacSeekPoint>, 
+/**
+ * Identifiants des blocs APPLICATION ("riff", "ATCH", ou "0x…" s'ils ne sont pas imprimables)
+ */
+applicationIds?: Array<string>, 
+/**
+ * Octets de remplissage disponibles pour réécrire les tags sans déplacer l'audio
+ */
+paddingBytes: number, 
+/**
+ * Position de la première trame audio dans le fichier
+ */
+audioOffset?: number, };
+
+export type MetadataFlacSeekPoint = { sample: number, offset: number, frameSamples: number, timeMs?: number, };
+
+/**
+ * Conteneur reconnu à la signature du fichier
+ */
+export type MetadataFormat = "flac" | "ogg" | "mp4" | "mpeg";
+
+/**
+ * Échantillons à retirer pour enchaîner les pistes sans blanc
+ */
+export type MetadataGapless = { 
+/**
+ * Délai encodeur ou pre-skip Opus, en échantillons
+ */
+delay: number, 
+/**
+ * Remplissage en fin de flux, en échantillons
+ */
+padding?: number, 
+/**
+ * Nombre exact d'échantillons (par canal) à conserver
+ */
+validSamples?: number, source: "lame" | "itunsmpb" | "opus", };
+
+/**
+ * Élément structurel : tag, frame ID3, bloc FLAC, clé Vorbis, trame MPEG…
+ */
+export type MetadataInspectNode = { 
+/**
+ * « id3v2 », « id3v2-frame », « id3v1 », « flac-block », « vorbis-comment »…
+ */
+kind: string, id: string, 
+/**
+ * Position absolue dans le fichier ; absente pour les données réassemblées (paquets Ogg)
+ */
+offset?: number, size: number, 
+/**
+ * Drapeaux, encodage déclaré, etc.
+ */
+details: Record<string, string>, usage?: MetadataInspectUsage, children: Array<MetadataInspectNode>, };
+
+/**
+ * Sort d'un élément structurel : champ du résultat alimenté (`mappedTo`),
+ * ou raison de l'écarter (`skipped`)
+ */
+export type MetadataInspectUsage = { "mappedTo": string } | { "skipped": string };
+
+/**
+ * Vue « débogueur de tags » d'un fichier
+ */
+export type MetadataInspection = { format: MetadataFormat, 
+/**
+ * Éléments structurels de premier niveau (tags, blocs, trame MPEG)
+ */
+nodes: Array<MetadataInspectNode>, metadata: MetadataResult, };
+
+/**
+ * Paroles non synchronisées (USLT, LYRICS) ou au format LRC
+ */
+export type MetadataLyrics = { lyrics?: string, syncedLyrics?: string, };
+
+export type MetadataModeExtension = { msStereo: boolean, intensityStereo: boolean, 
+/**
+ * Layer I / II : première sous-bande codée en intensity stereo
+ */
+intensityBound?: number, };
+
+/**
+ * Paramètres de la première trame d'un flux MPEG audio
+ */
+export type MetadataMpeg = { 
+/**
+ * Libellé lisible, ex. "MPEG-1 Layer III joint stereo"
+ */
+format: string, version: "1" | "2" | "2.5", layer: 1 | 2 | 3, channelMode: "stereo" | "joint stereo" | "dual channel" | "mono", 
+/**
+ * Outils de joint stereo actifs (absent hors joint stereo)
+ */
+modeExtension?: MetadataModeExtension, emphasis: "none" | "50/15" | "ccitt-j17", copyright: boolean, original: boolean, crcProtected: boolean, };
+
+export type MetadataPicture = { type: number, typeName: string, description: string, 
+/**
+ * Type MIME réel, déduit de l'en-tête de l'image
+ */
+mime: string, 
+/**
+ * Type MIME écrit dans le tag, s'il diffère du type réel
+ */
+declaredMime?: string, width?: number, height?: number, colorDepth?: number, byteLength: number, data?: Uint8Array, 
+/**
+ * Emplacement de l'image dans l'entrée, avec l'option `cover_refs`
+ */
+dataRef?: MetadataDataRef, };
+
+/**
+ * Image référencée par une URL plutôt qu'intégrée au fichier
+ */
+export type MetadataPictureLink = { type: number, typeName: string, description: string, url: string, };
+
+/**
+ * Métadonnées d'un fichier audio
+ */
+export type MetadataResult = { 
+/**
+ * Valeurs jointes par « ; » pour l'affichage
+ */
+title?: string, artist?: string, album?: string, 
+/**
+ * Toutes les valeurs des champs multi-valués, dans l'ordre des tags
+ */
+titles?: Array<string>, artists?: Array<string>, albums?: Array<string>, coverMime?: string, coverData?: Uint8Array, 
+/**
+ * Emplacement de la pochette dans l'entrée, avec l'option `cover_refs`
+ */
+coverRef?: MetadataDataRef, coverType?: number, coverThumbnails?: Array<MetadataThumbnail>, mpeg?: MetadataMpeg, flac?: MetadataFlac, seekPoints?: Array<MetadataSeekPoint>, gapless?: MetadataGapless, cueSheet?: MetadataCueSheet, pictures?: Array<MetadataPicture>, pictureLinks?: Array<MetadataPictureLink>, encoder?: MetadataEncoder, 
+/**
+ * Encodage détecté pour les textes déclarés ISO-8859-1 (ex. "windows-1251", "Shift_JIS")
+ */
+legacyEncoding?: string, 
+/**
+ * Anomalies relevées dans les tags, qui n'ont pas empêché l'analyse
+ */
+warnings?: Array<MetadataWarning>, sampleRate?: number, bitDepth?: number, 
+/**
+ * Débit moyen en kbps, sinon celui de la première trame
+ */
+bitrate?: number, channels?: number, durationMs?: number, bitrateMode?: BitrateMode, lyrics?: string, syncedLyrics?: string, };
+
+export type MetadataSeekPoint = { timeMs: number, 
+/**
+ * Position dans le fichier, en octets
+ */
+offset: number, };
+
+export type MetadataThumbnail = { 
+/**
+ * Côté maximal demandé
+ */
+size: number, width: number, height: number, 
+/**
+ * "png" : fichier PNG encodé, "rgba" : pixels bruts (width × height × 4)
+ */
+format: ThumbnailFormat, mime?: string, data: Uint8Array, };
+
+/**
+ * Anomalie relevée dans les tags, qui n'a pas empêché l'analyse
+ */
+export type MetadataWarning = { code: MetadataWarningCode, message: string, 
+/**
+ * Position dans le fichier, absente pour les paquets Ogg réassemblés
+ */
+offset?: number, };
+
+/**
+ * Codes stables de `Warning::code`, décrits pour TypeScript uniquement
+ */
+export type MetadataWarningCode = "truncated-tag" | "truncated-block" | "frame-overrun" | "invalid-encoding" | "cover-too-large" | "id3v1-mismatch" | "unknown-picture-type" | "unknown-picture-format" | "ogg-crc-mismatch";
+
+/**
+ * Nombre de trames à un débit donné (kbps)
+ */
+export type MpegBitrateCount = { bitrate: number, frames: number, };
+
+export type MpegChannelModeChange = { 
+/**
+ * Position de la première trame dans le nouveau mode
+ */
+offset: number, from: string, to: string, };
+
+/**
+ * Rapport de contrôle qualité produit par le parcours de toutes les trames d'un MP3
+ */
+export type MpegFrameScan = { 
+/**
+ * Début et fin du flux audio, tags exclus
+ */
+audioStart: number, audioEnd: number, 
+/**
+ * Taux d'échantillonnage et mode de canaux de la première trame (ou trame d'information)
+ */
+sampleRate?: number, channelMode?: string, 
+/**
+ * Trame Xing / Info / VBRI, qui ne contient pas d'audio et n'est pas décomptée
+ */
+infoFrameOffset?: number, frames: number, 
+/**
+ * Échantillons par canal
+ */
+samples: number, durationMs: number, minBitrate?: number, maxBitrate?: number, 
+/**
+ * Débit moyen en kbps, d'après la taille réelle des trames
+ */
+avgBitrate?: number, 
+/**
+ * Nombre de trames par débit, par débit croissant
+ */
+bitrateHistogram: Array<MpegBitrateCount>, channelModeChanges: Array<MpegChannelModeChange>, 
+/**
+ * « none », « partial » ou « all » selon la part de trames protégées par un CRC
+ */
+crcProtection: "none" | "partial" | "all", crcProtectedFrames: number, 
+/**
+ * Positions des trames protégées dont le CRC ne correspond pas au contenu
+ */
+crcErrors: Array<number>, syncErrors: Array<MpegSyncError>, };
+
+/**
+ * Zone du flux qui n'a pas pu être lue comme une suite de trames
+ */
+export type MpegSyncError = { offset: number, length: number, 
+/**
+ * « junk » avant la première trame ou après la dernière, « sync-lost » quand
+ * une trame n'est pas suivie d'un en-tête valide, « truncated » pour une trame incomplète
+ */
+kind: "junk" | "sync-lost" | "truncated", };
+
+export type PaletteColor = { r: number, g: number, b: number, 
+/**
+ * « #rrggbb »
+ */
+hex: string, };
+
+export type PaletteSwatch = { population: number, r: number, g: number, b: number, 
+/**
+ * « #rrggbb »
+ */
+hex: string, };
+
+export type ThumbnailFormat = "png" | "rgba";
@@ -7,6 +7,7 @@ export interface PlaylistTrack {
   formatLabel?: string;
   sampleRate?: number;
   bitDepth?: number;
+  durationMs?: number;
   file?: File;
   lyrics?: string;
   syncedLyrics?: string;
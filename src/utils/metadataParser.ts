@@ -1,62 +1,169 @@
-export interface MetadataResult {
-  title?: string;
-  artist?: string;
-  album?: string;
-  coverMime?: string;
-  coverData?: Uint8Array;
-  coverType?: number;
+import type {
+  CoverPalette,
+  MetadataByteRange,
+  MetadataCueSheet,
+  MetadataCueTrack,
+  MetadataDataRef,
+  MetadataInspection,
+  MetadataResult,
+  MetadataThumbnail,
+  MpegFrameScan,
+  ThumbnailFormat,
+} from "../types/metadata";
+
+/**
+ * Types des résultats (métadonnées, inspection, palette, parcours des trames),
+ * générés par ts-rs à partir des structures Rust (wasm/metadata/core/src) :
+ * `cargo test` les régénère.
+ */
+export type * from "../types/metadata";
+
+export interface CueSheetParseOptions {
+  /** Taux d'échantillonnage du fichier audio, 44100 par défaut */
   sampleRate?: number;
-  bitDepth?: number;
-  bitrate?: number;
-  channels?: number;
-  lyrics?: string;
-  syncedLyrics?: string;
+  /** Durée du fichier audio, pour connaître la fin de la dernière piste */
+  durationMs?: number;
+  /**
+   * Page de code privilégiée pour un .cue qui n'est pas en UTF-8 ; un nom
+   * inconnu est ignoré
+   */
+  fallbackEncoding?: string;
 }
 
+/** Piste à écrire : `indices` (offsetMs) ou `startMs` et `preGapMs` */
+export type CueSheetTrackInput = Partial<
+  Omit<MetadataCueTrack, "indices" | "startMs">
+> & {
+  startMs?: number;
+  indices?: { number: number; offsetMs: number }[];
+};
+
+export type CueSheetInput = Partial<Omit<MetadataCueSheet, "tracks">> & {
+  tracks: CueSheetTrackInput[];
+};
+
 export interface MetadataParseOptions {
   maxTextBytes?: number;
   maxCoverBytes?: number;
+  /**
+   * @deprecated Ignoré : la lecture incrémentale ne charge plus que les plages
+   * demandées par le parseur
+   */
   maxTagBytes?: number;
+  /**
+   * @deprecated Ignoré : les blocs de métadonnées FLAC sont lus un à un par le
+   * parseur incrémental
+   */
   maxFlacBytes?: number;
+  /** Lister toutes les images intégrées dans `pictures` */
+  includePictures?: boolean;
+  /** Copier les octets de chaque image listée (`pictures[].data`) */
+  includePictureData?: boolean;
+  /** Copier les octets de la pochette (`coverData`), `true` par défaut */
+  includeCoverData?: boolean;
+  /**
+   * Désigner les images par leur emplacement dans le fichier (`coverRef`,
+   * `pictures[].dataRef`) au lieu de copier leurs octets ; voir `readDataRef`
+   */
+  coverRefs?: boolean;
+  /** Côtés maximaux des miniatures de pochette générées par le module WASM (JPEG / PNG) */
+  thumbnailSizes?: number[];
+  thumbnailFormat?: ThumbnailFormat;
+  /**
+   * Page de code privilégiée pour les tags ID3 déclarés ISO-8859-1 (ex. "windows-1251", "cp932"),
+   * retenue à score égal ou quand la détection n'aboutit pas ; un nom inconnu
+   * est ignoré
+   */
+  fallbackEncoding?: string;
+  /** Points d'accès issus de la table TOC Xing / VBRI ou de la SEEKTABLE FLAC (`seekPoints`) */
+  includeSeekPoints?: boolean;
+  /** Octets examinés après les tags pour trouver la première trame MPEG (8192 par défaut) */
+  mpegScanBytes?: number;
 }
 
 export interface MetadataBatchOptions extends MetadataParseOptions {
   batchSize?: number;
 }
 
+/** Options transmises telles quelles au parseur WASM */
+export interface WasmParseOptions {
+  maxTextBytes: number;
+  maxCoverBytes: number;
+  includePictures: boolean;
+  includePictureData: boolean;
+  includeCoverData: boolean;
+  coverRefs: boolean;
+  thumbnailSizes: number[];
+  thumbnailFormat: ThumbnailFormat;
+  fallbackEncoding?: string;
+  includeSeekPoints: boolean;
+  mpegScanBytes: number;
+  /** Taille du fichier complet, quand seul un extrait est transmis */
+  fileSize?: number;
+  /** Lot : taille du fichier de chaque tampon */
+  fileSizes?: number[];
+}
+
+/**
+ * Parseur incrémental (classe `MetadataStreamParser` du module WASM) : chaque appel
+ * indique la prochaine plage à lire, `null` quand `finish()` peut conclure.
+ * Pour un MP4/M4A, seuls les en-têtes d'atomes et `moov` sont lus, même quand
+ * il suit `mdat` en fin de fichier.
+ */
+export interface MetadataStreamParser {
+  request(): MetadataByteRange | null;
+  feed(offset: number, chunk: Uint8Array): MetadataByteRange | null;
+  finish(): MetadataResult;
+  free(): void;
+}
+
 export type MetadataWasmModule = {
   parse_metadata_with_limits: (
     bytes: Uint8Array,
     maxTextBytes: number,
     maxCoverBytes: number,
-  ) => unknown;
+  ) => MetadataResult;
   parse_metadata_batch: (
     buffers: Uint8Array[],
     maxTextBytes: number,
     maxCoverBytes: number,
-  ) => unknown[];
+  ) => (MetadataResult | null)[];
+  parse_metadata_with_options: (
+    bytes: Uint8Array,
+    options: WasmParseOptions,
+  ) => MetadataResult;
+  parse_metadata_batch_with_options: (
+    buffers: Uint8Array[],
+    options: WasmParseOptions,
+  ) => (MetadataResult | null)[];
+  extract_cover_palette: (bytes: Uint8Array) => CoverPalette | null;
+  scan_mpeg_frames: (bytes: Uint8Array) => MpegFrameScan;
+  inspect: (bytes: Uint8Array) => MetadataInspection;
+  parse_cue_sheet: (
+    bytes: Uint8Array,
+    options: CueSheetParseOptions,
+  ) => MetadataCueSheet | null;
+  write_cue_sheet: (sheet: CueSheetInput) => string;
+  extract_palette_rgba: (
+    pixels: Uint8Array,
+    width: number,
+    height: number,
+  ) => CoverPalette | null;
+  MetadataStreamParser: new (
+    fileSize: number,
+    options: WasmParseOptions,
+  ) => MetadataStreamParser;
 };
 
 const DEFAULT_MAX_TEXT_BYTES = 16 * 1024;
 const DEFAULT_MAX_COVER_BYTES = 4 * 1024 * 1024;
-const DEFAULT_MAX_TAG_BYTES = 4 * 1024 * 1024;
-const DEFAULT_MAX_FLAC_BYTES = 2 * 1024 * 1024;
-
-const ID3_HEADER_SIZE = 10;
-const ID3_TAG = 0x494433;
-const FLAC_TAG = 0x664c6143;
-
-/**
- * Octets supplémentaires à lire après le tag ID3v2 pour que le parseur WASM puisse localiser
- * le premier en-tête de trame audio MPEG et extraire le taux d'échantillonnage / débit binaire.
- */
-const MPEG_FRAME_SCAN_BYTES = 4096;
 
 /**
- * Pour les fichiers qui commencent par une synchronisation MPEG brute (sans ID3v2), nous lisons
- * ce nombre d'octets depuis le début pour trouver l'en-tête de trame + queue ID3v1.
+ * Octets examinés après le tag ID3v2 (ou depuis le début d'un MPEG brut) pour que le parseur WASM
+ * puisse localiser le premier en-tête de trame audio MPEG et extraire le taux d'échantillonnage /
+ * débit binaire.
  */
-const RAW_MPEG_HEAD_BYTES = 4096;
+const DEFAULT_MPEG_SCAN_BYTES = 8192;
 
 let wasmModule: MetadataWasmModule | null = null;
 
@@ -80,78 +187,6 @@ function requireWasm(): MetadataWasmModule {
   return wasmModule;
 }
 
-function normalizeMetadata(value: unknown): MetadataResult {
-  if (!value || typeof value !== "object") {
-    return {};
-  }
-  const record = value as Record<string, unknown>;
-  const coverData = record.coverData;
-  return {
-    title: typeof record.title === "string" ? record.title : undefined,
-    artist: typeof record.artist === "string" ? record.artist : undefined,
-    album: typeof record.album === "string" ? record.album : undefined,
-    coverMime:
-      typeof record.coverMime === "string" ? record.coverMime : undefined,
-    coverData: coverData instanceof Uint8Array ? coverData : undefined,
-    coverType:
-      typeof record.coverType === "number" ? record.coverType : undefined,
-    sampleRate:
-      typeof record.sampleRate === "number" ? record.sampleRate : undefined,
-    bitDepth: typeof record.bitDepth === "number" ? record.bitDepth : undefined,
-    bitrate: typeof record.bitrate === "number" ? record.bitrate : undefined,
-    channels: typeof record.channels === "number" ? record.channels : undefined,
-    lyrics: typeof record.lyrics === "string" ? record.lyrics : undefined,
-    syncedLyrics:
-      typeof record.syncedLyrics === "string" ? record.syncedLyrics : undefined,
-  };
-}
-
-function mergeMetadata(
-  primary: MetadataResult,
-  fallback: MetadataResult,
-): MetadataResult {
-  return {
-    title: primary.title || fallback.title,
-    artist: primary.artist || fallback.artist,
-    album: primary.album || fallback.album,
-    coverMime: primary.coverMime || fallback.coverMime,
-    coverData: primary.coverData || fallback.coverData,
-    coverType:
-      primary.coverType !== undefined ? primary.coverType : fallback.coverType,
-    sampleRate:
-      primary.sampleRate !== undefined
-        ? primary.sampleRate
-        : fallback.sampleRate,
-    bitDepth:
-      primary.bitDepth !== undefined ? primary.bitDepth : fallback.bitDepth,
-    bitrate: primary.bitrate !== undefined ? primary.bitrate : fallback.bitrate,
-    channels:
-      primary.channels !== undefined ? primary.channels : fallback.channels,
-    lyrics: primary.lyrics || fallback.lyrics,
-    syncedLyrics: primary.syncedLyrics || fallback.syncedLyrics,
-  };
-}
-
-function synchsafeToSize(bytes: Uint8Array): number {
-  if (bytes.length < 4) return 0;
-  return (
-    ((bytes[0] & 0x7f) << 21) |
-    ((bytes[1] & 0x7f) << 14) |
-    ((bytes[2] & 0x7f) << 7) |
-    (bytes[3] & 0x7f)
-  );
-}
-
-function bytesToU32BE(bytes: Uint8Array, offset: number): number {
-  return (
-    ((bytes[offset] << 24) |
-      (bytes[offset + 1] << 16) |
-      (bytes[offset + 2] << 8) |
-      bytes[offset + 3]) >>>
-    0
-  );
-}
-
 async function readSlice(
   file: File,
   start: number,
@@ -162,251 +197,149 @@ async function readSlice(
   return new Uint8Array(buffer);
 }
 
-async function readHeader(file: File, length: number) {
-  const size = Math.min(file.size, length);
-  return readSlice(file, 0, size);
-}
-
-async function readTail(file: File, length: number) {
-  const size = Math.min(file.size, length);
-  return readSlice(file, file.size - size, file.size);
-}
-
-function isFlacHeader(header: Uint8Array) {
-  return header.length >= 4 && bytesToU32BE(header, 0) === FLAC_TAG;
-}
-
-function isId3Header(header: Uint8Array) {
-  return header.length >= 3 && bytesToU32BE(header, 0) >>> 8 === ID3_TAG;
-}
-
-/**
- * Vérifie la présence d'un mot de synchronisation MPEG brut (0xFF suivi de 0xE0+).
- * Cela détecte les fichiers MP3 qui commencent directement par des trames audio (sans ID3v2).
- */
-function isMpegSync(header: Uint8Array) {
-  return (
-    header.length >= 2 && header[0] === 0xff && (header[1] & 0xe0) === 0xe0
-  );
-}
-
-async function readFlacMetadataPrefix(
-  file: File,
-  maxFlacBytes: number,
-): Promise<Uint8Array> {
-  const fileSize = file.size;
-  const headerSize = Math.min(4, fileSize, maxFlacBytes);
-  if (headerSize < 4) {
-    return readSlice(file, 0, headerSize);
-  }
-
-  let total = 4;
-  let offset = 4;
-
-  while (offset + 4 <= fileSize && total + 4 <= maxFlacBytes) {
-    const header = await readSlice(file, offset, offset + 4);
-    if (header.length < 4) break;
-
-    const isLast = (header[0] & 0x80) !== 0;
-    const length = (header[1] << 16) | (header[2] << 8) | header[3];
-
-    const nextOffset = offset + 4 + length;
-    if (nextOffset > fileSize) break;
-    if (total + 4 + length > maxFlacBytes) break;
-
-    total += 4 + length;
-    offset = nextOffset;
-
-    if (isLast) break;
-  }
-
-  return readSlice(file, 0, total);
-}
-
-/**
- * Pour les MP3 avec ID3v2 : lire le tag + octets supplémentaires pour que le parseur WASM puisse
- * trouver le premier en-tête de trame MPEG juste après le tag.
- */
-function id3HeadReadSize(
-  fileSize: number,
-  tagSize: number,
-  maxTagBytes: number,
-): number {
-  const withFrame = ID3_HEADER_SIZE + tagSize + MPEG_FRAME_SCAN_BYTES;
-  const totalSize = Math.min(fileSize, withFrame);
-  return Math.min(totalSize, maxTagBytes);
-}
-
-function normalizeLimits(options?: MetadataParseOptions) {
+function normalizeOptions(options?: MetadataParseOptions): WasmParseOptions {
   return {
     maxTextBytes: options?.maxTextBytes ?? DEFAULT_MAX_TEXT_BYTES,
     maxCoverBytes: options?.maxCoverBytes ?? DEFAULT_MAX_COVER_BYTES,
-    maxTagBytes: options?.maxTagBytes ?? DEFAULT_MAX_TAG_BYTES,
-    maxFlacBytes: options?.maxFlacBytes ?? DEFAULT_MAX_FLAC_BYTES,
+    includePictures: options?.includePictures ?? false,
+    includePictureData: options?.includePictureData ?? false,
+    includeCoverData: options?.includeCoverData ?? true,
+    coverRefs: options?.coverRefs ?? false,
+    thumbnailSizes: options?.thumbnailSizes ?? [],
+    thumbnailFormat: options?.thumbnailFormat ?? "png",
+    fallbackEncoding: options?.fallbackEncoding,
+    includeSeekPoints: options?.includeSeekPoints ?? false,
+    mpegScanBytes: options?.mpegScanBytes ?? DEFAULT_MPEG_SCAN_BYTES,
   };
 }
 
-export async function parseMetadataFromFile(
+/**
+ * Lecture guidée par le parseur incrémental : seules les plages qu'il demande
+ * sont lues (blocs FLAC, pages d'en-tête Ogg, tags de tête et de fin).
+ */
+async function parseFileWithStream(
   file: File,
-  options?: MetadataParseOptions,
+  wasmOptions: WasmParseOptions,
 ): Promise<MetadataResult> {
   const wasm = requireWasm();
-  const { maxTextBytes, maxCoverBytes, maxTagBytes, maxFlacBytes } =
-    normalizeLimits(options);
-
-  const header = await readHeader(file, ID3_HEADER_SIZE);
-
-  // ── FLAC ────────────────────────────────────────────────────────────
-  if (isFlacHeader(header)) {
-    const flacBytes = await readFlacMetadataPrefix(file, maxFlacBytes);
-    return normalizeMetadata(
-      wasm.parse_metadata_with_limits(flacBytes, maxTextBytes, maxCoverBytes),
-    );
-  }
-
-  // ── MP3 avec ID3v2 ──────────────────────────────────────────────────
-  if (isId3Header(header)) {
-    const tagSize = synchsafeToSize(header.subarray(6, 10));
-    const cappedSize = id3HeadReadSize(file.size, tagSize, maxTagBytes);
-    const headBytes = await readSlice(file, 0, cappedSize);
-    const headMeta = normalizeMetadata(
-      wasm.parse_metadata_with_limits(headBytes, maxTextBytes, maxCoverBytes),
-    );
-
-    if (headMeta.title && headMeta.artist && headMeta.album) {
-      return headMeta;
+  const parser = new wasm.MetadataStreamParser(file.size, wasmOptions);
+  try {
+    let range = parser.request();
+    while (range) {
+      const chunk = await readSlice(file, range.start, range.end);
+      // Fichier modifié entre-temps : la plage demandée n'existe plus
+      if (!chunk.length) break;
+      range = parser.feed(range.start, chunk);
     }
-
-    const tailBytes = await readTail(file, 128);
-    const tailMeta = normalizeMetadata(
-      wasm.parse_metadata_with_limits(tailBytes, maxTextBytes, maxCoverBytes),
-    );
-    return mergeMetadata(headMeta, tailMeta);
+    return parser.finish();
+  } finally {
+    parser.free();
   }
-
-  // ── MPEG brut (sans ID3v2) ──────────────────────────────────────────
-  if (isMpegSync(header)) {
-    const headBytes = await readHeader(file, RAW_MPEG_HEAD_BYTES);
-    const headMeta = normalizeMetadata(
-      wasm.parse_metadata_with_limits(headBytes, maxTextBytes, maxCoverBytes),
-    );
-
-    const tailBytes = await readTail(file, 128);
-    const tailMeta = normalizeMetadata(
-      wasm.parse_metadata_with_limits(tailBytes, maxTextBytes, maxCoverBytes),
-    );
-    return mergeMetadata(headMeta, tailMeta);
-  }
-
-  // ── Fallback : essayer uniquement la queue ID3v1 ──────────────────────
-  const tailBytes = await readTail(file, 128);
-  const tailMeta = normalizeMetadata(
-    wasm.parse_metadata_with_limits(tailBytes, maxTextBytes, maxCoverBytes),
-  );
-  return tailMeta;
 }
 
-type BatchTask = {
-  file: File;
-  index: number;
-  header: Uint8Array;
-  headBytes?: Uint8Array;
-  tailBytes?: Uint8Array;
-  metadata?: MetadataResult;
-};
+export async function parseMetadataFromFile(
+  file: File,
+  options?: MetadataParseOptions,
+): Promise<MetadataResult> {
+  return parseFileWithStream(file, normalizeOptions(options));
+}
 
+/** `batchSize` : nombre de fichiers lus en parallèle */
 export async function parseMetadataBatch(
   files: File[],
   options?: MetadataBatchOptions,
 ): Promise<MetadataResult[]> {
-  const wasm = requireWasm();
-  const { maxTextBytes, maxCoverBytes, maxTagBytes, maxFlacBytes } =
-    normalizeLimits(options);
+  const wasmOptions = normalizeOptions(options);
   const batchSize = Math.max(1, options?.batchSize ?? 8);
-  const results: MetadataResult[] = new Array(files.length);
+  const results: MetadataResult[] = [];
 
   for (let batchStart = 0; batchStart < files.length; batchStart += batchSize) {
     const batch = files.slice(batchStart, batchStart + batchSize);
-    const tasks: BatchTask[] = await Promise.all(
-      batch.map(async (file, index) => {
-        const header = await readHeader(file, ID3_HEADER_SIZE);
-        return {
-          file,
-          index: batchStart + index,
-          header,
-        };
-      }),
+    const parsed = await Promise.all(
+      batch.map((file) => parseFileWithStream(file, wasmOptions)),
     );
+    results.push(...parsed);
+  }
 
-    // ── Passe d'en-tête ─────────────────────────────────────────────
-    const headBuffers: Uint8Array[] = [];
-    const headTasks: BatchTask[] = [];
-    for (const task of tasks) {
-      if (isFlacHeader(task.header)) {
-        task.headBytes = await readFlacMetadataPrefix(task.file, maxFlacBytes);
-      } else if (isId3Header(task.header)) {
-        const tagSize = synchsafeToSize(task.header.subarray(6, 10));
-        const cappedSize = id3HeadReadSize(
-          task.file.size,
-          tagSize,
-          maxTagBytes,
-        );
-        task.headBytes = await readSlice(task.file, 0, cappedSize);
-      } else if (isMpegSync(task.header)) {
-        // MPEG brut sans ID3v2 — lire suffisamment pour trouver l'en-tête de trame
-        task.headBytes = await readHeader(task.file, RAW_MPEG_HEAD_BYTES);
-      }
+  return results;
+}
 
-      if (task.headBytes) {
-        headBuffers.push(task.headBytes);
-        headTasks.push(task);
-      }
-    }
+/**
+ * Octets d'une image désignée par `coverRef` ou `pictures[].dataRef`, lus à la
+ * demande ; le 0x00 qui suit chaque 0xFF est retiré des octets désynchronisés.
+ */
+export async function readDataRef(
+  file: File,
+  ref: MetadataDataRef,
+): Promise<Uint8Array> {
+  const bytes = await readSlice(file, ref.offset, ref.offset + ref.length);
+  if (!ref.unsynchronised) return bytes;
+
+  const data = new Uint8Array(bytes.length);
+  let length = 0;
+  for (let i = 0; i < bytes.length; i++) {
+    if (i > 0 && bytes[i - 1] === 0xff && bytes[i] === 0x00) continue;
+    data[length++] = bytes[i];
+  }
+  return data.subarray(0, length);
+}
 
-    if (headBuffers.length) {
-      const parsed = wasm.parse_metadata_batch(
-        headBuffers,
-        maxTextBytes,
-        maxCoverBytes,
-      );
-      parsed.forEach((value, idx) => {
-        headTasks[idx].metadata = normalizeMetadata(value);
-      });
-    }
+/**
+ * Palette (dominante, vive, sourde, couleur de texte) calculée par le module WASM
+ * à partir d'une pochette encodée (JPEG / PNG) ou d'une miniature RVBA.
+ */
+export function extractCoverPalette(
+  cover: Uint8Array | MetadataThumbnail,
+): CoverPalette | null {
+  const wasm = requireWasm();
+  const value =
+    cover instanceof Uint8Array
+      ? wasm.extract_cover_palette(cover)
+      : cover.format === "rgba"
+        ? wasm.extract_palette_rgba(cover.data, cover.width, cover.height)
+        : wasm.extract_cover_palette(cover.data);
+  return value ?? null;
+}
 
-    // ── Passe de queue (repli ID3v1) ────────────────────────────────
-    const tailBuffers: Uint8Array[] = [];
-    const tailTasks: BatchTask[] = [];
-    for (const task of tasks) {
-      const meta = task.metadata ?? {};
-      const needsTail =
-        !meta.title || !meta.artist || !meta.album || !meta.title?.length;
-      if (needsTail) {
-        task.tailBytes = await readTail(task.file, 128);
-        tailBuffers.push(task.tailBytes);
-        tailTasks.push(task);
-      }
-    }
+/**
+ * Contrôle qualité d'un MP3 : le fichier est lu en entier et chaque trame est
+ * examinée (débits, modes de canaux, CRC, pertes de synchronisation).
+ */
+export async function scanMpegFrames(file: File): Promise<MpegFrameScan> {
+  const wasm = requireWasm();
+  const bytes = await readSlice(file, 0, file.size);
+  return wasm.scan_mpeg_frames(bytes);
+}
 
-    if (tailBuffers.length) {
-      const parsed = wasm.parse_metadata_batch(
-        tailBuffers,
-        maxTextBytes,
-        maxCoverBytes,
-      );
-      parsed.forEach((value, idx) => {
-        const tailMeta = normalizeMetadata(value);
-        tailTasks[idx].metadata = mergeMetadata(
-          tailTasks[idx].metadata ?? {},
-          tailMeta,
-        );
-      });
-    }
+/**
+ * Arbre des éléments structurels du fichier entier, avec le champ que chacun
+ * alimente ou la raison pour laquelle il a été écarté.
+ */
+export async function inspectMetadata(
+  file: File,
+): Promise<MetadataInspection> {
+  const wasm = requireWasm();
+  const bytes = await readSlice(file, 0, file.size);
+  return wasm.inspect(bytes);
+}
 
-    for (const task of tasks) {
-      results[task.index] = task.metadata ?? {};
-    }
-  }
+/**
+ * Feuille CUE externe (fichier .cue accompagnant une image d'album), avec détection
+ * de l'encodage. Même structure que `cueSheet` du résultat des métadonnées.
+ */
+export async function parseCueSheet(
+  source: File | Uint8Array,
+  options: CueSheetParseOptions = {},
+): Promise<MetadataCueSheet | null> {
+  const wasm = requireWasm();
+  const bytes =
+    source instanceof Uint8Array
+      ? source
+      : await readSlice(source, 0, source.size);
+  return wasm.parse_cue_sheet(bytes, options) ?? null;
+}
 
-  return results;
+/** Écrire le texte d'une feuille CUE à partir d'une liste de pistes */
+export function writeCueSheet(sheet: CueSheetInput): string {
+  return requireWasm().write_cue_sheet(sheet);
 }
@@ -18,6 +18,16 @@ async function defaultInit(): Promise<MetadataWasmModule> {
   return {
     parse_metadata_with_limits: initModule.parse_metadata_with_limits,
     parse_metadata_batch: initModule.parse_metadata_batch,
+    parse_metadata_with_options: initModule.parse_metadata_with_options,
+    parse_metadata_batch_with_options:
+      initModule.parse_metadata_batch_with_options,
+    extract_cover_palette: initModule.extract_cover_palette,
+    extract_palette_rgba: initModule.extract_palette_rgba,
+    scan_mpeg_frames: initModule.scan_mpeg_frames,
+    inspect: initModule.inspect,
+    parse_cue_sheet: initModule.parse_cue_sheet,
+    write_cue_sheet: initModule.write_cue_sheet,
+    MetadataStreamParser: initModule.MetadataStreamParser,
   };
 }
 
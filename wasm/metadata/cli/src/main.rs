@@ -0,0 +1,471 @@
+// ---------------------------------------------------------------------------
+// Inspection en ligne de commande d'une bibliothèque musicale, avec le même
+// parseur que l'application
+// ---------------------------------------------------------------------------
+
+use std::collections::HashSet;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Component, Path, PathBuf};
+use std::process::ExitCode;
+
+use clap::{Parser, ValueEnum};
+use rta_metadata::model::Metadata;
+use rta_metadata::{Format, ParseOptions};
+use serde::Serialize;
+use walkdir::WalkDir;
+
+/// Extensions retenues lors du parcours d'un dossier
+const AUDIO_EXTENSIONS: [&str; 10] = [
+    "mp3", "mp2", "mp1", "flac", "ogg", "oga", "opus", "m4a", "mp4", "m4b",
+];
+
+/// Analyse les métadonnées de fichiers audio (FLAC, Ogg, MP3, M4A)
+#[derive(Parser)]
+#[command(version, about)]
+struct Args {
+    /// Fichiers ou dossiers à analyser (les dossiers sont parcourus récursivement)
+    #[arg(required = true)]
+    paths: Vec<PathBuf>,
+
+    /// Format de sortie
+    #[arg(short, long, value_enum, default_value_t = OutputFormat::Table)]
+    format: OutputFormat,
+
+    /// Enregistre les pochettes dans ce dossier, en reprenant l'arborescence analysée
+    /// (album/a.flac → album/a.flac.jpg)
+    #[arg(long, value_name = "DIR")]
+    extract_covers: Option<PathBuf>,
+
+    /// Enregistre les paroles (a.flac.txt) et paroles synchronisées (a.flac.lrc) dans ce dossier
+    #[arg(long, value_name = "DIR")]
+    extract_lyrics: Option<PathBuf>,
+
+    /// Code de sortie en échec si un fichier produit des avertissements d'analyse
+    #[arg(long)]
+    deny_warnings: bool,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum OutputFormat {
+    Table,
+    Json,
+}
+
+/// Résultat d'un fichier, tel qu'écrit en JSON
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Entry {
+    path: String,
+    format: &'static str,
+    #[serde(flatten)]
+    metadata: Metadata,
+}
+
+/// Fichier à analyser, avec son chemin relatif à la racine donnée en argument
+struct Source {
+    path: PathBuf,
+    relative: PathBuf,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    if run(&args, &mut io::stdout().lock()) {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+/// Analyse les fichiers et écrit le résultat dans `out` ; `false` si une étape a échoué
+fn run(args: &Args, out: &mut impl Write) -> bool {
+    let mut failed = false;
+
+    let mut options = ParseOptions {
+        include_cover_data: args.extract_covers.is_some(),
+        ..ParseOptions::default()
+    };
+
+    let extracting = args.extract_covers.is_some() || args.extract_lyrics.is_some();
+    let mut outputs = HashSet::new();
+    let mut entries = Vec::new();
+    for source in collect_sources(&args.paths, &mut failed) {
+        let bytes = match fs::read(&source.path) {
+            Ok(bytes) => bytes,
+            Err(err) => {
+                eprintln!("{} : {err}", source.path.display());
+                failed = true;
+                continue;
+            }
+        };
+        options.file_size = Some(bytes.len() as u64);
+        let mut metadata = match rta_metadata::parse(&bytes, &options) {
+            Ok(metadata) => metadata,
+            Err(err) => {
+                eprintln!("{} : {err}", source.path.display());
+                failed = true;
+                continue;
+            }
+        };
+
+        for warning in &metadata.warnings {
+            let offset = warning
+                .offset
+                .map_or_else(String::new, |offset| format!(" @{offset}"));
+            eprintln!(
+                "{} : [{}{offset}] {}",
+                source.path.display(),
+                warning.code,
+                warning.message
+            );
+        }
+        failed |= args.deny_warnings && !metadata.warnings.is_empty();
+
+        // Deux fichiers de même chemin relatif écraseraient les mêmes fichiers de sortie
+        if extracting && !outputs.insert(source.relative.clone()) {
+            eprintln!(
+                "{} : chemin de sortie {} déjà utilisé, extraction ignorée",
+                source.path.display(),
+                source.relative.display()
+            );
+            failed = true;
+        } else {
+            if let Some(dir) = &args.extract_covers {
+                failed |= !extract_cover(&metadata, dir, &source.relative);
+            }
+            if let Some(dir) = &args.extract_lyrics {
+                failed |= !extract_lyrics(&metadata, dir, &source.relative);
+            }
+        }
+
+        // `coverMime` suffit à signaler la pochette en JSON, sans ses octets
+        metadata.cover_data = None;
+        entries.push(Entry {
+            path: source.path.to_string_lossy().into_owned(),
+            format: Format::detect(&bytes).as_str(),
+            metadata,
+        });
+    }
+
+    let written = match args.format {
+        OutputFormat::Json => write_json(out, &entries),
+        OutputFormat::Table => write_table(out, &entries),
+    };
+    if let Err(err) = written {
+        eprintln!("{err}");
+        failed = true;
+    }
+
+    !failed
+}
+
+/// Fichiers désignés directement, puis fichiers audio des dossiers, par ordre de nom.
+/// Un fichier désigné directement garde ses dossiers parents (a/01.flac), sans racine ni « .. »,
+/// un fichier trouvé dans un dossier son chemin relatif à ce dossier.
+fn collect_sources(paths: &[PathBuf], failed: &mut bool) -> Vec<Source> {
+    let mut sources = Vec::new();
+    for root in paths {
+        if !root.is_dir() {
+            sources.push(Source {
+                path: root.clone(),
+                relative: root
+                    .components()
+                    .filter(|component| matches!(component, Component::Normal(_)))
+                    .collect(),
+            });
+            continue;
+        }
+        for item in WalkDir::new(root).sort_by_file_name() {
+            let item = match item {
+                Ok(item) => item,
+                Err(err) => {
+                    eprintln!("{err}");
+                    *failed = true;
+                    continue;
+                }
+            };
+            if !item.file_type().is_file() || !is_audio_file(item.path()) {
+                continue;
+            }
+            sources.push(Source {
+                relative: item
+                    .path()
+                    .strip_prefix(root)
+                    .unwrap_or(item.path())
+                    .to_path_buf(),
+                path: item.into_path(),
+            });
+        }
+    }
+    sources
+}
+
+fn is_audio_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| {
+            AUDIO_EXTENSIONS
+                .iter()
+                .any(|known| known.eq_ignore_ascii_case(ext))
+        })
+}
+
+/// Écrit la pochette principale ; `false` en cas d'erreur d'écriture
+fn extract_cover(metadata: &Metadata, dir: &Path, relative: &Path) -> bool {
+    let Some(data) = &metadata.cover_data else {
+        return true;
+    };
+    let extension = match metadata.cover_mime.as_deref() {
+        Some("image/jpeg") => "jpg",
+        Some("image/png") => "png",
+        Some("image/gif") => "gif",
+        Some("image/webp") => "webp",
+        Some("image/bmp") => "bmp",
+        _ => "bin",
+    };
+    write_output(&output_path(dir, relative, extension), data)
+}
+
+/// Écrit les paroles non synchronisées en .txt et les paroles LRC en .lrc
+fn extract_lyrics(metadata: &Metadata, dir: &Path, relative: &Path) -> bool {
+    let mut ok = true;
+    if let Some(text) = &metadata.lyrics.lyrics {
+        ok &= write_output(&output_path(dir, relative, "txt"), text.as_bytes());
+    }
+    if let Some(text) = &metadata.lyrics.synced_lyrics {
+        ok &= write_output(&output_path(dir, relative, "lrc"), text.as_bytes());
+    }
+    ok
+}
+
+/// Fichier de sortie : l'extension est ajoutée à celle du fichier audio, pour que
+/// a.flac et a.mp3 d'un même dossier ne s'écrasent pas
+fn output_path(dir: &Path, relative: &Path, extension: &str) -> PathBuf {
+    let mut path = dir.join(relative).into_os_string();
+    path.push(".");
+    path.push(extension);
+    PathBuf::from(path)
+}
+
+fn write_output(path: &Path, data: &[u8]) -> bool {
+    let written = path
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|()| fs::write(path, data));
+    if let Err(err) = &written {
+        eprintln!("{} : {err}", path.display());
+    }
+    written.is_ok()
+}
+
+fn write_json(out: &mut impl Write, entries: &[Entry]) -> io::Result<()> {
+    serde_json::to_writer_pretty(&mut *out, entries)?;
+    writeln!(out)
+}
+
+fn write_table(out: &mut impl Write, entries: &[Entry]) -> io::Result<()> {
+    const HEADER: [&str; 9] = [
+        "Fichier",
+        "Titre",
+        "Artiste",
+        "Album",
+        "Format",
+        "Fréquence",
+        "Bits",
+        "Durée",
+        "Pochette",
+    ];
+    let rows: Vec<[String; 9]> = entries
+        .iter()
+        .map(|entry| {
+            let metadata = &entry.metadata;
+            let audio = &metadata.audio;
+            [
+                entry.path.clone(),
+                text_cell(metadata.title.as_deref()),
+                text_cell(metadata.artist.as_deref()),
+                text_cell(metadata.album.as_deref()),
+                entry.format.to_string(),
+                audio
+                    .sample_rate
+                    .map_or_else(|| "-".to_string(), |rate| format!("{rate} Hz")),
+                audio
+                    .bit_depth
+                    .map_or_else(|| "-".to_string(), |bits| bits.to_string()),
+                audio
+                    .duration_ms
+                    .map_or_else(|| "-".to_string(), format_duration),
+                if metadata.cover_mime.is_some() {
+                    "oui"
+                } else {
+                    "non"
+                }
+                .to_string(),
+            ]
+        })
+        .collect();
+
+    let mut widths = HEADER.map(|title| title.chars().count());
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    write_row(out, &HEADER, &widths)?;
+    for row in &rows {
+        write_row(out, row, &widths)?;
+    }
+    Ok(())
+}
+
+fn write_row(out: &mut impl Write, cells: &[impl AsRef<str>], widths: &[usize]) -> io::Result<()> {
+    let line: Vec<String> = cells
+        .iter()
+        .zip(widths)
+        .map(|(cell, &width)| format!("{:width$}", cell.as_ref()))
+        .collect();
+    writeln!(out, "{}", line.join("  ").trim_end())
+}
+
+fn text_cell(value: Option<&str>) -> String {
+    value.unwrap_or("-").to_string()
+}
+
+/// `m:ss`, ou `h:mm:ss` au-delà d'une heure
+fn format_duration(ms: u64) -> String {
+    let seconds = ms / 1000;
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if hours > 0 {
+        format!("{hours}:{minutes:02}:{seconds:02}")
+    } else {
+        format!("{minutes}:{seconds:02}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Dossier temporaire vide, propre au test et au processus
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("rta-metadata-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Tag ID3v2.3 seul, avec une frame TIT2 qui annonce `frame_size` octets
+    fn id3_file(frame_size: u32) -> Vec<u8> {
+        let mut frame = b"TIT2".to_vec();
+        frame.extend_from_slice(&frame_size.to_be_bytes());
+        frame.extend_from_slice(&[0, 0, 0]);
+        frame.extend_from_slice(b"Title");
+        let mut bytes = b"ID3\x03\x00\x00\x00\x00\x00".to_vec();
+        bytes.push(frame.len() as u8);
+        bytes.extend(frame);
+        bytes
+    }
+
+    fn run_with(args: &[&str]) -> bool {
+        let args = Args::parse_from(std::iter::once("rta-metadata").chain(args.iter().copied()));
+        run(&args, &mut Vec::new())
+    }
+
+    fn relatives(sources: &[Source]) -> Vec<&Path> {
+        sources
+            .iter()
+            .map(|source| source.relative.as_path())
+            .collect()
+    }
+
+    #[test]
+    fn directories_keep_only_audio_files() {
+        let dir = temp_dir("collect");
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        for name in ["a.mp3", "B.FLAC", "cover.jpg", "notes.txt", "sub/c.opus"] {
+            fs::write(dir.join(name), b"").unwrap();
+        }
+        let mut failed = false;
+        let sources = collect_sources(std::slice::from_ref(&dir), &mut failed);
+        assert!(!failed);
+        assert_eq!(
+            relatives(&sources),
+            [
+                Path::new("B.FLAC"),
+                Path::new("a.mp3"),
+                Path::new("sub/c.opus")
+            ]
+        );
+        // Un fichier désigné directement est analysé quelle que soit son extension
+        let sources = collect_sources(&[dir.join("notes.txt")], &mut failed);
+        assert_eq!(sources.len(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn named_files_keep_their_parent_directories() {
+        let paths = ["a/01.flac", "b/01.flac", "../c/01.flac", "/d/01.flac"].map(PathBuf::from);
+        let sources = collect_sources(&paths, &mut false);
+        assert_eq!(
+            relatives(&sources),
+            ["a/01.flac", "b/01.flac", "c/01.flac", "d/01.flac"].map(Path::new)
+        );
+    }
+
+    #[test]
+    fn deny_warnings_sets_the_exit_status() {
+        let dir = temp_dir("warnings");
+        let file = dir.join("overrun.mp3");
+        // La frame annonce plus d'octets que le tag n'en contient
+        fs::write(&file, id3_file(100)).unwrap();
+        let file = file.to_str().unwrap();
+        assert!(run_with(&[file]));
+        assert!(!run_with(&["--deny-warnings", file]));
+
+        fs::write(dir.join("clean.mp3"), id3_file(6)).unwrap();
+        let clean = dir.join("clean.mp3");
+        assert!(run_with(&["--deny-warnings", clean.to_str().unwrap()]));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn colliding_outputs_fail_the_extraction() {
+        let dir = temp_dir("collision");
+        for album in ["a", "b"] {
+            fs::create_dir_all(dir.join(album)).unwrap();
+            fs::write(dir.join(album).join("01.mp3"), id3_file(6)).unwrap();
+        }
+        let out = dir.join("out");
+        let out = out.to_str().unwrap();
+        let path = |name: &str| dir.join(name).to_string_lossy().into_owned();
+        // Deux dossiers contenant chacun 01.mp3 : même chemin de sortie
+        assert!(!run_with(&[
+            "--extract-lyrics",
+            out,
+            &path("a"),
+            &path("b")
+        ]));
+        // Désignés directement, les fichiers gardent leur dossier parent
+        assert!(run_with(&[
+            "--extract-lyrics",
+            out,
+            &path("a/01.mp3"),
+            &path("b/01.mp3")
+        ]));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn outputs_keep_the_audio_extension() {
+        let dir = Path::new("out");
+        assert_eq!(
+            output_path(dir, Path::new("album/a.flac"), "jpg"),
+            Path::new("out/album/a.flac.jpg")
+        );
+        assert_ne!(
+            output_path(dir, Path::new("a.flac"), "txt"),
+            output_path(dir, Path::new("a.mp3"), "txt")
+        );
+    }
+}
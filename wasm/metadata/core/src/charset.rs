@@ -0,0 +1,441 @@
+// ---------------------------------------------------------------------------
+// Détection de l'encodage réel des textes déclarés ISO-8859-1
+// (frames ID3v2 d'encodage 0, tag ID3v1)
+// ---------------------------------------------------------------------------
+
+use encoding_rs::{EUC_KR, Encoding, GB18030, GBK, SHIFT_JIS, UTF_8, WINDOWS_1251, WINDOWS_1252};
+
+/// Encodages essayés, dans l'ordre de préférence en cas d'égalité
+const CANDIDATES: [&Encoding; 5] = [WINDOWS_1252, WINDOWS_1251, SHIFT_JIS, GBK, EUC_KR];
+
+/// Avance accordée à l'encodage de repli fourni par l'appelant
+const FALLBACK_BONUS: i32 = 2;
+
+/// Décodeur des chaînes « Latin-1 » : choisit pour chacune l'encodage le plus plausible
+/// et mémorise, pour le rapport, le volume de texte décodé par encodage.
+pub(crate) struct LegacyTextDecoder {
+    fallback: Option<&'static Encoding>,
+    /// Octets non ASCII décodés, par encodage retenu
+    usage: Vec<(&'static Encoding, usize)>,
+}
+
+impl LegacyTextDecoder {
+    pub(crate) fn new(fallback: Option<&'static Encoding>) -> Self {
+        Self {
+            fallback,
+            usage: Vec::new(),
+        }
+    }
+
+    pub(crate) fn decode(&mut self, bytes: &[u8]) -> String {
+        let non_ascii = bytes.iter().filter(|b| !b.is_ascii()).count();
+        if non_ascii == 0 {
+            return bytes.iter().map(|&b| b as char).collect();
+        }
+
+        let (encoding, text) = detect_encoding(bytes, self.fallback);
+        match self.usage.iter_mut().find(|(e, _)| *e == encoding) {
+            Some((_, count)) => *count += non_ascii,
+            None => self.usage.push((encoding, non_ascii)),
+        }
+        text
+    }
+
+    /// Décoder sans compter le texte dans le rapport, pour une simple comparaison
+    pub(crate) fn peek(&self, bytes: &[u8]) -> String {
+        if bytes.is_ascii() {
+            return bytes.iter().map(|&b| b as char).collect();
+        }
+        detect_encoding(bytes, self.fallback).1
+    }
+
+    /// Encodage ayant servi pour la plus grande partie du texte non ASCII
+    pub(crate) fn chosen_encoding(&self) -> Option<&'static str> {
+        self.usage
+            .iter()
+            .max_by_key(|(_, count)| *count)
+            .map(|(encoding, _)| encoding.name())
+    }
+}
+
+/// Retrouver un encodage à partir de son nom (« windows-1251 », « cp1251 », « sjis »…).
+/// `None` pour un nom inconnu : l'option qui le portait est alors sans effet.
+pub fn encoding_for_label(label: &str) -> Option<&'static Encoding> {
+    let label = label.trim().to_ascii_lowercase();
+    let label = match label.as_str() {
+        "cp1250" | "cp1251" | "cp1252" | "cp1253" | "cp1254" | "cp1257" => {
+            format!("windows-{}", &label[2..])
+        }
+        "sjis" | "cp932" => "shift_jis".to_string(),
+        "cp936" => "gbk".to_string(),
+        "cp949" => "euc-kr".to_string(),
+        _ => label,
+    };
+    Encoding::for_label(label.as_bytes())
+}
+
+/// UTF-8 valide d'abord, puis l'encodage candidat au meilleur score.
+/// L'encodage de repli l'emporte à égalité, et s'impose si aucun candidat n'est plausible.
+fn detect_encoding(
+    bytes: &[u8],
+    fallback: Option<&'static Encoding>,
+) -> (&'static Encoding, String) {
+    if let Ok(text) = std::str::from_utf8(bytes) {
+        return (UTF_8, text.to_string());
+    }
+
+    let candidates = fallback
+        .into_iter()
+        .chain(CANDIDATES.into_iter().filter(|&e| Some(e) != fallback));
+
+    let mut best: Option<(&'static Encoding, String, i32)> = None;
+    for encoding in candidates {
+        let Some(text) = encoding.decode_without_bom_handling_and_without_replacement(bytes) else {
+            continue;
+        };
+        let mut score = score_text(&text, encoding);
+        if Some(encoding) == fallback {
+            score += FALLBACK_BONUS;
+        }
+        if best
+            .as_ref()
+            .is_none_or(|(_, _, best_score)| score > *best_score)
+        {
+            best = Some((encoding, text.into_owned(), score));
+        }
+    }
+
+    match best {
+        Some((encoding, text, score)) if score > 0 || Some(encoding) == fallback => {
+            (encoding, text)
+        }
+        // Rien de plausible : on garde l'interprétation déclarée
+        _ => (
+            WINDOWS_1252,
+            WINDOWS_1252
+                .decode_without_bom_handling(bytes)
+                .0
+                .into_owned(),
+        ),
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum CharClass {
+    Other,
+    AsciiLetter,
+    Latin,
+    Cyrillic,
+    Kana,
+    Ideograph,
+    Hangul,
+}
+
+fn classify(c: char) -> CharClass {
+    match c {
+        'a'..='z' | 'A'..='Z' => CharClass::AsciiLetter,
+        '\u{C0}'..='\u{FF}' if c != '\u{D7}' && c != '\u{F7}' => CharClass::Latin,
+        '\u{100}'..='\u{24F}' => CharClass::Latin,
+        '\u{400}'..='\u{45F}' | '\u{490}' | '\u{491}' => CharClass::Cyrillic,
+        '\u{3040}'..='\u{30FF}' => CharClass::Kana,
+        '\u{4E00}'..='\u{9FFF}' => CharClass::Ideograph,
+        '\u{AC00}'..='\u{D7A3}' => CharClass::Hangul,
+        _ => CharClass::Other,
+    }
+}
+
+/// Plausibilité d'un texte décodé : lettres cohérentes avec leur voisinage positives,
+/// symboles rares, mélanges d'écritures au sein d'un mot et caractères de contrôle négatifs.
+/// Les barèmes sont exprimés par octet source pour comparer encodages simples et doubles.
+fn score_text(text: &str, encoding: &'static Encoding) -> i32 {
+    let mut score = 0;
+    let mut prev = CharClass::Other;
+    let mut prev_lower_cyrillic = false;
+    let mut upper_cyrillic_run = 0;
+    // Lettres et voyelles du mot cyrillique en cours
+    let mut word_letters = 0;
+    let mut word_vowels = 0;
+    // Classe du caractère qui précède l'espace précédent, si le caractère précédent en est un
+    let mut before_space = None;
+
+    for c in text.chars() {
+        let class = classify(c);
+        let is_cjk = matches!(
+            class,
+            CharClass::Kana | CharClass::Ideograph | CharClass::Hangul
+        );
+        let prev_is_cjk = matches!(
+            prev,
+            CharClass::Kana | CharClass::Ideograph | CharClass::Hangul
+        );
+
+        if class != CharClass::Cyrillic {
+            // Un mot cyrillique de quatre lettres sans voyelle trahit un mauvais décodage
+            if word_letters >= 4 && word_vowels == 0 {
+                score -= 2 * word_letters;
+            }
+            word_letters = 0;
+            word_vowels = 0;
+        }
+
+        score += match class {
+            CharClass::AsciiLetter if prev_is_cjk => -2,
+            CharClass::AsciiLetter if prev == CharClass::Cyrillic => -3,
+            CharClass::AsciiLetter | CharClass::Other if c.is_ascii() => 0,
+            // Une lettre accentuée isolée dans un mot latin est normale, une suite l'est moins
+            CharClass::Latin => match prev {
+                CharClass::AsciiLetter => 2,
+                CharClass::Latin => 0,
+                CharClass::Cyrillic => -1,
+                _ => 1,
+            },
+            CharClass::Cyrillic => {
+                word_letters += 1;
+                if CYRILLIC_VOWELS.contains(c.to_lowercase().next().unwrap_or(c)) {
+                    word_vowels += 1;
+                }
+                let mut value = if prev == CharClass::AsciiLetter {
+                    -3
+                } else if is_rare_cyrillic(c) {
+                    -2
+                } else {
+                    2
+                };
+                // Casse incohérente : « пРИВЕТ », « ПРИвет »
+                if (c.is_uppercase() && prev_lower_cyrillic)
+                    || (c.is_lowercase() && upper_cyrillic_run >= 2)
+                {
+                    value -= 2;
+                }
+                value
+            }
+            _ if is_cjk => {
+                let mut value = score_cjk(c, class, encoding);
+                if prev == CharClass::AsciiLetter {
+                    value -= 2;
+                }
+                // Le coréen moderne ne mêle presque plus hangul et hanja
+                if (class == CharClass::Hangul && prev == CharClass::Ideograph)
+                    || (class == CharClass::Ideograph && prev == CharClass::Hangul)
+                {
+                    value -= 2;
+                }
+                // Le chinois et le japonais n'espacent pas les mots, le coréen si
+                if class == CharClass::Ideograph && before_space == Some(CharClass::Ideograph) {
+                    value -= 2;
+                }
+                value
+            }
+            _ => score_symbol(c),
+        };
+        prev_lower_cyrillic = class == CharClass::Cyrillic && c.is_lowercase();
+        upper_cyrillic_run = if class == CharClass::Cyrillic && c.is_uppercase() {
+            upper_cyrillic_run + 1
+        } else {
+            0
+        };
+        before_space = (c == ' ').then_some(prev);
+        prev = class;
+    }
+    if word_letters >= 4 && word_vowels == 0 {
+        score -= 2 * word_letters;
+    }
+    score
+}
+
+const CYRILLIC_VOWELS: &str = "аеёиоуыэюяіїєў";
+
+/// Lettres cyrilliques hors russe / ukrainien / biélorusse (serbe, macédonien…)
+fn is_rare_cyrillic(c: char) -> bool {
+    matches!(c, '\u{400}'..='\u{40F}' | '\u{450}'..='\u{45F}')
+        && !matches!(c, 'Ё' | 'ё' | 'Є' | 'є' | 'І' | 'і' | 'Ї' | 'ї' | 'Ў' | 'ў')
+}
+
+/// Score d'un caractère CJK d'après sa classe de fréquence dans l'encodage :
+/// les premiers niveaux (GB2312 niveau 1, JIS X 0208 niveau 1, hangul KS X 1001)
+/// regroupent les caractères courants, les extensions rarement utilisées n'apportent rien.
+/// Les kana ne sont attendus qu'en japonais.
+fn score_cjk(c: char, class: CharClass, encoding: &'static Encoding) -> i32 {
+    let mut buffer = [0u8; 4];
+    let (encoded, _, _) = encoding.encode(c.encode_utf8(&mut buffer));
+    let code = match *encoded {
+        [lead, trail] => u16::from_be_bytes([lead, trail]),
+        _ => 0,
+    };
+
+    if encoding == SHIFT_JIS {
+        match (class, code) {
+            (CharClass::Kana, _) => 4,
+            (_, 0x889F..=0x9872) => 4,
+            (_, 0x989F..=0xEAA4) => 2,
+            _ => 0,
+        }
+    } else if class == CharClass::Kana {
+        1
+    } else if encoding == GBK || encoding == GB18030 {
+        match code {
+            _ if code & 0xFF < 0xA1 => 0,
+            0xB0A1..=0xD7FE => 4,
+            0xD8A1..=0xF7FE => 2,
+            _ => 0,
+        }
+    } else if encoding == EUC_KR {
+        match class {
+            // Hangul KS X 1001 ; les syllabes ajoutées par l'extension Windows (CP949) sont rares
+            CharClass::Hangul if code & 0xFF >= 0xA1 => score_hangul(c),
+            CharClass::Hangul => 0,
+            // Les hanja sont devenus rares dans le coréen moderne
+            _ => -1,
+        }
+    } else {
+        3
+    }
+}
+
+/// Classe de fréquence d'une syllabe hangul d'après ses jamos. Le coréen réel est fait
+/// pour moitié de syllabes ouvertes et pour un tiers de syllabes à voyelle simple fermées
+/// par ㄴ, ㄹ, ㅁ ou ㅇ, alors que ces deux classes ne forment qu'un quart des syllabes
+/// de KS X 1001. Du texte coréen dépasse ainsi les 4 points d'un hanzi courant, et des
+/// octets chinois décodés en EUC-KR, qui tombent au hasard dans le jeu, restent en deçà.
+fn score_hangul(c: char) -> i32 {
+    let index = c as u32 - 0xAC00;
+    let initial = index / (21 * 28);
+    let medial = index / 28 % 21;
+    let last = index % 28;
+    // ㄲ, ㄸ, ㅃ, ㅆ, ㅉ
+    let tense_initial = matches!(initial, 1 | 4 | 8 | 10 | 13);
+    // ㅏ, ㅐ, ㅓ, ㅕ, ㅗ, ㅜ, ㅡ, ㅣ
+    let simple_medial = matches!(medial, 0 | 1 | 4 | 6 | 8 | 13 | 18 | 20);
+    match last {
+        0 if !tense_initial => 6,
+        0 => 5,
+        // ㄴ, ㄹ, ㅁ, ㅇ
+        4 | 8 | 16 | 21 if simple_medial && !tense_initial => 5,
+        _ => 2,
+    }
+}
+
+fn score_symbol(c: char) -> i32 {
+    match c {
+        // Caractères de contrôle, zone privée, demi-chasse : signes d'un mauvais décodage
+        '\u{80}'..='\u{9F}' | '\u{E000}'..='\u{F8FF}' | '\u{FFFD}' => -10,
+        c if c.is_control() => -10,
+        '\u{FF61}'..='\u{FF9F}' => -1,
+        // Ponctuation typographique courante dans les titres
+        '\u{2018}'..='\u{201E}'
+        | '\u{2013}'
+        | '\u{2014}'
+        | '\u{2026}'
+        | '\u{AB}'
+        | '\u{BB}'
+        | '\u{B7}' => 0,
+        // Autres écritures (grec, latin étendu…) : possibles avec un encodage de repli
+        c if c.is_alphabetic() => 1,
+        // Ponctuation et formes pleine chasse CJK
+        '\u{3000}'..='\u{303F}' | '\u{FF01}'..='\u{FF5E}' => 1,
+        _ => -2,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Encodage retenu pour `text` encodé en `encoding`, et texte décodé
+    fn roundtrip(text: &str, encoding: &'static Encoding) -> (&'static str, String) {
+        let (bytes, _, unmappable) = encoding.encode(text);
+        assert!(
+            !unmappable,
+            "{text} is not representable in {}",
+            encoding.name()
+        );
+        let (detected, decoded) = detect_encoding(&bytes, None);
+        (detected.name(), decoded)
+    }
+
+    fn assert_detected(samples: &[&str], encoding: &'static Encoding) {
+        for text in samples {
+            assert_eq!(
+                roundtrip(text, encoding),
+                (encoding.name(), text.to_string()),
+                "{text}"
+            );
+        }
+    }
+
+    #[test]
+    fn detects_cp1251() {
+        assert_detected(
+            &[
+                "Группа крови",
+                "Звезда по имени Солнце",
+                "Пачка сигарет",
+                "Ой, у лузі червона калина",
+            ],
+            WINDOWS_1251,
+        );
+    }
+
+    #[test]
+    fn detects_cp1252() {
+        assert_detected(&["Café del Mar", "Noël à Paris", "Für Elise"], WINDOWS_1252);
+    }
+
+    #[test]
+    fn detects_shift_jis() {
+        assert_detected(
+            &["残酷な天使のテーゼ", "春よ、来い", "夜に駆ける", "東京事変"],
+            SHIFT_JIS,
+        );
+    }
+
+    #[test]
+    fn detects_gbk() {
+        assert_detected(&["月亮代表我的心", "童话", "后来", "甜蜜蜜"], GBK);
+    }
+
+    #[test]
+    fn detects_euc_kr() {
+        assert_detected(
+            &[
+                "사랑했지만",
+                "좋은 날",
+                "밤편지",
+                "내 마음 속의 노래",
+                "바람이 분다",
+            ],
+            EUC_KR,
+        );
+    }
+
+    #[test]
+    fn keeps_valid_utf8() {
+        for text in [
+            "Ágætis byrjun",
+            "Группа крови",
+            "残酷な天使のテーゼ",
+            "밤편지",
+        ] {
+            assert_eq!(
+                detect_encoding(text.as_bytes(), None),
+                (UTF_8, text.to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn fallback_wins_ties() {
+        // Deux octets valides en GBK comme en EUC-KR
+        let (bytes, _, _) = EUC_KR.encode("가");
+        assert_eq!(detect_encoding(&bytes, Some(EUC_KR)).0, EUC_KR);
+        assert_eq!(detect_encoding(&bytes, Some(GBK)).0, GBK);
+    }
+
+    #[test]
+    fn resolves_code_page_labels() {
+        assert_eq!(encoding_for_label(" CP1251 "), Some(WINDOWS_1251));
+        assert_eq!(encoding_for_label("sjis"), Some(SHIFT_JIS));
+        assert_eq!(encoding_for_label("cp949"), Some(EUC_KR));
+        assert_eq!(encoding_for_label("klingon"), None);
+    }
+}
@@ -0,0 +1,522 @@
+// ---------------------------------------------------------------------------
+// Feuilles de montage (cue sheets) : bloc FLAC CUESHEET et texte CUE
+// ---------------------------------------------------------------------------
+
+use std::fmt::Write;
+
+use crate::be_u32;
+
+/// Taille fixe de l'en-tête d'un bloc CUESHEET : catalogue (128), lead-in (8),
+/// drapeau CD + réservé (259), nombre de pistes (1)
+const CUESHEET_HEADER_SIZE: usize = 396;
+/// Taille d'une piste du bloc CUESHEET, hors points d'index
+const CUESHEET_TRACK_SIZE: usize = 36;
+const CUESHEET_INDEX_SIZE: usize = 12;
+/// Numéro de la piste de fin (lead-out) d'un CD, 255 pour les autres sources
+const CD_LEAD_OUT_TRACK: u8 = 170;
+const LEAD_OUT_TRACK: u8 = 255;
+/// Trames CD par seconde, unité des positions MM:SS:FF
+const CD_FRAMES_PER_SECOND: u64 = 75;
+/// Taux d'échantillonnage supposé des positions d'une feuille sans STREAMINFO
+pub const CD_SAMPLE_RATE: u32 = 44100;
+
+#[derive(Default)]
+pub struct CueSheet {
+    pub catalog: Option<String>,
+    pub title: Option<String>,
+    pub performer: Option<String>,
+    /// Premier fichier référencé par la feuille
+    pub file: Option<String>,
+    /// Commentaires REM courants (EAC, foobar2000)
+    pub genre: Option<String>,
+    pub date: Option<String>,
+    pub comment: Option<String>,
+    /// ReplayGain de l'album, en dB et en amplitude crête
+    pub album_gain: Option<f32>,
+    pub album_peak: Option<f32>,
+    /// Échantillons de lead-in d'un CD (bloc FLAC uniquement)
+    pub lead_in: Option<u64>,
+    pub is_cd: bool,
+    /// Taux d'échantillonnage des positions, nécessaire à leur conversion en millisecondes
+    pub sample_rate: u32,
+    /// Position de fin du dernier morceau, si la feuille la donne
+    pub lead_out: Option<u64>,
+    pub tracks: Vec<CueTrack>,
+}
+
+#[derive(Default)]
+pub struct CueTrack {
+    pub number: u8,
+    pub isrc: Option<String>,
+    pub title: Option<String>,
+    pub performer: Option<String>,
+    /// Fichier contenant la piste ; les positions sont relatives à son début
+    pub file: Option<String>,
+    pub is_audio: bool,
+    pub pre_emphasis: bool,
+    /// Drapeaux FLAGS autres que PRE (DCP, 4CH, SCMS)
+    pub flags: Vec<String>,
+    pub track_gain: Option<f32>,
+    pub track_peak: Option<f32>,
+    pub indices: Vec<CueIndex>,
+}
+
+/// Point d'index d'une piste ; 0 marque le début du pre-gap, 1 le début du morceau
+pub struct CueIndex {
+    pub number: u8,
+    /// Position en échantillons depuis le début du flux
+    pub offset: u64,
+}
+
+impl CueSheet {
+    pub fn samples_to_ms(&self, samples: u64) -> u64 {
+        samples.saturating_mul(1000) / self.sample_rate.max(1) as u64
+    }
+
+    pub fn ms_to_samples(&self, ms: f64) -> u64 {
+        (ms * self.sample_rate as f64 / 1000.0).round().max(0.0) as u64
+    }
+
+    /// Fin de la piste `index` : début de la suivante si elle est dans le même fichier,
+    /// lead-out ou `total_samples` pour la dernière, inconnue sinon
+    pub(crate) fn track_end(&self, index: usize, total_samples: Option<u64>) -> Option<u64> {
+        match self.tracks.get(index + 1) {
+            Some(next) if next.file == self.tracks[index].file => next.start(),
+            Some(_) => None,
+            None => self.lead_out.or(total_samples.filter(|&total| total > 0)),
+        }
+    }
+}
+
+impl CueTrack {
+    /// Début du morceau : index 01, à défaut le premier index
+    pub(crate) fn start(&self) -> Option<u64> {
+        self.index(1)
+            .or_else(|| self.indices.first().map(|index| index.offset))
+    }
+
+    /// Durée du pre-gap (index 00 → index 01), en échantillons
+    pub(crate) fn pre_gap(&self) -> Option<u64> {
+        let gap_start = self.index(0)?;
+        Some(self.index(1)?.saturating_sub(gap_start))
+    }
+
+    fn index(&self, number: u8) -> Option<u64> {
+        self.indices
+            .iter()
+            .find(|index| index.number == number)
+            .map(|index| index.offset)
+    }
+}
+
+/// Analyser un bloc de métadonnées FLAC CUESHEET (type 5).
+/// Les positions des index sont relatives à leur piste, elles-mêmes relatives au début du flux ;
+/// un bloc dont la somme dépasse 64 bits est rejeté.
+pub(crate) fn parse_flac_cuesheet(block: &[u8], sample_rate: u32) -> Option<CueSheet> {
+    let header = block.get(..CUESHEET_HEADER_SIZE)?;
+    let catalog = ascii_field(&header[0..128]);
+    let lead_in = be_u64(&header[128..136]);
+    let is_cd = header[136] & 0x80 != 0;
+    let track_count = header[395] as usize;
+
+    let mut sheet = CueSheet {
+        catalog,
+        lead_in: (lead_in > 0).then_some(lead_in),
+        is_cd,
+        sample_rate,
+        ..CueSheet::default()
+    };
+
+    let mut offset = CUESHEET_HEADER_SIZE;
+    for _ in 0..track_count {
+        let track = block.get(offset..offset + CUESHEET_TRACK_SIZE)?;
+        let track_offset = be_u64(&track[0..8]);
+        let number = track[8];
+        let index_count = track[35] as usize;
+        offset += CUESHEET_TRACK_SIZE;
+
+        if number == CD_LEAD_OUT_TRACK || number == LEAD_OUT_TRACK {
+            sheet.lead_out = Some(track_offset);
+            offset += index_count * CUESHEET_INDEX_SIZE;
+            continue;
+        }
+
+        let mut indices = Vec::with_capacity(index_count);
+        for _ in 0..index_count {
+            let index = block.get(offset..offset + CUESHEET_INDEX_SIZE)?;
+            indices.push(CueIndex {
+                number: index[8],
+                offset: track_offset.checked_add(be_u64(&index[0..8]))?,
+            });
+            offset += CUESHEET_INDEX_SIZE;
+        }
+
+        sheet.tracks.push(CueTrack {
+            number,
+            isrc: ascii_field(&track[9..21]),
+            // Bit de type : 0 = audio, 1 = données
+            is_audio: track[21] & 0x80 == 0,
+            pre_emphasis: track[21] & 0x40 != 0,
+            indices,
+            ..CueTrack::default()
+        });
+    }
+
+    (!sheet.tracks.is_empty()).then_some(sheet)
+}
+
+/// Analyser une feuille CUE au format texte (fichier .cue ou commentaire Vorbis CUESHEET).
+/// Les positions MM:SS:FF sont converties en échantillons au taux `sample_rate`.
+pub(crate) fn parse_cue_text(text: &str, sample_rate: u32) -> Option<CueSheet> {
+    let mut sheet = CueSheet {
+        sample_rate,
+        ..CueSheet::default()
+    };
+    let mut current_file: Option<String> = None;
+
+    for line in text.lines() {
+        let tokens = tokenize_cue_line(line);
+        let Some((command, args)) = tokens.split_first() else {
+            continue;
+        };
+        let first_arg = args.first().cloned();
+        let track = sheet.tracks.last_mut();
+
+        match (command.to_ascii_uppercase().as_str(), track) {
+            ("REM", track) => {
+                let Some((key, value)) = args.split_first() else {
+                    continue;
+                };
+                let value = value.join(" ");
+                let gain = || value.split_whitespace().next()?.parse::<f32>().ok();
+                match (key.to_ascii_uppercase().as_str(), track) {
+                    ("GENRE", None) => sheet.genre = Some(value),
+                    ("DATE", None) => sheet.date = Some(value),
+                    ("COMMENT", None) => sheet.comment = Some(value),
+                    ("REPLAYGAIN_ALBUM_GAIN", _) => sheet.album_gain = gain(),
+                    ("REPLAYGAIN_ALBUM_PEAK", _) => sheet.album_peak = gain(),
+                    ("REPLAYGAIN_TRACK_GAIN", Some(track)) => track.track_gain = gain(),
+                    ("REPLAYGAIN_TRACK_PEAK", Some(track)) => track.track_peak = gain(),
+                    _ => {}
+                }
+            }
+            ("CATALOG", _) => sheet.catalog = first_arg,
+            ("FILE", _) => {
+                if sheet.file.is_none() {
+                    sheet.file = first_arg.clone();
+                }
+                current_file = first_arg;
+            }
+            ("TITLE", None) => sheet.title = first_arg,
+            ("TITLE", Some(track)) => track.title = first_arg,
+            ("PERFORMER", None) => sheet.performer = first_arg,
+            ("PERFORMER", Some(track)) => track.performer = first_arg,
+            ("TRACK", _) => {
+                let Some(number) = first_arg.and_then(|n| n.parse::<u8>().ok()) else {
+                    continue;
+                };
+                let is_audio = args
+                    .get(1)
+                    .is_none_or(|kind| kind.eq_ignore_ascii_case("AUDIO"));
+                sheet.tracks.push(CueTrack {
+                    number,
+                    file: current_file.clone(),
+                    is_audio,
+                    ..CueTrack::default()
+                });
+            }
+            ("ISRC", Some(track)) => track.isrc = first_arg,
+            ("FLAGS", Some(track)) => {
+                for flag in args {
+                    if flag.eq_ignore_ascii_case("PRE") {
+                        track.pre_emphasis = true;
+                    } else {
+                        track.flags.push(flag.to_ascii_uppercase());
+                    }
+                }
+            }
+            ("INDEX", Some(track)) => {
+                let number = first_arg.and_then(|n| n.parse::<u8>().ok());
+                let offset = args
+                    .get(1)
+                    .and_then(|time| parse_cue_time(time))
+                    .and_then(|frames| frames.checked_mul(sample_rate as u64))
+                    .map(|samples| samples / CD_FRAMES_PER_SECOND);
+                if let (Some(number), Some(offset)) = (number, offset) {
+                    track.indices.push(CueIndex { number, offset });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    (!sheet.tracks.is_empty()).then_some(sheet)
+}
+
+/// Écrire une feuille CUE texte. Les positions sont arrondies à la trame CD (1/75 s).
+pub(crate) fn write_cue_text(sheet: &CueSheet) -> String {
+    let mut out = String::new();
+    let mut line = |indent: usize, text: String| {
+        let _ = write!(out, "{:indent$}{text}\r\n", "");
+    };
+
+    if let Some(genre) = &sheet.genre {
+        line(0, format!("REM GENRE {}", quote_if_needed(genre)));
+    }
+    if let Some(date) = &sheet.date {
+        line(0, format!("REM DATE {date}"));
+    }
+    if let Some(comment) = &sheet.comment {
+        line(0, format!("REM COMMENT {}", quote(comment)));
+    }
+    if let Some(gain) = sheet.album_gain {
+        line(0, format!("REM REPLAYGAIN_ALBUM_GAIN {gain:.2} dB"));
+    }
+    if let Some(peak) = sheet.album_peak {
+        line(0, format!("REM REPLAYGAIN_ALBUM_PEAK {peak:.6}"));
+    }
+    if let Some(catalog) = &sheet.catalog {
+        line(0, format!("CATALOG {catalog}"));
+    }
+    if let Some(performer) = &sheet.performer {
+        line(0, format!("PERFORMER {}", quote(performer)));
+    }
+    if let Some(title) = &sheet.title {
+        line(0, format!("TITLE {}", quote(title)));
+    }
+
+    let mut current_file: Option<&String> = None;
+    for (position, track) in sheet.tracks.iter().enumerate() {
+        let file = track.file.as_ref().or(sheet.file.as_ref());
+        if let Some(file) = file
+            && current_file != Some(file)
+        {
+            line(0, format!("FILE {} {}", quote(file), cue_file_type(file)));
+            current_file = Some(file);
+        }
+        let number = if track.number > 0 {
+            track.number as usize
+        } else {
+            position + 1
+        };
+        let kind = if track.is_audio {
+            "AUDIO"
+        } else {
+            "MODE1/2352"
+        };
+        line(2, format!("TRACK {number:02} {kind}"));
+        if let Some(title) = &track.title {
+            line(4, format!("TITLE {}", quote(title)));
+        }
+        if let Some(performer) = &track.performer {
+            line(4, format!("PERFORMER {}", quote(performer)));
+        }
+        if let Some(isrc) = &track.isrc {
+            line(4, format!("ISRC {isrc}"));
+        }
+        let mut flags = track.flags.clone();
+        if track.pre_emphasis {
+            flags.push("PRE".to_string());
+        }
+        if !flags.is_empty() {
+            line(4, format!("FLAGS {}", flags.join(" ")));
+        }
+        if let Some(gain) = track.track_gain {
+            line(4, format!("REM REPLAYGAIN_TRACK_GAIN {gain:.2} dB"));
+        }
+        if let Some(peak) = track.track_peak {
+            line(4, format!("REM REPLAYGAIN_TRACK_PEAK {peak:.6}"));
+        }
+        for index in &track.indices {
+            let frames = index
+                .offset
+                .saturating_mul(CD_FRAMES_PER_SECOND * 2)
+                .saturating_add(sheet.sample_rate as u64)
+                / (2 * sheet.sample_rate.max(1) as u64);
+            line(
+                4,
+                format!("INDEX {:02} {}", index.number, format_cue_time(frames)),
+            );
+        }
+    }
+    out
+}
+
+/// Type déclaré après le nom de fichier : WAVE pour tout fichier audio, MP3 et AIFF à part
+fn cue_file_type(file: &str) -> &'static str {
+    let extension = file.rsplit('.').next().unwrap_or_default();
+    if extension.eq_ignore_ascii_case("mp3") {
+        "MP3"
+    } else if extension.eq_ignore_ascii_case("aif") || extension.eq_ignore_ascii_case("aiff") {
+        "AIFF"
+    } else {
+        "WAVE"
+    }
+}
+
+/// Les guillemets ne pouvant pas être échappés en CUE, ils sont remplacés par des apostrophes
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('"', "'"))
+}
+
+fn quote_if_needed(value: &str) -> String {
+    if value.contains(char::is_whitespace) || value.is_empty() {
+        quote(value)
+    } else {
+        value.to_string()
+    }
+}
+
+fn format_cue_time(frames: u64) -> String {
+    let seconds = frames / CD_FRAMES_PER_SECOND;
+    format!(
+        "{:02}:{:02}:{:02}",
+        seconds / 60,
+        seconds % 60,
+        frames % CD_FRAMES_PER_SECOND
+    )
+}
+
+/// Découper une ligne CUE en mots ; les chaînes entre guillemets forment un seul mot
+fn tokenize_cue_line(line: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut chars = line.trim().chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '"' {
+            chars.next();
+            tokens.push(chars.by_ref().take_while(|&c| c != '"').collect());
+        } else {
+            let mut token = String::new();
+            while let Some(&c) = chars.peek() {
+                if c.is_whitespace() {
+                    break;
+                }
+                token.push(c);
+                chars.next();
+            }
+            tokens.push(token);
+        }
+    }
+    tokens
+}
+
+/// Position MM:SS:FF en trames CD (75 par seconde)
+fn parse_cue_time(time: &str) -> Option<u64> {
+    let mut parts = time.split(':').map(|part| part.parse::<u64>().ok());
+    let (minutes, seconds, frames) = (parts.next()??, parts.next()??, parts.next()??);
+    if parts.next().is_some() || seconds >= 60 || frames >= CD_FRAMES_PER_SECOND {
+        return None;
+    }
+    minutes
+        .checked_mul(60)?
+        .checked_add(seconds)?
+        .checked_mul(CD_FRAMES_PER_SECOND)?
+        .checked_add(frames)
+}
+
+/// Champ ASCII de longueur fixe complété par des zéros
+fn ascii_field(bytes: &[u8]) -> Option<String> {
+    let value: String = bytes
+        .iter()
+        .take_while(|&&b| b != 0)
+        .filter(|b| b.is_ascii_graphic() || **b == b' ')
+        .map(|&b| b as char)
+        .collect();
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+fn be_u64(bytes: &[u8]) -> u64 {
+    ((be_u32(&bytes[0..4]) as u64) << 32) | be_u32(&bytes[4..8]) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Bloc CUESHEET d'une piste avec un index, et son lead-out
+    fn flac_cuesheet(track_offset: u64, index_offset: u64) -> Vec<u8> {
+        let mut block = vec![0; CUESHEET_HEADER_SIZE];
+        block[395] = 2;
+        let mut track = [0; CUESHEET_TRACK_SIZE];
+        track[0..8].copy_from_slice(&track_offset.to_be_bytes());
+        track[8] = 1;
+        track[35] = 1;
+        block.extend_from_slice(&track);
+        let mut index = [0; CUESHEET_INDEX_SIZE];
+        index[0..8].copy_from_slice(&index_offset.to_be_bytes());
+        index[8] = 1;
+        block.extend_from_slice(&index);
+        let mut lead_out = [0; CUESHEET_TRACK_SIZE];
+        lead_out[8] = LEAD_OUT_TRACK;
+        block.extend_from_slice(&lead_out);
+        block
+    }
+
+    #[test]
+    fn flac_index_is_relative_to_its_track() {
+        let sheet = parse_flac_cuesheet(&flac_cuesheet(44100, 588), CD_SAMPLE_RATE).unwrap();
+        assert_eq!(sheet.tracks[0].start(), Some(44688));
+        assert_eq!(sheet.samples_to_ms(44100), 1000);
+    }
+
+    #[test]
+    fn overflowing_positions_are_rejected() {
+        assert!(parse_flac_cuesheet(&flac_cuesheet(u64::MAX, 1), CD_SAMPLE_RATE).is_none());
+        assert!(parse_cue_time(&format!("{}:00:00", u64::MAX / 60)).is_none());
+        let sheet = parse_flac_cuesheet(&flac_cuesheet(u64::MAX, 0), CD_SAMPLE_RATE).unwrap();
+        assert_eq!(
+            sheet.samples_to_ms(u64::MAX),
+            u64::MAX / CD_SAMPLE_RATE as u64
+        );
+        assert!(write_cue_text(&sheet).contains("INDEX 01"));
+    }
+
+    #[test]
+    fn track_ends_where_the_next_track_of_its_file_starts() {
+        let text = "FILE \"a.flac\" WAVE\n  TRACK 01 AUDIO\n    INDEX 01 00:00:00\n  \
+                    TRACK 02 AUDIO\n    INDEX 01 01:00:00\n\
+                    FILE \"b.flac\" WAVE\n  TRACK 03 AUDIO\n    INDEX 01 00:00:00\n";
+        let sheet = parse_cue_text(text, CD_SAMPLE_RATE).unwrap();
+        assert_eq!(sheet.track_end(0, None), Some(60 * CD_SAMPLE_RATE as u64));
+        assert_eq!(sheet.track_end(1, Some(1)), None);
+        assert_eq!(sheet.track_end(2, Some(1)), Some(1));
+    }
+
+    #[test]
+    fn written_sheet_reads_back_identically() {
+        let text = "REM GENRE Rock\r\n\
+                    REM DATE 1999\r\n\
+                    REM COMMENT \"ExactAudioCopy v1.6\"\r\n\
+                    REM REPLAYGAIN_ALBUM_GAIN -7.50 dB\r\n\
+                    REM REPLAYGAIN_ALBUM_PEAK 0.987654\r\n\
+                    CATALOG 0724384260958\r\n\
+                    PERFORMER \"The Band\"\r\n\
+                    TITLE \"Live 'At' Home\"\r\n\
+                    FILE \"CD1.flac\" WAVE\r\n  \
+                      TRACK 01 AUDIO\r\n    \
+                        TITLE \"Intro\"\r\n    \
+                        ISRC USABC9900001\r\n    \
+                        FLAGS DCP PRE\r\n    \
+                        REM REPLAYGAIN_TRACK_GAIN -6.20 dB\r\n    \
+                        INDEX 01 00:00:00\r\n  \
+                      TRACK 02 AUDIO\r\n    \
+                        TITLE \"Song\"\r\n    \
+                        PERFORMER \"Guest\"\r\n    \
+                        INDEX 00 03:58:40\r\n    \
+                        INDEX 01 04:01:00\r\n\
+                    FILE \"bonus.mp3\" MP3\r\n  \
+                      TRACK 03 AUDIO\r\n    \
+                        INDEX 01 00:00:00\r\n";
+        let sheet = parse_cue_text(text, CD_SAMPLE_RATE).unwrap();
+        assert_eq!(sheet.tracks.len(), 3);
+        assert_eq!(
+            sheet.tracks[1].pre_gap(),
+            Some(2 * CD_SAMPLE_RATE as u64 + 35 * 588)
+        );
+        assert_eq!(sheet.tracks[2].file.as_deref(), Some("bonus.mp3"));
+        assert_eq!(write_cue_text(&sheet), text);
+    }
+}
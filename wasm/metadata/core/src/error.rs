@@ -0,0 +1,30 @@
+// ---------------------------------------------------------------------------
+// Erreurs renvoyées par l'API publique
+// ---------------------------------------------------------------------------
+
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum Error {
+    /// Ni signature FLAC / Ogg, ni tag, ni trame MPEG dans les octets fournis
+    #[error("no FLAC, Ogg, MP4, ID3 or MPEG audio data found")]
+    UnrecognizedFormat,
+    /// Feuille CUE sans aucune piste exploitable
+    #[error("cue sheet contains no track")]
+    InvalidCueSheet,
+    /// Analyse incrémentale terminée avant d'avoir reçu toutes les plages demandées
+    #[error("bytes {start}..{end} were requested but not supplied")]
+    MissingBytes { start: u64, end: u64 },
+    #[error("image could not be decoded")]
+    UndecodableImage,
+    /// Image entièrement transparente : aucune couleur à retenir
+    #[error("image has no opaque pixel")]
+    TransparentImage,
+    #[error("{length} bytes is not a {width}x{height} RGBA buffer")]
+    InvalidPixelBuffer {
+        width: u32,
+        height: u32,
+        length: usize,
+    },
+}
@@ -0,0 +1,558 @@
+// ---------------------------------------------------------------------------
+// Reconnaissance des formats d'image à partir de leurs en-têtes
+// ---------------------------------------------------------------------------
+
+use crate::{be_u16, be_u32, le_u16, le_u32};
+
+const PNG_SIGNATURE: &[u8; 8] = b"\x89PNG\r\n\x1a\n";
+
+/// Informations lues dans l'en-tête d'une image, indépendamment du type MIME déclaré.
+pub(crate) struct ImageInfo {
+    pub(crate) mime: &'static str,
+    pub(crate) width: Option<u32>,
+    pub(crate) height: Option<u32>,
+    /// Profondeur de couleur en bits par pixel
+    pub(crate) color_depth: Option<u32>,
+}
+
+/// Identifier le format réel d'une image (JPEG, PNG, GIF, WebP, BMP)
+/// et en extraire les dimensions et la profondeur de couleur.
+pub(crate) fn sniff_image(data: &[u8]) -> Option<ImageInfo> {
+    if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        return Some(sniff_jpeg(data));
+    }
+    if data.starts_with(PNG_SIGNATURE) {
+        return Some(sniff_png(data));
+    }
+    if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+        return Some(sniff_gif(data));
+    }
+    if data.len() >= 12 && &data[0..4] == b"RIFF" && &data[8..12] == b"WEBP" {
+        return Some(sniff_webp(data));
+    }
+    if data.starts_with(b"BM") && data.len() >= 18 {
+        return Some(sniff_bmp(data));
+    }
+    None
+}
+
+/// Parcourir les segments JPEG jusqu'au marqueur SOFn (début de trame)
+/// qui porte la précision, la hauteur, la largeur et le nombre de composantes.
+fn sniff_jpeg(data: &[u8]) -> ImageInfo {
+    let mut info = ImageInfo {
+        mime: "image/jpeg",
+        width: None,
+        height: None,
+        color_depth: None,
+    };
+
+    let mut i = 2;
+    while i + 4 <= data.len() {
+        if data[i] != 0xFF {
+            break;
+        }
+        let marker = data[i + 1];
+        // Octets de bourrage 0xFF entre les segments
+        if marker == 0xFF {
+            i += 1;
+            continue;
+        }
+        // Marqueurs autonomes (sans champ de longueur) : TEM, RSTn, SOI
+        if marker == 0x01 || (0xD0..=0xD8).contains(&marker) {
+            i += 2;
+            continue;
+        }
+        // EOI / SOS : plus d'en-tête de trame à attendre
+        if marker == 0xD9 || marker == 0xDA {
+            break;
+        }
+        let length = be_u16(&data[i + 2..i + 4]) as usize;
+        if length < 2 {
+            break;
+        }
+        let is_sof =
+            (0xC0..=0xCF).contains(&marker) && marker != 0xC4 && marker != 0xC8 && marker != 0xCC;
+        if is_sof && i + 10 <= data.len() {
+            let precision = data[i + 4] as u32;
+            let height = be_u16(&data[i + 5..i + 7]) as u32;
+            let width = be_u16(&data[i + 7..i + 9]) as u32;
+            let components = data[i + 9] as u32;
+            info.width = (width > 0).then_some(width);
+            info.height = (height > 0).then_some(height);
+            info.color_depth = Some(precision * components);
+            break;
+        }
+        i += 2 + length;
+    }
+    info
+}
+
+/// Lire le bloc IHDR, obligatoirement le premier après la signature PNG.
+fn sniff_png(data: &[u8]) -> ImageInfo {
+    let mut info = ImageInfo {
+        mime: "image/png",
+        width: None,
+        height: None,
+        color_depth: None,
+    };
+    if data.len() < 8 + 8 + 13 || &data[12..16] != b"IHDR" {
+        return info;
+    }
+    let ihdr = &data[16..29];
+    info.width = Some(be_u32(&ihdr[0..4]));
+    info.height = Some(be_u32(&ihdr[4..8]));
+    let bit_depth = ihdr[8] as u32;
+    let channels = match ihdr[9] {
+        0 => 1, // niveaux de gris
+        2 => 3, // RVB
+        3 => 1, // palette
+        4 => 2, // niveaux de gris + alpha
+        6 => 4, // RVBA
+        _ => 0,
+    };
+    if channels > 0 {
+        info.color_depth = Some(bit_depth * channels);
+    }
+    info
+}
+
+fn sniff_gif(data: &[u8]) -> ImageInfo {
+    let mut info = ImageInfo {
+        mime: "image/gif",
+        width: None,
+        height: None,
+        color_depth: None,
+    };
+    if data.len() < 11 {
+        return info;
+    }
+    info.width = Some(le_u16(&data[6..8]) as u32);
+    info.height = Some(le_u16(&data[8..10]) as u32);
+    let packed = data[10];
+    // Taille de la palette globale si présente, sinon résolution de couleur annoncée
+    info.color_depth = Some(if packed & 0x80 != 0 {
+        (packed & 0x07) as u32 + 1
+    } else {
+        ((packed >> 4) & 0x07) as u32 + 1
+    });
+    info
+}
+
+/// Les dimensions dépendent du premier bloc : VP8 (avec perte), VP8L (sans perte) ou VP8X (étendu).
+fn sniff_webp(data: &[u8]) -> ImageInfo {
+    let mut info = ImageInfo {
+        mime: "image/webp",
+        width: None,
+        height: None,
+        color_depth: None,
+    };
+    if data.len() < 30 {
+        return info;
+    }
+    match &data[12..16] {
+        // Étiquette de trame (3 octets) puis code de départ 9D 01 2A
+        b"VP8 " if data[23..26] == [0x9D, 0x01, 0x2A] => {
+            info.width = Some((le_u16(&data[26..28]) & 0x3FFF) as u32);
+            info.height = Some((le_u16(&data[28..30]) & 0x3FFF) as u32);
+            info.color_depth = Some(24);
+        }
+        // Octet de signature 0x2F puis largeur / hauteur sur 14 bits et drapeau alpha
+        b"VP8L" if data[20] == 0x2F => {
+            let bits = le_u32(&data[21..25]);
+            info.width = Some((bits & 0x3FFF) + 1);
+            info.height = Some(((bits >> 14) & 0x3FFF) + 1);
+            let has_alpha = (bits >> 28) & 0x01 != 0;
+            info.color_depth = Some(if has_alpha { 32 } else { 24 });
+        }
+        b"VP8X" => {
+            let flags = data[20];
+            let width = le_u24(&data[24..27]) + 1;
+            let height = le_u24(&data[27..30]) + 1;
+            info.width = Some(width);
+            info.height = Some(height);
+            info.color_depth = Some(if flags & 0x10 != 0 { 32 } else { 24 });
+        }
+        _ => {}
+    }
+    info
+}
+
+fn sniff_bmp(data: &[u8]) -> ImageInfo {
+    let mut info = ImageInfo {
+        mime: "image/bmp",
+        width: None,
+        height: None,
+        color_depth: None,
+    };
+    let dib_size = le_u32(&data[14..18]);
+    if dib_size == 12 && data.len() >= 26 {
+        // BITMAPCOREHEADER (OS/2) : dimensions sur 16 bits
+        info.width = Some(le_u16(&data[18..20]) as u32);
+        info.height = Some(le_u16(&data[20..22]) as u32);
+        info.color_depth = Some(le_u16(&data[24..26]) as u32);
+    } else if dib_size >= 40 && data.len() >= 30 {
+        // BITMAPINFOHEADER et suivants : hauteur négative pour les images de haut en bas
+        info.width = Some((le_u32(&data[18..22]) as i32).unsigned_abs());
+        info.height = Some((le_u32(&data[22..26]) as i32).unsigned_abs());
+        info.color_depth = Some(le_u16(&data[28..30]) as u32);
+    }
+    info
+}
+
+fn le_u24(bytes: &[u8]) -> u32 {
+    (bytes[0] as u32) | ((bytes[1] as u32) << 8) | ((bytes[2] as u32) << 16)
+}
+
+// ---------------------------------------------------------------------------
+// Décodage et miniatures
+// ---------------------------------------------------------------------------
+
+/// Mémoire maximale allouée par un décodeur, pour ne pas faire exploser le module WASM
+/// sur une image aux dimensions aberrantes.
+const MAX_DECODE_BYTES: usize = 64 * 1024 * 1024;
+
+/// Image décodée en RVBA 8 bits par canal.
+pub(crate) struct RgbaImage {
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    pub(crate) pixels: Vec<u8>,
+}
+
+/// Décoder une image JPEG ou PNG en RVBA. Les autres formats ne sont pas pris en charge.
+pub(crate) fn decode_rgba(data: &[u8]) -> Option<RgbaImage> {
+    match sniff_image(data)?.mime {
+        "image/jpeg" => decode_jpeg(data),
+        "image/png" => decode_png(data),
+        _ => None,
+    }
+}
+
+fn decode_jpeg(data: &[u8]) -> Option<RgbaImage> {
+    use jpeg_decoder::PixelFormat;
+
+    let mut decoder = jpeg_decoder::Decoder::new(data);
+    decoder.set_max_decoding_buffer_size(MAX_DECODE_BYTES);
+    let pixels = decoder.decode().ok()?;
+    let info = decoder.info()?;
+
+    let pixel_count = info.width as usize * info.height as usize;
+    let mut rgba = Vec::with_capacity(pixel_count * 4);
+    match info.pixel_format {
+        PixelFormat::L8 => {
+            for &l in &pixels {
+                rgba.extend_from_slice(&[l, l, l, 255]);
+            }
+        }
+        PixelFormat::L16 => {
+            for px in pixels.chunks_exact(2) {
+                let l = (u16::from_ne_bytes([px[0], px[1]]) >> 8) as u8;
+                rgba.extend_from_slice(&[l, l, l, 255]);
+            }
+        }
+        PixelFormat::RGB24 => {
+            for px in pixels.chunks_exact(3) {
+                rgba.extend_from_slice(&[px[0], px[1], px[2], 255]);
+            }
+        }
+        PixelFormat::CMYK32 => {
+            for px in pixels.chunks_exact(4) {
+                let k = 255 - px[3] as u32;
+                let channel = |c: u8| ((255 - c as u32) * k / 255) as u8;
+                rgba.extend_from_slice(&[channel(px[0]), channel(px[1]), channel(px[2]), 255]);
+            }
+        }
+    }
+    if rgba.len() != pixel_count * 4 {
+        return None;
+    }
+
+    Some(RgbaImage {
+        width: info.width as u32,
+        height: info.height as u32,
+        pixels: rgba,
+    })
+}
+
+fn decode_png(data: &[u8]) -> Option<RgbaImage> {
+    let limits = png::Limits {
+        bytes: MAX_DECODE_BYTES,
+    };
+    let mut decoder = png::Decoder::new_with_limits(std::io::Cursor::new(data), limits);
+    // Palette → RVB, 16 bits → 8 bits, transparence tRNS → canal alpha
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().ok()?;
+    let mut buf = vec![0u8; reader.output_buffer_size()?];
+    let frame = reader.next_frame(&mut buf).ok()?;
+    buf.truncate(frame.buffer_size());
+
+    let pixel_count = frame.width as usize * frame.height as usize;
+    let rgba = match frame.color_type {
+        png::ColorType::Rgba => buf,
+        png::ColorType::Rgb => buf
+            .chunks_exact(3)
+            .flat_map(|px| [px[0], px[1], px[2], 255])
+            .collect(),
+        png::ColorType::GrayscaleAlpha => buf
+            .chunks_exact(2)
+            .flat_map(|px| [px[0], px[0], px[0], px[1]])
+            .collect(),
+        png::ColorType::Grayscale => buf.iter().flat_map(|&l| [l, l, l, 255]).collect(),
+        png::ColorType::Indexed => return None,
+    };
+    if rgba.len() != pixel_count * 4 {
+        return None;
+    }
+
+    Some(RgbaImage {
+        width: frame.width,
+        height: frame.height,
+        pixels: rgba,
+    })
+}
+
+/// Réduire une image pour que son plus grand côté tienne dans `max_side`
+/// (moyenne par zone, pondérée par l'alpha). Une image déjà assez petite est recopiée telle quelle.
+pub(crate) fn resize_to_fit(image: &RgbaImage, max_side: u32) -> RgbaImage {
+    let (src_w, src_h) = (image.width as usize, image.height as usize);
+    let longest = src_w.max(src_h);
+    if max_side == 0 || longest <= max_side as usize {
+        return RgbaImage {
+            width: image.width,
+            height: image.height,
+            pixels: image.pixels.clone(),
+        };
+    }
+
+    let dst_w = ((src_w * max_side as usize + longest / 2) / longest).max(1);
+    let dst_h = ((src_h * max_side as usize + longest / 2) / longest).max(1);
+    let mut pixels = Vec::with_capacity(dst_w * dst_h * 4);
+
+    for ty in 0..dst_h {
+        let y0 = ty * src_h / dst_h;
+        let y1 = ((ty + 1) * src_h / dst_h).max(y0 + 1);
+        for tx in 0..dst_w {
+            let x0 = tx * src_w / dst_w;
+            let x1 = ((tx + 1) * src_w / dst_w).max(x0 + 1);
+
+            let mut sum = [0u64; 4];
+            for y in y0..y1 {
+                let row = &image.pixels[(y * src_w + x0) * 4..(y * src_w + x1) * 4];
+                for px in row.chunks_exact(4) {
+                    let a = px[3] as u64;
+                    sum[0] += px[0] as u64 * a;
+                    sum[1] += px[1] as u64 * a;
+                    sum[2] += px[2] as u64 * a;
+                    sum[3] += a;
+                }
+            }
+
+            let count = ((y1 - y0) * (x1 - x0)) as u64;
+            let alpha = sum[3];
+            // Couleurs pondérées par l'alpha : un pixel transparent n'influence pas la teinte
+            let channel = |total: u64| (total + alpha / 2).checked_div(alpha).unwrap_or(0) as u8;
+            pixels.extend_from_slice(&[
+                channel(sum[0]),
+                channel(sum[1]),
+                channel(sum[2]),
+                ((alpha + count / 2) / count) as u8,
+            ]);
+        }
+    }
+
+    RgbaImage {
+        width: dst_w as u32,
+        height: dst_h as u32,
+        pixels,
+    }
+}
+
+/// Encoder une image RVBA en PNG.
+pub(crate) fn encode_png(image: &RgbaImage) -> Option<Vec<u8>> {
+    let mut out = Vec::new();
+    {
+        let mut encoder = png::Encoder::new(&mut out, image.width, image.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().ok()?;
+        writer.write_image_data(&image.pixels).ok()?;
+        writer.finish().ok()?;
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solid(width: u32, height: u32, pixel: [u8; 4]) -> RgbaImage {
+        RgbaImage {
+            width,
+            height,
+            pixels: pixel.repeat((width * height) as usize),
+        }
+    }
+
+    fn sniffed(data: &[u8]) -> (&'static str, Option<u32>, Option<u32>, Option<u32>) {
+        let info = sniff_image(data).unwrap();
+        (info.mime, info.width, info.height, info.color_depth)
+    }
+
+    /// En-tête RIFF/WEBP suivi d'un premier bloc, complété à 30 octets
+    fn webp(chunk: &[u8; 4], payload: &[u8]) -> Vec<u8> {
+        let mut data = b"RIFF\0\0\0\0WEBP".to_vec();
+        data.extend_from_slice(chunk);
+        data.extend_from_slice(&[0; 4]);
+        data.extend_from_slice(payload);
+        data.resize(30, 0);
+        data
+    }
+
+    #[test]
+    fn jpeg_dimensions_come_from_the_sof_segment() {
+        let mut data = vec![0xFF, 0xD8];
+        // APP0 de 16 octets, un octet de bourrage, puis SOF2 (progressif)
+        data.extend_from_slice(&[0xFF, 0xE0, 0x00, 0x10]);
+        data.extend_from_slice(&[0; 14]);
+        data.push(0xFF);
+        data.extend_from_slice(&[0xFF, 0xC2, 0x00, 0x11, 8, 0x00, 0xF0, 0x01, 0x40, 3]);
+        assert_eq!(
+            sniffed(&data),
+            ("image/jpeg", Some(320), Some(240), Some(24))
+        );
+
+        // DHT (0xC4) n'est pas un SOF ; SOS arrête la recherche
+        let data = [0xFF, 0xD8, 0xFF, 0xC4, 0x00, 0x02, 0xFF, 0xDA, 0x00, 0x02];
+        assert_eq!(sniffed(&data), ("image/jpeg", None, None, None));
+    }
+
+    #[test]
+    fn png_dimensions_come_from_ihdr() {
+        let ihdr = |bit_depth: u8, color_type: u8| {
+            let mut data = PNG_SIGNATURE.to_vec();
+            data.extend_from_slice(&13u32.to_be_bytes());
+            data.extend_from_slice(b"IHDR");
+            data.extend_from_slice(&640u32.to_be_bytes());
+            data.extend_from_slice(&480u32.to_be_bytes());
+            data.extend_from_slice(&[bit_depth, color_type, 0, 0, 0]);
+            data
+        };
+        assert_eq!(
+            sniffed(&ihdr(8, 6)),
+            ("image/png", Some(640), Some(480), Some(32))
+        );
+        assert_eq!(sniffed(&ihdr(16, 2)).3, Some(48));
+        assert_eq!(sniffed(&ihdr(4, 3)).3, Some(4));
+        // Type de couleur inconnu : pas de profondeur
+        assert_eq!(sniffed(&ihdr(8, 5)).3, None);
+        // Signature seule : format reconnu, dimensions inconnues
+        assert_eq!(sniffed(PNG_SIGNATURE), ("image/png", None, None, None));
+    }
+
+    #[test]
+    fn gif_depth_comes_from_the_global_palette() {
+        let gif = |packed: u8| {
+            let mut data = b"GIF89a".to_vec();
+            data.extend_from_slice(&[16, 0, 9, 0, packed]);
+            data
+        };
+        assert_eq!(
+            sniffed(&gif(0xF2)),
+            ("image/gif", Some(16), Some(9), Some(3))
+        );
+        // Sans palette globale : résolution de couleur annoncée
+        assert_eq!(sniffed(&gif(0x50)).3, Some(6));
+    }
+
+    #[test]
+    fn webp_dimensions_depend_on_the_first_chunk() {
+        // VP8 : bits d'échelle (2 bits de poids fort) ignorés
+        let mut vp8 = vec![0; 3];
+        vp8.extend_from_slice(&[0x9D, 0x01, 0x2A]);
+        vp8.extend_from_slice(&(0xC000u16 | 400).to_le_bytes());
+        vp8.extend_from_slice(&300u16.to_le_bytes());
+        assert_eq!(
+            sniffed(&webp(b"VP8 ", &vp8)),
+            ("image/webp", Some(400), Some(300), Some(24))
+        );
+
+        // VP8L : largeur - 1 et hauteur - 1 sur 14 bits, puis drapeau alpha
+        let bits: u32 = 99 | (49 << 14) | (1 << 28);
+        let mut vp8l = vec![0x2F];
+        vp8l.extend_from_slice(&bits.to_le_bytes());
+        assert_eq!(
+            sniffed(&webp(b"VP8L", &vp8l)),
+            ("image/webp", Some(100), Some(50), Some(32))
+        );
+
+        // VP8X : largeur - 1 et hauteur - 1 sur 24 bits
+        let mut vp8x = vec![0x10, 0, 0, 0];
+        vp8x.extend_from_slice(&[0x7F, 0x07, 0x00, 0x37, 0x04, 0x00]);
+        assert_eq!(
+            sniffed(&webp(b"VP8X", &vp8x)),
+            ("image/webp", Some(1920), Some(1080), Some(32))
+        );
+        assert_eq!(sniffed(&webp(b"VP8X", &[0; 10])).3, Some(24));
+    }
+
+    #[test]
+    fn bmp_dimensions_come_from_the_dib_header() {
+        let mut info = b"BM".to_vec();
+        info.extend_from_slice(&[0; 12]);
+        info.extend_from_slice(&40u32.to_le_bytes());
+        info.extend_from_slice(&64i32.to_le_bytes());
+        // Hauteur négative : image enregistrée de haut en bas
+        info.extend_from_slice(&(-32i32).to_le_bytes());
+        info.extend_from_slice(&1u16.to_le_bytes());
+        info.extend_from_slice(&24u16.to_le_bytes());
+        assert_eq!(sniffed(&info), ("image/bmp", Some(64), Some(32), Some(24)));
+
+        let mut core = b"BM".to_vec();
+        core.extend_from_slice(&[0; 12]);
+        core.extend_from_slice(&12u32.to_le_bytes());
+        core.extend_from_slice(&[10, 0, 20, 0, 1, 0, 8, 0]);
+        assert_eq!(sniffed(&core), ("image/bmp", Some(10), Some(20), Some(8)));
+    }
+
+    #[test]
+    fn unknown_data_is_not_an_image() {
+        assert!(sniff_image(b"http://example.com/cover.jpg").is_none());
+        assert!(sniff_image(b"BM").is_none());
+        assert!(sniff_image(b"RIFF\0\0\0\0WAVE").is_none());
+    }
+
+    #[test]
+    fn resize_keeps_the_aspect_ratio() {
+        let thumbnail = resize_to_fit(&solid(400, 100, [10, 20, 30, 255]), 64);
+        assert_eq!((thumbnail.width, thumbnail.height), (64, 16));
+        assert_eq!(&thumbnail.pixels[..4], &[10, 20, 30, 255]);
+        // Déjà assez petite : recopiée telle quelle
+        let small = resize_to_fit(&solid(8, 8, [1, 2, 3, 4]), 64);
+        assert_eq!((small.width, small.height), (8, 8));
+    }
+
+    #[test]
+    fn transparent_pixels_do_not_tint_the_average() {
+        // Pixel rouge opaque et pixel vert transparent réduits en un seul
+        let image = RgbaImage {
+            width: 2,
+            height: 1,
+            pixels: vec![255, 0, 0, 255, 0, 255, 0, 0],
+        };
+        let thumbnail = resize_to_fit(&image, 1);
+        assert_eq!(thumbnail.pixels, [255, 0, 0, 128]);
+    }
+
+    #[test]
+    fn encoded_png_decodes_to_the_same_pixels() {
+        let image = solid(3, 2, [200, 100, 50, 255]);
+        let png = encode_png(&image).unwrap();
+        let info = sniff_image(&png).unwrap();
+        assert_eq!(
+            (info.mime, info.width, info.height),
+            ("image/png", Some(3), Some(2))
+        );
+        let decoded = decode_rgba(&png).unwrap();
+        assert_eq!((decoded.width, decoded.height), (3, 2));
+        assert_eq!(decoded.pixels, image.pixels);
+    }
+}
@@ -0,0 +1,230 @@
+// ---------------------------------------------------------------------------
+// Inspection de la structure des tags : arbre des éléments vus par les parseurs
+// ---------------------------------------------------------------------------
+
+use serde::Serialize;
+use ts_rs::TS;
+
+use crate::be_u16;
+
+/// Sort d'un élément structurel : champ du résultat alimenté (`mappedTo`),
+/// ou raison de l'écarter (`skipped`)
+#[derive(Serialize, TS, Clone, Copy)]
+#[ts(export, export_to = "metadata.ts", rename = "MetadataInspectUsage")]
+pub enum Usage {
+    #[serde(rename = "mappedTo")]
+    Mapped(#[ts(type = "string")] &'static str),
+    #[serde(rename = "skipped")]
+    Skipped(#[ts(type = "string")] &'static str),
+}
+
+impl Usage {
+    /// `Mapped(field)` si l'élément a fourni une valeur, sinon écarté pour `reason`
+    pub(crate) fn mapped_or(mapped: bool, field: &'static str, reason: &'static str) -> Self {
+        if mapped {
+            Self::Mapped(field)
+        } else {
+            Self::Skipped(reason)
+        }
+    }
+}
+
+pub(crate) const ALREADY_SET: &str = "already set by an earlier tag";
+pub(crate) const UNSUPPORTED: &str = "not used by the parser";
+pub(crate) const EMPTY_VALUE: &str = "empty value";
+pub(crate) const INVALID_PICTURE: &str = "invalid picture data";
+pub(crate) const FRAME_OVERRUN: &str = "frame overruns the tag";
+pub(crate) const ENCODED_FRAME: &str = "compressed or encrypted frame";
+pub(crate) const COVER_TOO_LARGE: &str = "larger than maxCoverBytes";
+pub(crate) const TRUNCATED_BLOCK: &str = "block extends past the end of the data";
+pub(crate) const INVALID_CUE_SHEET: &str = "invalid cue sheet";
+pub(crate) const INVALID_UTF8: &str = "invalid UTF-8";
+pub(crate) const MISSING_SEPARATOR: &str = "missing '=' separator";
+pub(crate) const NOT_AUDIO_TRACK: &str = "not an audio track";
+
+/// Élément structurel : tag, frame ID3, bloc FLAC, clé Vorbis, trame MPEG…
+#[derive(Serialize, TS)]
+#[ts(export, export_to = "metadata.ts", rename = "MetadataInspectNode")]
+pub struct InspectNode {
+    /// « id3v2 », « id3v2-frame », « id3v1 », « flac-block », « vorbis-comment »…
+    #[ts(type = "string")]
+    pub kind: &'static str,
+    pub id: String,
+    /// Position absolue dans le fichier ; absente pour les données réassemblées (paquets Ogg)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional, type = "number")]
+    pub offset: Option<usize>,
+    #[ts(type = "number")]
+    pub size: usize,
+    /// Drapeaux, encodage déclaré, etc.
+    #[serde(with = "details")]
+    #[ts(type = "Record<string, string>")]
+    pub details: Vec<(&'static str, String)>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub usage: Option<Usage>,
+    pub children: Vec<InspectNode>,
+}
+
+/// Détails sérialisés comme un objet, dans leur ordre d'ajout
+mod details {
+    use serde::Serializer;
+
+    pub(super) fn serialize<S: Serializer>(
+        details: &[(&'static str, String)],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(details.iter().map(|(key, value)| (key, value)))
+    }
+}
+
+impl InspectNode {
+    /// `offset` est relatif à la base courante de l'inspecteur
+    pub(crate) fn new(
+        kind: &'static str,
+        id: impl Into<String>,
+        offset: usize,
+        size: usize,
+    ) -> Self {
+        Self {
+            kind,
+            id: id.into(),
+            offset: Some(offset),
+            size,
+            details: Vec::new(),
+            usage: None,
+            children: Vec::new(),
+        }
+    }
+
+    pub(crate) fn detail(mut self, key: &'static str, value: impl ToString) -> Self {
+        self.details.push((key, value.to_string()));
+        self
+    }
+
+    pub(crate) fn usage(mut self, usage: Usage) -> Self {
+        self.usage = Some(usage);
+        self
+    }
+
+    /// Rendre l'offset absolu ; sans base (paquets Ogg réassemblés), il est retiré
+    pub(crate) fn rebase(mut self, base: Option<usize>) -> Self {
+        self.offset = base.zip(self.offset).map(|(base, offset)| base + offset);
+        self
+    }
+}
+
+/// Enregistreur activé par `inspect()` : les parseurs y déposent les éléments rencontrés.
+/// Les nœuds ouverts reçoivent les éléments suivants comme enfants jusqu'à leur fermeture.
+pub(crate) struct Inspector {
+    roots: Vec<InspectNode>,
+    open: Vec<InspectNode>,
+}
+
+impl Inspector {
+    pub(crate) fn new() -> Self {
+        Self {
+            roots: Vec::new(),
+            open: Vec::new(),
+        }
+    }
+
+    pub(crate) fn push(&mut self, node: InspectNode) {
+        match self.open.last_mut() {
+            Some(parent) => parent.children.push(node),
+            None => self.roots.push(node),
+        }
+    }
+
+    pub(crate) fn open(&mut self, node: InspectNode) {
+        self.open.push(node);
+    }
+
+    pub(crate) fn close(&mut self) {
+        if let Some(node) = self.open.pop() {
+            self.push(node);
+        }
+    }
+
+    /// Nœud ouvert le plus récent, pour compléter son sort une fois ses enfants analysés
+    pub(crate) fn current(&mut self) -> Option<&mut InspectNode> {
+        self.open.last_mut()
+    }
+
+    pub(crate) fn finish(mut self) -> Vec<InspectNode> {
+        while !self.open.is_empty() {
+            self.close();
+        }
+        self.roots
+    }
+}
+
+/// Nœud d'une frame ID3v2 : identifiant, taille totale, drapeaux (v2.3+) et encodage déclaré
+pub(crate) fn id3_frame_node(
+    frame_id: &[u8],
+    offset: usize,
+    size: usize,
+    flags: Option<&[u8]>,
+    data: &[u8],
+) -> InspectNode {
+    let mut node = InspectNode::new(
+        "id3v2-frame",
+        String::from_utf8_lossy(frame_id),
+        offset,
+        size,
+    );
+    if let Some(flags) = flags {
+        node = node.detail("flags", format!("0x{:04x}", be_u16(flags)));
+    }
+    if has_text_encoding(frame_id)
+        && let Some(&encoding) = data.first()
+    {
+        node = node.detail("encoding", id3_encoding_name(encoding));
+    }
+    node
+}
+
+/// Frames dont le premier octet déclare l'encodage des textes
+pub(crate) fn has_text_encoding(frame_id: &[u8]) -> bool {
+    frame_id.first() == Some(&b'T')
+        || matches!(
+            frame_id,
+            b"COMM"
+                | b"COM"
+                | b"USLT"
+                | b"ULT"
+                | b"SYLT"
+                | b"SLT"
+                | b"APIC"
+                | b"PIC"
+                | b"WXXX"
+                | b"WXX"
+                | b"USER"
+        )
+}
+
+/// Nom de l'encodage déclaré par le premier octet d'une frame ID3v2. Un texte déclaré
+/// ISO-8859-1 peut être décodé avec une autre page de code (`legacyEncoding`).
+pub(crate) fn id3_encoding_name(encoding: u8) -> &'static str {
+    match encoding {
+        0 => "ISO-8859-1 (declared)",
+        1 => "UTF-16",
+        2 => "UTF-16BE",
+        3 => "UTF-8",
+        _ => "invalid",
+    }
+}
+
+pub(crate) fn flac_block_name(block_type: u8) -> &'static str {
+    match block_type {
+        0 => "STREAMINFO",
+        1 => "PADDING",
+        2 => "APPLICATION",
+        3 => "SEEKTABLE",
+        4 => "VORBIS_COMMENT",
+        5 => "CUESHEET",
+        6 => "PICTURE",
+        127 => "INVALID",
+        _ => "RESERVED",
+    }
+}
@@ -0,0 +1,4196 @@
+use std::borrow::Cow;
+use std::ops::Range;
+
+pub use encoding_rs::Encoding;
+use serde::Serialize;
+use ts_rs::TS;
+
+mod charset;
+pub mod cuesheet;
+mod error;
+mod image;
+pub mod inspect;
+pub mod model;
+mod mp4;
+mod mpeg;
+pub mod mpeg_scan;
+pub mod palette;
+pub mod stream;
+mod unsync;
+mod xing;
+
+use charset::LegacyTextDecoder;
+pub use charset::encoding_for_label;
+use cuesheet::{CD_SAMPLE_RATE, CueSheet, parse_cue_text, parse_flac_cuesheet, write_cue_text};
+pub use error::Error;
+use image::{RgbaImage, decode_rgba, encode_png, resize_to_fit, sniff_image};
+use inspect::{
+    ALREADY_SET, COVER_TOO_LARGE, EMPTY_VALUE, ENCODED_FRAME, FRAME_OVERRUN, INVALID_CUE_SHEET,
+    INVALID_PICTURE, INVALID_UTF8, InspectNode, Inspector, MISSING_SEPARATOR, TRUNCATED_BLOCK,
+    UNSUPPORTED, Usage, flac_block_name, has_text_encoding, id3_encoding_name, id3_frame_node,
+};
+use model::{BitrateMode, ThumbnailFormat};
+use mp4::{MP4_SIGNATURE, parse_mp4};
+use mpeg::{MpegFrame, find_mpeg_frame};
+use mpeg_scan::{FrameScan, scan_frames};
+use palette::{Palette, extract_palette};
+use unsync::{UnsyncMap, resynchronise};
+use xing::{parse_vbri_header, parse_xing_header};
+
+const ID3V2_HEADER_SIZE: usize = 10;
+const ID3V1_SIZE: usize = 128;
+const APE_FOOTER_SIZE: usize = 32;
+const LYRICS3V2_END: &[u8; 9] = b"LYRICS200";
+
+/// Nombre maximal de tags ID3v2 chaînés (SEEK) suivis, pour se protéger des boucles
+const MAX_CHAINED_ID3_TAGS: usize = 8;
+const FLAC_SIGNATURE: &[u8; 4] = b"fLaC";
+const OGG_SIGNATURE: &[u8; 4] = b"OggS";
+const OGG_PAGE_HEADER_SIZE: usize = 27;
+
+pub const MAX_TEXT_BYTES: usize = 16 * 1024;
+pub const MAX_COVER_BYTES: usize = 4 * 1024 * 1024;
+
+/// Fenêtre de recherche par défaut de la première trame MPEG après les tags
+const MPEG_SCAN_BYTES: usize = 8192;
+/// Côté maximal d'une miniature de pochette
+pub const MAX_THUMBNAIL_SIZE: u32 = 2048;
+
+/// Séparateur utilisé pour la chaîne d'affichage des champs à valeurs multiples
+const MULTI_VALUE_SEPARATOR: &str = "; ";
+
+/// Tables de recherche de taux d'échantillonnage MPEG indexées par [version_index][sr_index]
+/// version_index: 0 = MPEG2.5, 1 = réservé, 2 = MPEG2, 3 = MPEG1
+const MPEG_SAMPLE_RATES: [[u32; 3]; 4] = [
+    [11025, 12000, 8000],  // MPEG2.5
+    [0, 0, 0],             // réservé
+    [22050, 24000, 16000], // MPEG2
+    [44100, 48000, 32000], // MPEG1
+];
+
+/// Tables de recherche de débit binaire MPEG (kbps)
+/// L'index 0 est inutilisé (format libre), l'index 15 est invalide
+/// [version_layer_combo][bitrate_index]
+/// Combos: 0 = V1/L1, 1 = V1/L2, 2 = V1/L3, 3 = V2/L1, 4 = V2/L2+L3
+const MPEG_BITRATES: [[u16; 15]; 5] = [
+    // V1, Layer I
+    [
+        0, 32, 64, 96, 128, 160, 192, 224, 256, 288, 320, 352, 384, 416, 448,
+    ],
+    // V1, Layer II
+    [
+        0, 32, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320, 384,
+    ],
+    // V1, Layer III
+    [
+        0, 32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320,
+    ],
+    // V2, Layer I
+    [
+        0, 32, 48, 56, 64, 80, 96, 112, 128, 144, 160, 176, 192, 224, 256,
+    ],
+    // V2, Layer II & III
+    [0, 8, 16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 144, 160],
+];
+
+struct AudioInfo {
+    sample_rate: Option<u32>,
+    bit_depth: Option<u16>,
+    /// Débit moyen (kbps) lorsqu'il est connu, sinon celui de la première trame
+    bitrate: Option<u32>,
+    channels: Option<u8>,
+    duration_ms: Option<u64>,
+    bitrate_mode: Option<BitrateMode>,
+    /// Points d'accès issus de la table TOC Xing / VBRI ou de la SEEKTABLE FLAC
+    seek_points: Vec<SeekPoint>,
+    /// Première trame audio d'un flux MPEG
+    mpeg_frame: Option<MpegFrame>,
+    flac: Option<FlacStreamInfo>,
+    flac_blocks: FlacBlocks,
+}
+
+impl AudioInfo {
+    fn new() -> Self {
+        Self {
+            sample_rate: None,
+            bit_depth: None,
+            bitrate: None,
+            channels: None,
+            duration_ms: None,
+            bitrate_mode: None,
+            seek_points: Vec::new(),
+            mpeg_frame: None,
+            flac: None,
+            flac_blocks: FlacBlocks::default(),
+        }
+    }
+}
+
+/// Échantillons à retirer pour une lecture sans blanc entre les pistes
+struct GaplessInfo {
+    /// Échantillons de retard en début de flux (délai encodeur, pre-skip Opus)
+    delay: u32,
+    /// Échantillons de remplissage en fin de flux
+    padding: Option<u32>,
+    /// Nombre exact d'échantillons (par canal) à conserver
+    valid_samples: Option<u64>,
+    /// Origine des valeurs : « lame », « itunsmpb » ou « opus »
+    source: &'static str,
+}
+
+/// Blocs FLAC SEEKTABLE, APPLICATION et PADDING
+#[derive(Default)]
+struct FlacBlocks {
+    seek_table: Vec<FlacSeekPoint>,
+    /// Identifiants enregistrés des blocs APPLICATION (« riff », « ATCH »…)
+    application_ids: Vec<String>,
+    /// Octets de remplissage disponibles pour réécrire les tags sans déplacer l'audio
+    padding_bytes: u64,
+    /// Position de la première trame audio, après le dernier bloc de métadonnées
+    audio_offset: Option<u64>,
+}
+
+struct FlacSeekPoint {
+    /// Premier échantillon de la trame ciblée
+    sample: u64,
+    /// Position de la trame, relative à la première trame audio
+    offset: u64,
+    frame_samples: u16,
+}
+
+/// Champs du bloc STREAMINFO FLAC au-delà du format audio
+struct FlacStreamInfo {
+    /// Tailles de bloc en échantillons
+    min_block_size: u16,
+    max_block_size: u16,
+    /// Tailles de trame en octets ; 0 si l'encodeur ne les connaissait pas
+    min_frame_size: u32,
+    max_frame_size: u32,
+    /// Échantillons par canal ; 0 si inconnu (flux en direct)
+    total_samples: u64,
+    /// Signature MD5 de l'audio décodé ; nulle si non calculée
+    md5: [u8; 16],
+}
+
+/// Position d'octet dans le fichier correspondant à un instant de lecture
+struct SeekPoint {
+    time_ms: u64,
+    offset: u64,
+}
+
+/// Logiciel ayant produit le fichier
+struct EncoderInfo {
+    /// Chaîne vendeur FLAC / Vorbis / Opus, ou version LAME (« LAME3.100 »)
+    name: Option<String>,
+    /// Méthode de débit LAME : cbr, abr, vbr-rh, vbr-mtrh…
+    vbr_method: Option<&'static str>,
+    /// Préréglage LAME (« V2 », « extreme », « ABR 192 »)
+    preset: Option<String>,
+    lowpass_hz: Option<u32>,
+    /// Qualité déclarée dans l'en-tête Xing (0 = meilleure … 100)
+    quality: Option<u32>,
+    /// Réglages de l'encodeur (TSSE, ENCODER)
+    settings: Option<String>,
+    /// Personne ou logiciel ayant encodé (TENC, ENCODED-BY)
+    encoded_by: Option<String>,
+}
+
+impl EncoderInfo {
+    fn new() -> Self {
+        Self {
+            name: None,
+            vbr_method: None,
+            preset: None,
+            lowpass_hz: None,
+            quality: None,
+            settings: None,
+            encoded_by: None,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.name.is_none()
+            && self.vbr_method.is_none()
+            && self.preset.is_none()
+            && self.lowpass_hz.is_none()
+            && self.quality.is_none()
+            && self.settings.is_none()
+            && self.encoded_by.is_none()
+    }
+}
+
+/// Noms des types d'image ID3v2 (APIC / PIC), également utilisés par le bloc PICTURE FLAC
+const PICTURE_TYPE_NAMES: [&str; 21] = [
+    "Other",
+    "32x32 pixels file icon",
+    "Other file icon",
+    "Cover (front)",
+    "Cover (back)",
+    "Leaflet page",
+    "Media",
+    "Lead artist/lead performer/soloist",
+    "Artist/performer",
+    "Conductor",
+    "Band/Orchestra",
+    "Composer",
+    "Lyricist/text writer",
+    "Recording Location",
+    "During recording",
+    "During performance",
+    "Movie/video screen capture",
+    "A bright coloured fish",
+    "Illustration",
+    "Band/artist logotype",
+    "Publisher/Studio logotype",
+];
+
+const FRONT_COVER_TYPE: u8 = 3;
+
+/// Type MIME réservé par ID3v2 / FLAC : les données de l'image sont alors une URL
+const PICTURE_LINK_MIME: &str = "-->";
+
+/// Image intégrée (APIC, PIC ou bloc PICTURE).
+/// Les octets de l'image sont empruntés au tampon d'entrée tant que possible.
+struct Picture<'a> {
+    pic_type: u8,
+    /// Type MIME réel, déduit de l'en-tête de l'image quand il est reconnu
+    mime: String,
+    /// Type MIME tel qu'écrit dans le tag
+    declared_mime: String,
+    description: String,
+    width: Option<u32>,
+    height: Option<u32>,
+    color_depth: Option<u32>,
+    data: Cow<'a, [u8]>,
+    /// Taille de l'image ; `data` n'en contient que le début quand le corps
+    /// n'a pas été lu (bloc PICTURE d'un fichier lu par morceaux)
+    length: usize,
+    /// Emplacement des octets de l'image : relatif au contenu de la frame ou du bloc
+    /// à la lecture, puis au tampon analysé une fois l'image rangée
+    span: Option<DataSpan>,
+}
+
+/// Emplacement d'octets dans le tampon analysé
+#[derive(Clone, Copy)]
+struct DataSpan {
+    offset: usize,
+    length: usize,
+    /// Octets désynchronisés (ID3v2) : le 0x00 qui suit chaque 0xFF est à retirer
+    unsynchronised: bool,
+}
+
+impl DataSpan {
+    fn new(offset: usize, length: usize) -> Self {
+        Self {
+            offset,
+            length,
+            unsynchronised: false,
+        }
+    }
+}
+
+impl<'a> Picture<'a> {
+    /// Construire une image en vérifiant son en-tête : le type MIME déclaré n'est
+    /// conservé (normalisé) que si le format n'a pas pu être reconnu.
+    fn new(pic_type: u8, declared_mime: String, description: String, data: Cow<'a, [u8]>) -> Self {
+        let sniffed = sniff_image(&data);
+        let mime = match &sniffed {
+            Some(info) => info.mime.to_string(),
+            None => normalize_image_mime(&declared_mime),
+        };
+        Self {
+            pic_type,
+            mime,
+            declared_mime,
+            description,
+            width: sniffed.as_ref().and_then(|info| info.width),
+            height: sniffed.as_ref().and_then(|info| info.height),
+            color_depth: sniffed.as_ref().and_then(|info| info.color_depth),
+            length: data.len(),
+            data,
+            span: None,
+        }
+    }
+
+    /// Situer l'image dans un contenu qui commence à `start`
+    fn at(self, start: usize) -> Self {
+        Self {
+            span: self.span.map(|span| DataSpan {
+                offset: start + span.offset,
+                ..span
+            }),
+            ..self
+        }
+    }
+
+    fn is_link(&self) -> bool {
+        self.declared_mime == PICTURE_LINK_MIME
+    }
+
+    /// Octets de l'image entièrement lus : ils peuvent être copiés ou décodés
+    fn is_complete(&self) -> bool {
+        self.data.len() == self.length
+    }
+
+    /// Copier les octets empruntés, pour une image lue dans un tampon temporaire
+    /// (paquet Ogg réassemblé, valeur base64 décodée) : elle n'a plus d'emplacement.
+    fn into_owned(self) -> Picture<'static> {
+        Picture {
+            data: Cow::Owned(self.data.into_owned()),
+            span: None,
+            ..self
+        }
+    }
+}
+
+/// Image référencée par une URL (type MIME « --> ») plutôt qu'intégrée au fichier.
+struct PictureLink {
+    pic_type: u8,
+    description: String,
+    url: String,
+}
+
+/// Métadonnées accumulées au fil des parseurs.
+/// Les champs texte sont multi-valués : une liste vide signifie « absent ».
+struct Metadata<'a> {
+    title: Vec<String>,
+    artist: Vec<String>,
+    album: Vec<String>,
+    pictures: Vec<Picture<'a>>,
+    picture_links: Vec<PictureLink>,
+    lyrics: Option<String>,
+    synced_lyrics: Option<String>,
+    audio_info: AudioInfo,
+    encoder: EncoderInfo,
+    gapless: Option<GaplessInfo>,
+    /// Feuille de montage d'une image d'album (bloc CUESHEET ou commentaire CUESHEET)
+    cue_sheet: Option<CueSheet>,
+    /// Décodage des textes déclarés ISO-8859-1 (ID3v2 encodage 0, ID3v1)
+    legacy_text: LegacyTextDecoder,
+    /// Anomalies relevées en cours d'analyse
+    warnings: Vec<Warning>,
+    /// Arbre des éléments structurels, tenu seulement par `inspect()`
+    inspector: Option<Inspector>,
+    /// Position dans le fichier des offsets relatifs passés à `trace` et `warn`
+    /// (début du tag ID3v2 ou du bloc en cours) ; absente pour les paquets Ogg réassemblés
+    offset_base: Option<usize>,
+    /// Un tag ID3 ou une trame MPEG a été trouvé : sans signature, c'est ce qui
+    /// distingue un MP3 sans métadonnées utiles d'un fichier non reconnu
+    found_mpeg_data: bool,
+}
+
+/// Anomalie qui n'empêche pas de lire le reste du fichier
+struct Warning {
+    /// Identifiant stable : « truncated-tag », « frame-overrun », « invalid-encoding »…
+    code: &'static str,
+    message: String,
+    offset: Option<usize>,
+}
+
+impl<'a> Metadata<'a> {
+    fn new(fallback_encoding: Option<&'static Encoding>) -> Self {
+        Self {
+            title: Vec::new(),
+            artist: Vec::new(),
+            album: Vec::new(),
+            pictures: Vec::new(),
+            picture_links: Vec::new(),
+            lyrics: None,
+            synced_lyrics: None,
+            audio_info: AudioInfo::new(),
+            encoder: EncoderInfo::new(),
+            gapless: None,
+            cue_sheet: None,
+            legacy_text: LegacyTextDecoder::new(fallback_encoding),
+            warnings: Vec::new(),
+            inspector: None,
+            offset_base: Some(0),
+            found_mpeg_data: false,
+        }
+    }
+
+    /// Relever une anomalie ; `offset` est relatif à la base courante.
+    fn warn(&mut self, code: &'static str, offset: usize, message: impl Into<String>) {
+        self.warnings.push(Warning {
+            code,
+            message: message.into(),
+            offset: self.offset_base.map(|base| base + offset),
+        });
+    }
+
+    /// Signaler un élément à l'inspecteur ; le nœud n'est construit que si l'inspection est active.
+    fn trace(&mut self, node: impl FnOnce() -> InspectNode) {
+        if let Some(inspector) = &mut self.inspector {
+            inspector.push(node().rebase(self.offset_base));
+        }
+    }
+
+    /// Ouvrir un conteneur : les éléments suivants deviennent ses enfants jusqu'à `trace_close`.
+    fn trace_open(&mut self, node: impl FnOnce() -> InspectNode) {
+        if let Some(inspector) = &mut self.inspector {
+            inspector.open(node().rebase(self.offset_base));
+        }
+    }
+
+    fn trace_close(&mut self) {
+        if let Some(inspector) = &mut self.inspector {
+            inspector.close();
+        }
+    }
+
+    /// Sort du conteneur ouvert, connu une fois son contenu analysé
+    fn trace_usage(&mut self, usage: Usage) {
+        if let Some(node) = self.inspector.as_mut().and_then(Inspector::current) {
+            node.usage = Some(usage);
+        }
+    }
+
+    /// Changer la base des offsets signalés ; renvoie l'ancienne pour la rétablir ensuite
+    fn set_offset_base(&mut self, base: Option<usize>) -> Option<usize> {
+        std::mem::replace(&mut self.offset_base, base)
+    }
+
+    /// Ranger une image : les liens « --> » sont séparés des images intégrées.
+    /// `offset` situe le bloc ou la frame qui la contient, pour les avertissements.
+    fn add_picture(
+        &mut self,
+        picture: Picture<'a>,
+        offset: usize,
+        max_cover_bytes: usize,
+    ) -> Usage {
+        let picture = match self.offset_base {
+            Some(base) => picture.at(base),
+            None => Picture {
+                span: None,
+                ..picture
+            },
+        };
+        if picture.pic_type as usize >= PICTURE_TYPE_NAMES.len() {
+            self.warn(
+                "unknown-picture-type",
+                offset,
+                format!("unknown picture type {}", picture.pic_type),
+            );
+        }
+        if picture.is_link() {
+            self.picture_links.push(PictureLink {
+                pic_type: picture.pic_type,
+                description: picture.description,
+                url: trim_string(String::from_utf8_lossy(&picture.data).to_string()),
+            });
+            Usage::Mapped("pictureLinks")
+        } else {
+            if picture.length > max_cover_bytes {
+                self.warn(
+                    "cover-too-large",
+                    offset,
+                    cover_too_large_message(picture.length, max_cover_bytes),
+                );
+            }
+            self.pictures.push(picture);
+            Usage::Mapped("pictures")
+        }
+    }
+
+    /// Image retenue comme pochette : la première couverture avant (type 3),
+    /// sinon la première image, parmi celles qui respectent `max_cover_bytes`.
+    fn cover(&self, max_cover_bytes: usize) -> Option<&Picture<'a>> {
+        let mut eligible = self
+            .pictures
+            .iter()
+            .filter(|pic| pic.length <= max_cover_bytes);
+        let first = eligible.clone().next();
+        eligible
+            .find(|pic| pic.pic_type == FRONT_COVER_TYPE)
+            .or(first)
+    }
+
+    fn offer_lyrics(&mut self, text: String) -> Usage {
+        if self.lyrics.is_some() || self.synced_lyrics.is_some() {
+            return Usage::Skipped(ALREADY_SET);
+        }
+        if text.starts_with('[') && text.contains(']') {
+            self.synced_lyrics = Some(text);
+            Usage::Mapped("syncedLyrics")
+        } else {
+            self.lyrics = Some(text);
+            Usage::Mapped("lyrics")
+        }
+    }
+}
+
+/// Options d'analyse ; `Default` reprend les limites `MAX_TEXT_BYTES` et `MAX_COVER_BYTES`
+#[derive(Clone, Debug)]
+pub struct ParseOptions {
+    pub max_text_bytes: usize,
+    pub max_cover_bytes: usize,
+    /// Remplir `pictures` avec toutes les images intégrées
+    pub include_pictures: bool,
+    /// Copier les octets de chaque image dans `pictures[].data`
+    pub include_picture_data: bool,
+    /// Copier les octets de la pochette dans `cover_data`
+    pub include_cover_data: bool,
+    /// Désigner les images par leur emplacement (`cover_ref`, `pictures[].data_ref`)
+    /// au lieu de copier leurs octets ; une image sans emplacement dans l'entrée
+    /// (Ogg, base64) reste copiée
+    pub cover_refs: bool,
+    /// Côtés maximaux (px) des miniatures de pochette à générer
+    pub thumbnail_sizes: Vec<u32>,
+    pub thumbnail_format: ThumbnailFormat,
+    /// Page de code à privilégier pour les textes « Latin-1 » mal déclarés
+    pub fallback_encoding: Option<&'static Encoding>,
+    /// Taille totale du fichier, quand `bytes` n'en est qu'un extrait (estimation de durée)
+    pub file_size: Option<u64>,
+    /// Remplir `seek_points`
+    pub include_seek_points: bool,
+    /// Octets explorés à la recherche de la première trame MPEG
+    pub mpeg_scan_bytes: usize,
+}
+
+impl ParseOptions {
+    pub fn with_limits(max_text_bytes: usize, max_cover_bytes: usize) -> Self {
+        Self {
+            max_text_bytes,
+            max_cover_bytes,
+            include_pictures: false,
+            include_picture_data: false,
+            include_cover_data: true,
+            cover_refs: false,
+            thumbnail_sizes: Vec::new(),
+            thumbnail_format: ThumbnailFormat::Png,
+            fallback_encoding: None,
+            file_size: None,
+            include_seek_points: false,
+            mpeg_scan_bytes: MPEG_SCAN_BYTES,
+        }
+    }
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self::with_limits(MAX_TEXT_BYTES, MAX_COVER_BYTES)
+    }
+}
+
+/// Conteneur reconnu à la signature du fichier
+#[derive(Serialize, TS, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+#[ts(export, export_to = "metadata.ts", rename = "MetadataFormat")]
+pub enum Format {
+    Flac,
+    Ogg,
+    /// MP4 / M4A (AAC, ALAC…), reconnu à son atome `ftyp`
+    Mp4,
+    /// MPEG audio, avec ou sans tags ID3 ; choix par défaut sans signature reconnue
+    Mpeg,
+}
+
+impl Format {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Flac => "flac",
+            Self::Ogg => "ogg",
+            Self::Mp4 => "mp4",
+            Self::Mpeg => "mpeg",
+        }
+    }
+
+    /// Conteneur d'après la signature des premiers octets
+    pub fn detect(bytes: &[u8]) -> Self {
+        match bytes.get(0..4) {
+            Some(magic) if magic == FLAC_SIGNATURE => Self::Flac,
+            Some(magic) if magic == OGG_SIGNATURE => Self::Ogg,
+            _ if bytes.get(4..8) == Some(MP4_SIGNATURE) => Self::Mp4,
+            _ => Self::Mpeg,
+        }
+    }
+}
+
+/// Analyser un fichier (ou son début) et renvoyer ses métadonnées.
+/// Échoue si aucun tag ni aucune trame audio n'a été trouvé.
+pub fn parse(bytes: &[u8], options: &ParseOptions) -> Result<model::Metadata, Error> {
+    let mut meta = Metadata::new(options.fallback_encoding);
+    let format = parse_container(bytes, options, &mut meta);
+    if format == Format::Mpeg && !meta.found_mpeg_data {
+        return Err(Error::UnrecognizedFormat);
+    }
+    Ok(build_metadata(&meta, options))
+}
+
+/// Vue « débogueur de tags » d'un fichier
+#[derive(Serialize, TS)]
+#[ts(export, export_to = "metadata.ts", rename = "MetadataInspection")]
+pub struct Inspection {
+    pub format: Format,
+    /// Éléments structurels de premier niveau (tags, blocs, trame MPEG)
+    pub nodes: Vec<InspectNode>,
+    pub metadata: model::Metadata,
+}
+
+/// Chaque élément structurel rencontré (tag, frame, bloc, clé, trame de synchronisation)
+/// avec sa position, le champ qu'il a alimenté ou la raison pour laquelle il a été écarté.
+pub fn inspect(bytes: &[u8]) -> Inspection {
+    let options = ParseOptions::default();
+    let mut meta = Metadata::new(None);
+    meta.inspector = Some(Inspector::new());
+    let format = parse_container(bytes, &options, &mut meta);
+    Inspection {
+        format,
+        nodes: meta
+            .inspector
+            .take()
+            .map(Inspector::finish)
+            .unwrap_or_default(),
+        metadata: build_metadata(&meta, &options),
+    }
+}
+
+/// Contrôle qualité d'un MP3 complet : parcours de toutes les trames audio
+/// (débits, modes de canaux, CRC, pertes de synchronisation).
+pub fn scan_mpeg_frames(bytes: &[u8]) -> FrameScan {
+    let mut start = 0;
+    for _ in 0..MAX_CHAINED_ID3_TAGS {
+        if !is_id3v2_header(bytes, start) {
+            break;
+        }
+        start += id3v2_tag_len(&bytes[start..]);
+    }
+    let end = find_appended_id3v2(bytes).unwrap_or_else(|| trailing_tags_start(bytes));
+    scan_frames(bytes, start.min(end), end)
+}
+
+/// Palette d'une pochette encodée (JPEG / PNG)
+pub fn extract_cover_palette(bytes: &[u8]) -> Result<Palette, Error> {
+    let image = decode_rgba(bytes).ok_or(Error::UndecodableImage)?;
+    extract_palette(&image).ok_or(Error::TransparentImage)
+}
+
+/// Palette d'une image déjà décodée en RVBA 8 bits (par exemple une miniature `rgba`)
+pub fn extract_palette_rgba(pixels: &[u8], width: u32, height: u32) -> Result<Palette, Error> {
+    // Sur wasm32, des dimensions aberrantes dépasseraient `usize`
+    let expected = (width as usize)
+        .checked_mul(height as usize)
+        .and_then(|count| count.checked_mul(4));
+    if width == 0 || height == 0 || expected != Some(pixels.len()) {
+        return Err(Error::InvalidPixelBuffer {
+            width,
+            height,
+            length: pixels.len(),
+        });
+    }
+    let image = RgbaImage {
+        width,
+        height,
+        pixels: pixels.to_vec(),
+    };
+    extract_palette(&image).ok_or(Error::TransparentImage)
+}
+
+/// Options de lecture d'une feuille CUE externe
+#[derive(Clone, Debug)]
+pub struct CueSheetOptions {
+    /// Taux d'échantillonnage du fichier audio, 44100 par défaut
+    pub sample_rate: u32,
+    /// Durée du fichier audio, pour connaître la fin de la dernière piste
+    pub duration_ms: Option<f64>,
+    /// Page de code privilégiée pour un .cue qui n'est pas en UTF-8
+    pub fallback_encoding: Option<&'static Encoding>,
+}
+
+impl Default for CueSheetOptions {
+    fn default() -> Self {
+        Self {
+            sample_rate: CD_SAMPLE_RATE,
+            duration_ms: None,
+            fallback_encoding: None,
+        }
+    }
+}
+
+/// Feuille CUE externe (fichier .cue) : même structure que `cue_sheet` du résultat,
+/// avec l'encodage détecté du texte.
+pub fn parse_cue_sheet(bytes: &[u8], options: &CueSheetOptions) -> Result<model::CueSheet, Error> {
+    let sample_rate = options.sample_rate.max(1);
+    let total_samples = options
+        .duration_ms
+        .map(|ms| (ms * sample_rate as f64 / 1000.0) as u64);
+    let (text, encoding) = decode_cue_bytes(bytes, options.fallback_encoding);
+    let sheet = parse_cue_text(&text, sample_rate).ok_or(Error::InvalidCueSheet)?;
+    let mut sheet = build_cue_sheet(&sheet, total_samples);
+    sheet.encoding = Some(encoding.to_string());
+    Ok(sheet)
+}
+
+/// Écrire le texte d'une feuille CUE
+pub fn write_cue_sheet(sheet: &CueSheet) -> String {
+    write_cue_text(sheet)
+}
+
+/// Aiguiller vers le parseur du conteneur reconnu à sa signature
+fn parse_container<'a>(bytes: &'a [u8], options: &ParseOptions, meta: &mut Metadata<'a>) -> Format {
+    let format = Format::detect(bytes);
+    match format {
+        Format::Flac => {
+            let len = bytes.len();
+            let read = |range: Range<usize>| &bytes[range.start.min(len)..range.end.min(len)];
+            parse_flac(read, len, options, meta)
+        }
+        Format::Ogg => parse_ogg(bytes, bytes, options, meta),
+        Format::Mp4 => parse_mp4(bytes, options, meta),
+        Format::Mpeg => parse_mp3(bytes, options, meta),
+    }
+    format
+}
+
+/// Texte d'un fichier .cue : BOM UTF-8 / UTF-16, sinon UTF-8 valide ou page de code détectée
+fn decode_cue_bytes(bytes: &[u8], fallback: Option<&'static Encoding>) -> (String, &'static str) {
+    if let Some(rest) = bytes.strip_prefix(b"\xEF\xBB\xBF") {
+        return (String::from_utf8_lossy(rest).into_owned(), "UTF-8");
+    }
+    if bytes.starts_with(b"\xFF\xFE") || bytes.starts_with(b"\xFE\xFF") {
+        let encoding = if bytes[0] == 0xFF {
+            "UTF-16LE"
+        } else {
+            "UTF-16BE"
+        };
+        return (decode_utf16_with_bom(bytes).unwrap_or_default(), encoding);
+    }
+    let mut decoder = LegacyTextDecoder::new(fallback);
+    let text = decoder.decode(bytes);
+    (text, decoder.chosen_encoding().unwrap_or("UTF-8"))
+}
+
+fn build_metadata(meta: &Metadata, options: &ParseOptions) -> model::Metadata {
+    let audio_info = &meta.audio_info;
+    let mut result = model::Metadata {
+        title: join_values(&meta.title),
+        artist: join_values(&meta.artist),
+        album: join_values(&meta.album),
+        titles: meta.title.clone(),
+        artists: meta.artist.clone(),
+        albums: meta.album.clone(),
+        audio: model::AudioProperties {
+            sample_rate: audio_info.sample_rate,
+            bit_depth: audio_info.bit_depth,
+            bitrate: audio_info.bitrate,
+            channels: audio_info.channels,
+            duration_ms: audio_info.duration_ms,
+            bitrate_mode: audio_info.bitrate_mode,
+        },
+        mpeg: audio_info.mpeg_frame.as_ref().map(build_mpeg_info),
+        flac: audio_info
+            .flac
+            .as_ref()
+            .map(|flac| build_flac_info(flac, &audio_info.flac_blocks, audio_info.sample_rate)),
+        gapless: meta.gapless.as_ref().map(build_gapless),
+        cue_sheet: meta.cue_sheet.as_ref().map(|sheet| {
+            let total_samples = audio_info.flac.as_ref().map(|flac| flac.total_samples);
+            build_cue_sheet(sheet, total_samples)
+        }),
+        lyrics: model::Lyrics {
+            lyrics: meta.lyrics.clone(),
+            synced_lyrics: meta.synced_lyrics.clone(),
+        },
+        encoder: (!meta.encoder.is_empty()).then(|| build_encoder(&meta.encoder)),
+        legacy_encoding: meta.legacy_text.chosen_encoding().map(str::to_string),
+        warnings: meta.warnings.iter().map(build_warning).collect(),
+        ..model::Metadata::default()
+    };
+    if let Some(cover) = meta.cover(options.max_cover_bytes) {
+        result.cover_mime = Some(cover.mime.clone());
+        result.cover_ref = build_data_ref(cover, options);
+        if options.include_cover_data && result.cover_ref.is_none() && cover.is_complete() {
+            result.cover_data = Some(cover.data.to_vec());
+        }
+        result.cover_type = Some(cover.pic_type);
+        if !options.thumbnail_sizes.is_empty() && cover.is_complete() {
+            result.cover_thumbnails = build_thumbnails(&cover.data, options);
+        }
+    }
+    if options.include_pictures {
+        result.pictures = meta
+            .pictures
+            .iter()
+            .map(|picture| build_picture(picture, options))
+            .collect();
+        result.picture_links = meta.picture_links.iter().map(build_picture_link).collect();
+    }
+    if options.include_seek_points {
+        result.seek_points = audio_info
+            .seek_points
+            .iter()
+            .map(|point| model::SeekPoint {
+                time_ms: point.time_ms,
+                offset: point.offset,
+            })
+            .collect();
+    }
+    result
+}
+
+/// Chaîne d'affichage d'un champ multi-valué : valeurs jointes, absente si vide
+fn join_values(values: &[String]) -> Option<String> {
+    (!values.is_empty()).then(|| values.join(MULTI_VALUE_SEPARATOR))
+}
+
+fn build_picture(picture: &Picture, options: &ParseOptions) -> model::Picture {
+    let data_ref = build_data_ref(picture, options);
+    let include_data = options.include_picture_data
+        && data_ref.is_none()
+        && picture.is_complete()
+        && picture.length <= options.max_cover_bytes;
+    model::Picture {
+        pic_type: picture.pic_type,
+        type_name: picture_type_name(picture.pic_type).to_string(),
+        description: picture.description.clone(),
+        mime: picture.mime.clone(),
+        declared_mime: (picture.declared_mime != picture.mime)
+            .then(|| picture.declared_mime.clone()),
+        width: picture.width,
+        height: picture.height,
+        color_depth: picture.color_depth,
+        byte_length: picture.length,
+        data: include_data.then(|| picture.data.to_vec()),
+        data_ref,
+    }
+}
+
+fn build_data_ref(picture: &Picture, options: &ParseOptions) -> Option<model::DataRef> {
+    let span = picture.span.filter(|_| options.cover_refs)?;
+    Some(model::DataRef {
+        offset: span.offset as u64,
+        length: span.length,
+        unsynchronised: span.unsynchronised,
+    })
+}
+
+/// Décoder la pochette et produire une miniature par taille demandée.
+/// L'image pleine taille ne quitte jamais le module WASM.
+fn build_thumbnails(data: &[u8], options: &ParseOptions) -> Vec<model::Thumbnail> {
+    let Some(image) = decode_rgba(data) else {
+        return Vec::new();
+    };
+    let mut thumbnails = Vec::new();
+    for &size in &options.thumbnail_sizes {
+        let thumbnail = resize_to_fit(&image, size);
+        let (mime, data) = match options.thumbnail_format {
+            ThumbnailFormat::Png => {
+                let Some(encoded) = encode_png(&thumbnail) else {
+                    continue;
+                };
+                (Some("image/png".to_string()), encoded)
+            }
+            ThumbnailFormat::Rgba => (None, thumbnail.pixels),
+        };
+        thumbnails.push(model::Thumbnail {
+            size,
+            width: thumbnail.width,
+            height: thumbnail.height,
+            format: options.thumbnail_format,
+            mime,
+            data,
+        });
+    }
+    thumbnails
+}
+
+fn build_gapless(gapless: &GaplessInfo) -> model::Gapless {
+    model::Gapless {
+        delay: gapless.delay,
+        padding: gapless.padding,
+        valid_samples: gapless.valid_samples,
+        source: gapless.source.to_string(),
+    }
+}
+
+fn build_encoder(encoder: &EncoderInfo) -> model::Encoder {
+    model::Encoder {
+        name: encoder.name.clone(),
+        vbr_method: encoder.vbr_method.map(str::to_string),
+        preset: encoder.preset.clone(),
+        lowpass: encoder.lowpass_hz,
+        quality: encoder.quality,
+        settings: encoder.settings.clone(),
+        encoded_by: encoder.encoded_by.clone(),
+    }
+}
+
+fn build_mpeg_info(frame: &MpegFrame) -> model::MpegInfo {
+    // L'extension de mode n'a de sens qu'en joint stereo
+    let mode_extension = (frame.channel_mode == 1).then(|| {
+        if frame.layer_idx == 1 {
+            model::ModeExtension {
+                ms_stereo: frame.mode_extension & 0x02 != 0,
+                intensity_stereo: frame.mode_extension & 0x01 != 0,
+                intensity_bound: None,
+            }
+        } else {
+            // Layer I / II : intensity stereo à partir de la sous-bande 4, 8, 12 ou 16
+            model::ModeExtension {
+                ms_stereo: false,
+                intensity_stereo: true,
+                intensity_bound: Some((frame.mode_extension as u32 + 1) * 4),
+            }
+        }
+    });
+    model::MpegInfo {
+        format: frame.format_name(),
+        version: frame.version_name().to_string(),
+        layer: frame.layer(),
+        channel_mode: frame.channel_mode_name().to_string(),
+        mode_extension,
+        emphasis: frame.emphasis_name().to_string(),
+        copyright: frame.copyright,
+        original: frame.original,
+        crc_protected: frame.protected,
+    }
+}
+
+fn build_flac_info(
+    flac: &FlacStreamInfo,
+    blocks: &FlacBlocks,
+    sample_rate: Option<u32>,
+) -> model::FlacInfo {
+    let seek_table = blocks
+        .seek_table
+        .iter()
+        .map(|point| model::FlacSeekPoint {
+            sample: point.sample,
+            offset: point.offset,
+            frame_samples: point.frame_samples,
+            time_ms: sample_rate
+                .filter(|&rate| rate > 0)
+                .map(|rate| point.sample.saturating_mul(1000) / rate as u64),
+        })
+        .collect();
+    model::FlacInfo {
+        min_block_size: flac.min_block_size,
+        max_block_size: flac.max_block_size,
+        min_frame_size: (flac.min_frame_size > 0).then_some(flac.min_frame_size),
+        max_frame_size: (flac.max_frame_size > 0).then_some(flac.max_frame_size),
+        total_samples: (flac.total_samples > 0).then_some(flac.total_samples),
+        md5: flac
+            .md5
+            .iter()
+            .any(|&b| b != 0)
+            .then(|| flac.md5.iter().map(|b| format!("{b:02x}")).collect()),
+        seek_table,
+        application_ids: blocks.application_ids.clone(),
+        padding_bytes: blocks.padding_bytes,
+        audio_offset: blocks.audio_offset,
+    }
+}
+
+fn build_cue_sheet(sheet: &CueSheet, total_samples: Option<u64>) -> model::CueSheet {
+    let tracks = sheet
+        .tracks
+        .iter()
+        .enumerate()
+        .map(|(index, track)| {
+            let start = track.start();
+            let end = sheet.track_end(index, total_samples);
+            model::CueTrack {
+                number: track.number,
+                isrc: track.isrc.clone(),
+                title: track.title.clone(),
+                performer: track.performer.clone(),
+                file: track.file.clone(),
+                audio: track.is_audio,
+                pre_emphasis: track.pre_emphasis,
+                flags: track.flags.clone(),
+                track_gain: track.track_gain,
+                track_peak: track.track_peak,
+                start_sample: start,
+                start_ms: start.map(|start| sheet.samples_to_ms(start)),
+                end_sample: end,
+                end_ms: end.map(|end| sheet.samples_to_ms(end)),
+                pre_gap_ms: track.pre_gap().map(|gap| sheet.samples_to_ms(gap)),
+                indices: track
+                    .indices
+                    .iter()
+                    .map(|point| model::CueIndex {
+                        number: point.number,
+                        sample: point.offset,
+                        offset_ms: sheet.samples_to_ms(point.offset),
+                    })
+                    .collect(),
+            }
+        })
+        .collect();
+    model::CueSheet {
+        catalog: sheet.catalog.clone(),
+        title: sheet.title.clone(),
+        performer: sheet.performer.clone(),
+        file: sheet.file.clone(),
+        genre: sheet.genre.clone(),
+        date: sheet.date.clone(),
+        comment: sheet.comment.clone(),
+        album_gain: sheet.album_gain,
+        album_peak: sheet.album_peak,
+        lead_in_samples: sheet.lead_in,
+        is_cd: sheet.is_cd,
+        tracks,
+        encoding: None,
+    }
+}
+
+fn build_warning(warning: &Warning) -> model::Warning {
+    model::Warning {
+        code: warning.code.to_string(),
+        message: warning.message.clone(),
+        offset: warning.offset,
+    }
+}
+
+fn build_picture_link(link: &PictureLink) -> model::PictureLink {
+    model::PictureLink {
+        pic_type: link.pic_type,
+        type_name: picture_type_name(link.pic_type).to_string(),
+        description: link.description.clone(),
+        url: link.url.clone(),
+    }
+}
+
+fn picture_type_name(pic_type: u8) -> &'static str {
+    PICTURE_TYPE_NAMES
+        .get(pic_type as usize)
+        .copied()
+        .unwrap_or("Unknown")
+}
+
+// ---------------------------------------------------------------------------
+// MP3 / ID3
+// ---------------------------------------------------------------------------
+
+fn parse_mp3<'a>(bytes: &'a [u8], options: &ParseOptions, meta: &mut Metadata<'a>) {
+    // Octets audio jusqu'aux tags de fin, ou jusqu'à la fin du fichier pour un extrait
+    let audio_end = match options.file_size {
+        Some(size) if size > bytes.len() as u64 => size,
+        _ => trailing_tags_start(bytes) as u64,
+    };
+    parse_mp3_head(bytes, audio_end, options, meta);
+    parse_mp3_trailing(bytes, 0, options, meta);
+}
+
+/// Tag ID3v2 de tête et première trame MPEG. `audio_end` borne le flux audio pour
+/// l'estimation de la durée.
+fn parse_mp3_head<'a>(
+    bytes: &'a [u8],
+    audio_end: u64,
+    options: &ParseOptions,
+    meta: &mut Metadata<'a>,
+) {
+    let mut mpeg_scan_start: usize = 0;
+
+    if is_id3v2_header(bytes, 0) {
+        mpeg_scan_start = id3v2_tag_len(bytes);
+        parse_id3v2_chain(bytes, 0, options, meta);
+    }
+
+    // Essayer de trouver le premier en-tête de trame MPEG pour le taux d'échantillonnage / débit / canaux
+    if meta.audio_info.sample_rate.is_none()
+        && let Some(frame) = find_mpeg_frame(bytes, mpeg_scan_start, options.mpeg_scan_bytes)
+    {
+        meta.found_mpeg_data = true;
+        let audio_info = &mut meta.audio_info;
+        audio_info.sample_rate = Some(frame.sample_rate);
+        // Pas de profondeur de bits : le format avec perte n'en a pas, le PCM décodé dépend du décodeur
+        audio_info.bitrate = Some(frame.bitrate_kbps);
+        audio_info.channels = Some(frame.channel_count() as u8);
+        audio_info.mpeg_frame = Some(frame);
+        meta.trace_open(|| {
+            InspectNode::new(
+                "mpeg-frame",
+                frame.format_name(),
+                frame.offset,
+                frame.frame_len,
+            )
+            .detail("bitrate", frame.bitrate_kbps)
+            .detail("sampleRate", frame.sample_rate)
+            .detail("channelMode", frame.channel_mode_name())
+            .usage(Usage::Mapped("mpeg"))
+        });
+        parse_mpeg_stream_info(bytes, &frame, audio_end, meta);
+        meta.trace_close();
+    }
+}
+
+/// Tags de fin de fichier : ID3v2.4 ajouté (repéré par son pied de page « 3DI »), puis ID3v1.
+/// `tail` se termine à la fin du fichier et commence à la position `tail_offset`.
+/// Le tag de tête reste prioritaire : on ne complète que les champs manquants.
+fn parse_mp3_trailing<'a>(
+    tail: &'a [u8],
+    tail_offset: usize,
+    options: &ParseOptions,
+    meta: &mut Metadata<'a>,
+) {
+    let previous_base = meta.set_offset_base(meta.offset_base.map(|base| base + tail_offset));
+    // Un tag ajouté qui commence le fichier est le tag de tête, déjà lu
+    if let Some(tag_start) = find_appended_id3v2(tail)
+        && tail_offset + tag_start != 0
+    {
+        parse_id3v2_chain(tail, tag_start, options, meta);
+    }
+
+    // Le tag ID3v1 est aussi signalé à l'inspecteur quand ID3v2 a déjà tout fourni
+    if tail.len() >= ID3V1_SIZE {
+        parse_id3v1(tail, meta);
+    }
+    meta.set_offset_base(previous_base);
+}
+
+/// Durée, débit moyen et mode de débit du flux MPEG, d'après l'en-tête Xing / Info ou VBRI
+/// de la première trame ; à défaut, estimation à débit constant sur la taille du flux audio.
+fn parse_mpeg_stream_info(
+    bytes: &[u8],
+    frame: &MpegFrame,
+    audio_end: u64,
+    meta: &mut Metadata<'_>,
+) {
+    let frame_bytes = &bytes[frame.offset..];
+    let samples_per_frame = frame.samples_per_frame() as u64;
+    let sample_rate = frame.sample_rate as u64;
+    // Octets audio entre la première trame et les tags de fin
+    let stream_bytes = audio_end.saturating_sub(frame.offset as u64);
+    let frames_duration_ms = |frames: u64| frames * samples_per_frame * 1000 / sample_rate;
+
+    let xing = if frame.layer_idx == 1 {
+        parse_xing_header(frame_bytes, frame.data_offset(), frame.side_info_len())
+    } else {
+        None
+    };
+
+    if let Some(xing) = xing {
+        // La première trame d'un fichier LAME porte aussi l'extension LAME
+        let encoder = &mut meta.encoder;
+        encoder.quality = xing.quality;
+        let mut bitrate_mode = if xing.is_info {
+            BitrateMode::Cbr
+        } else {
+            BitrateMode::Vbr
+        };
+        if let Some(lame) = &xing.lame
+            && (lame.delay > 0 || lame.padding > 0)
+            && meta.gapless.is_none()
+        {
+            // Le décodeur produit `frames × échantillons par trame` échantillons ;
+            // son propre retard (529) s'annule entre début et fin
+            let total = xing.frames.map(|frames| frames as u64 * samples_per_frame);
+            meta.gapless = Some(GaplessInfo {
+                delay: lame.delay,
+                padding: Some(lame.padding),
+                valid_samples: total
+                    .map(|total| total.saturating_sub((lame.delay + lame.padding) as u64)),
+                source: "lame",
+            });
+        }
+        if let Some(lame) = xing.lame {
+            encoder.name = Some(lame.version);
+            encoder.vbr_method = lame.vbr_method;
+            encoder.preset = lame.preset;
+            encoder.lowpass_hz = lame.lowpass_hz;
+            bitrate_mode = lame.bitrate_mode.unwrap_or(bitrate_mode);
+        }
+        meta.audio_info.bitrate_mode = Some(bitrate_mode);
+        let xing_offset = frame.data_offset() + frame.side_info_len();
+        meta.trace(|| {
+            let id = if xing.is_info { "Info" } else { "Xing" };
+            let mut node = InspectNode::new(
+                "xing",
+                id,
+                frame.offset + xing_offset,
+                frame.frame_len.saturating_sub(xing_offset),
+            )
+            .usage(Usage::Mapped("durationMs"));
+            if let Some(frames) = xing.frames {
+                node = node.detail("frames", frames);
+            }
+            if let Some(bytes) = xing.bytes {
+                node = node.detail("bytes", bytes);
+            }
+            node
+        });
+        let audio_info = &mut meta.audio_info;
+
+        if let Some(frames) = xing.frames {
+            let duration_ms = frames_duration_ms(frames as u64);
+            let total_bytes = xing.bytes.map_or(stream_bytes, |b| b as u64);
+            audio_info.duration_ms = Some(duration_ms);
+            if let Some(bitrate) = (total_bytes * 8).checked_div(duration_ms) {
+                audio_info.bitrate = Some(bitrate as u32);
+            }
+            if let Some(toc) = xing.toc {
+                audio_info.seek_points = toc
+                    .iter()
+                    .enumerate()
+                    .map(|(percent, &position)| SeekPoint {
+                        time_ms: duration_ms * percent as u64 / 100,
+                        offset: frame.offset as u64 + position as u64 * total_bytes / 256,
+                    })
+                    .collect();
+            }
+        }
+    } else if frame.layer_idx == 1
+        && let Some(vbri) = parse_vbri_header(frame_bytes, frame.data_offset())
+    {
+        let duration_ms = frames_duration_ms(vbri.frames as u64);
+        let vbri_offset = frame.data_offset() + 32;
+        meta.trace(|| {
+            InspectNode::new(
+                "vbri",
+                "VBRI",
+                frame.offset + vbri_offset,
+                frame.frame_len.saturating_sub(vbri_offset),
+            )
+            .detail("frames", vbri.frames)
+            .usage(Usage::Mapped("durationMs"))
+        });
+        let audio_info = &mut meta.audio_info;
+        audio_info.bitrate_mode = Some(BitrateMode::Vbr);
+        audio_info.duration_ms = Some(duration_ms);
+        if vbri.bytes > 0
+            && let Some(bitrate) = (vbri.bytes as u64 * 8).checked_div(duration_ms)
+        {
+            audio_info.bitrate = Some(bitrate as u32);
+        }
+        // Chaque entrée donne la taille d'un segment de `frames_per_entry` trames
+        let mut offset = frame.offset as u64;
+        audio_info
+            .seek_points
+            .push(SeekPoint { time_ms: 0, offset });
+        for (index, &size) in vbri.toc.iter().enumerate() {
+            offset += size as u64;
+            audio_info.seek_points.push(SeekPoint {
+                time_ms: frames_duration_ms((index as u64 + 1) * vbri.frames_per_entry as u64),
+                offset,
+            });
+        }
+    }
+
+    // Sans en-tête : on suppose un débit constant égal à celui de la première trame
+    let audio_info = &mut meta.audio_info;
+    if audio_info.duration_ms.is_none() && frame.bitrate_kbps > 0 {
+        audio_info.duration_ms = Some(stream_bytes * 8 / frame.bitrate_kbps as u64);
+        audio_info.bitrate_mode.get_or_insert(BitrateMode::Cbr);
+    }
+}
+
+fn is_id3v2_header(bytes: &[u8], offset: usize) -> bool {
+    bytes.len() >= offset + ID3V2_HEADER_SIZE && &bytes[offset..offset + 3] == b"ID3"
+}
+
+/// Taille totale d'un tag ID3v2 : en-tête + corps + pied de page éventuel (v2.4, drapeau 0x10).
+fn id3v2_tag_len(tag: &[u8]) -> usize {
+    let size = synchsafe_to_u32(&tag[6..10]) as usize;
+    let footer = if tag[3] >= 4 && tag[5] & 0x10 != 0 {
+        ID3V2_HEADER_SIZE
+    } else {
+        0
+    };
+    ID3V2_HEADER_SIZE + size + footer
+}
+
+/// Analyser le tag ID3v2 situé à `tag_start`, puis suivre les frames SEEK vers les tags suivants.
+fn parse_id3v2_chain<'a>(
+    bytes: &'a [u8],
+    tag_start: usize,
+    options: &ParseOptions,
+    meta: &mut Metadata<'a>,
+) {
+    let mut visited: Vec<usize> = Vec::new();
+    let mut next = Some(tag_start);
+
+    while let Some(start) = next {
+        if visited.contains(&start)
+            || visited.len() >= MAX_CHAINED_ID3_TAGS
+            || !is_id3v2_header(bytes, start)
+        {
+            break;
+        }
+        visited.push(start);
+        meta.found_mpeg_data = true;
+
+        let tag = &bytes[start..];
+        let previous_base = meta.set_offset_base(meta.offset_base.map(|base| base + start));
+        meta.trace_open(|| {
+            InspectNode::new(
+                "id3v2",
+                format!("ID3v2.{}.{}", tag[3], tag[4]),
+                0,
+                id3v2_tag_len(tag),
+            )
+            .detail("flags", format!("0x{:02x}", tag[5]))
+        });
+        // Un tag qui dépasse l'extrait transmis n'est tronqué que s'il dépasse aussi le fichier
+        let file_start = previous_base.unwrap_or(0) + start;
+        let available = options
+            .file_size
+            .unwrap_or((previous_base.unwrap_or(0) + bytes.len()) as u64)
+            .saturating_sub(file_start as u64);
+        if id3v2_tag_len(tag) as u64 > available {
+            let message = format!(
+                "ID3v2 tag declares {} bytes but only {available} remain",
+                id3v2_tag_len(tag)
+            );
+            meta.warn("truncated-tag", 0, message);
+        }
+        let seek = parse_id3v2(tag, options, meta);
+        meta.trace_close();
+        meta.set_offset_base(previous_base);
+
+        // L'offset SEEK est compté depuis la fin du tag courant
+        next = seek.map(|offset| start + id3v2_tag_len(tag) + offset);
+    }
+}
+
+/// Localiser un tag ID3v2 ajouté en fin de fichier.
+/// Les tags de fin (ID3v1, Lyrics3v2, APE) sont retirés un à un, puis on cherche
+/// le pied de page « 3DI » qui permet de remonter à l'en-tête du tag.
+fn find_appended_id3v2(bytes: &[u8]) -> Option<usize> {
+    let end = trailing_tags_start(bytes);
+    if end < 2 * ID3V2_HEADER_SIZE {
+        return None;
+    }
+    let footer = &bytes[end - ID3V2_HEADER_SIZE..end];
+    if &footer[0..3] != b"3DI" {
+        return None;
+    }
+    let size = synchsafe_to_u32(&footer[6..10]) as usize;
+    let start = end.checked_sub(2 * ID3V2_HEADER_SIZE + size)?;
+    is_id3v2_header(bytes, start).then_some(start)
+}
+
+/// Position où commencent les tags de fin de fichier (ID3v1, Lyrics3v2, APE),
+/// ou `bytes.len()` s'il n'y en a aucun.
+fn trailing_tags_start(bytes: &[u8]) -> usize {
+    let mut end = bytes.len();
+    while let Some(start) = trailing_tag_len(&bytes[..end]).and_then(|len| end.checked_sub(len)) {
+        end = start;
+    }
+    end
+}
+
+/// Taille du tag de fin (ID3v1, APE, Lyrics3v2) qui se termine avec `bytes`.
+/// Seuls les `ID3V1_SIZE` derniers octets sont examinés.
+fn trailing_tag_len(bytes: &[u8]) -> Option<usize> {
+    let end = bytes.len();
+    if end >= ID3V1_SIZE && &bytes[end - ID3V1_SIZE..end - ID3V1_SIZE + 3] == b"TAG" {
+        return Some(ID3V1_SIZE);
+    }
+    if end >= APE_FOOTER_SIZE && &bytes[end - APE_FOOTER_SIZE..end - 24] == b"APETAGEX" {
+        let footer = &bytes[end - APE_FOOTER_SIZE..end];
+        // La taille inclut les items et le pied de page, mais pas l'en-tête optionnel
+        let size = le_u32(&footer[12..16]) as usize;
+        let has_header = le_u32(&footer[20..24]) & 0x8000_0000 != 0;
+        let total = size + if has_header { APE_FOOTER_SIZE } else { 0 };
+        return (total >= APE_FOOTER_SIZE).then_some(total);
+    }
+    if end >= 15 && &bytes[end - 9..end] == LYRICS3V2_END {
+        // Taille du tag Lyrics3v2 : 6 chiffres ASCII précédant « LYRICS200 »
+        let digits = &bytes[end - 15..end - 9];
+        return std::str::from_utf8(digits)
+            .ok()
+            .and_then(|d| d.parse::<usize>().ok())
+            .map(|size| size + 15);
+    }
+    None
+}
+
+/// Analyser un tag ID3v2 commençant au début de `bytes`.
+/// Renvoie l'offset de la frame SEEK (v2.4) s'il y en a une.
+fn parse_id3v2<'a>(
+    bytes: &'a [u8],
+    options: &ParseOptions,
+    meta: &mut Metadata<'a>,
+) -> Option<usize> {
+    if bytes.len() < ID3V2_HEADER_SIZE {
+        return None;
+    }
+    let max_text_bytes = options.max_text_bytes;
+
+    let version = bytes[3];
+    let flags = bytes[5];
+    let size = synchsafe_to_u32(&bytes[6..10]) as usize;
+
+    // ID3v2.2 / 2.3 : tout le tag est désynchronisé, en-têtes de frames compris.
+    // La v2.4 désynchronise chaque frame séparément.
+    let tag_unsync = (version < 4 && flags & 0x80 != 0)
+        .then(|| resynchronise(&bytes[..(ID3V2_HEADER_SIZE + size).min(bytes.len())]))
+        .flatten();
+    let tag: &[u8] = tag_unsync.as_ref().map_or(bytes, |(data, _)| data);
+
+    let mut offset = ID3V2_HEADER_SIZE;
+    if flags & 0x40 != 0 && tag.len() >= offset + 4 && version >= 3 {
+        // La taille v2.4 compte ses propres 4 octets, celle de la v2.3 non
+        let ext_size = if version == 4 {
+            synchsafe_to_u32(&tag[offset..offset + 4]) as usize
+        } else {
+            (be_u32(&tag[offset..offset + 4]) as usize).saturating_add(4)
+        };
+        meta.trace(|| {
+            InspectNode::new("id3v2-extended-header", "extended header", offset, ext_size)
+        });
+        offset = offset.saturating_add(ext_size);
+    }
+
+    // La taille déclarée couvre le tag après son en-tête, en-tête étendu compris
+    let removed = tag_unsync.as_ref().map_or(0, |(_, map)| map.removed());
+    let declared_end = (ID3V2_HEADER_SIZE + size).saturating_sub(removed);
+    let end = declared_end.min(tag.len());
+    let mut seek = None;
+
+    while offset < end {
+        if version == 2 {
+            if offset + 6 > end {
+                break;
+            }
+            let frame_id = &tag[offset..offset + 3];
+            if frame_id == [0, 0, 0] {
+                meta.trace(|| InspectNode::new("padding", "padding", offset, end - offset));
+                break;
+            }
+            let frame_size = ((tag[offset + 3] as usize) << 16)
+                | ((tag[offset + 4] as usize) << 8)
+                | (tag[offset + 5] as usize);
+            let frame_data_offset = offset + 6;
+            if frame_data_offset + frame_size > declared_end {
+                meta.warn(
+                    "frame-overrun",
+                    offset,
+                    frame_overrun_message(frame_id, frame_size),
+                );
+            }
+            if frame_size == 0 || frame_data_offset + frame_size > end {
+                meta.trace(|| {
+                    id3_frame_node(frame_id, offset, 6 + frame_size, None, &[])
+                        .usage(Usage::Skipped(FRAME_OVERRUN))
+                });
+                break;
+            }
+            let (content, origin) = id3_frame_content(
+                bytes,
+                tag_unsync.as_ref(),
+                frame_data_offset..frame_data_offset + frame_size,
+                false,
+                false,
+            );
+            let frame_data: &[u8] = &content;
+            check_id3_text_encoding(frame_id, frame_data, version, offset, meta);
+
+            let usage = match frame_id {
+                b"TT2" if meta.title.is_empty() => {
+                    meta.title =
+                        parse_id3_text_frame(frame_data, max_text_bytes, &mut meta.legacy_text);
+                    Usage::mapped_or(!meta.title.is_empty(), "title", EMPTY_VALUE)
+                }
+                b"TP1" if meta.artist.is_empty() => {
+                    meta.artist = split_slash_values(parse_id3_text_frame(
+                        frame_data,
+                        max_text_bytes,
+                        &mut meta.legacy_text,
+                    ));
+                    Usage::mapped_or(!meta.artist.is_empty(), "artist", EMPTY_VALUE)
+                }
+                b"TAL" if meta.album.is_empty() => {
+                    meta.album =
+                        parse_id3_text_frame(frame_data, max_text_bytes, &mut meta.legacy_text);
+                    Usage::mapped_or(!meta.album.is_empty(), "album", EMPTY_VALUE)
+                }
+                b"PIC" => {
+                    // Format d'image sur trois caractères : seuls PNG et JPG sont normalisés
+                    let format = frame_data.get(1..4).unwrap_or_default();
+                    if !matches!(format, b"PNG" | b"JPG" | b"GIF" | b"-->") {
+                        let format = String::from_utf8_lossy(format);
+                        let message = format!("unknown PIC image format \"{format}\"");
+                        meta.warn("unknown-picture-format", offset, message);
+                    }
+                    match id3_frame_picture(
+                        &content,
+                        &origin,
+                        parse_pic_frame,
+                        &mut meta.legacy_text,
+                    ) {
+                        Some(picture) => meta.add_picture(picture, offset, options.max_cover_bytes),
+                        None => Usage::Skipped(INVALID_PICTURE),
+                    }
+                }
+                b"COM" | b"TXX" if meta.gapless.is_none() => {
+                    meta.gapless = parse_itunsmpb_frame(frame_data, frame_id == b"COM");
+                    Usage::mapped_or(meta.gapless.is_some(), "gapless", UNSUPPORTED)
+                }
+                b"TSS" if meta.encoder.settings.is_none() => {
+                    meta.encoder.settings = first_text_value(frame_data, max_text_bytes, meta);
+                    Usage::mapped_or(
+                        meta.encoder.settings.is_some(),
+                        "encoder.settings",
+                        EMPTY_VALUE,
+                    )
+                }
+                b"TEN" if meta.encoder.encoded_by.is_none() => {
+                    meta.encoder.encoded_by = first_text_value(frame_data, max_text_bytes, meta);
+                    Usage::mapped_or(
+                        meta.encoder.encoded_by.is_some(),
+                        "encoder.encodedBy",
+                        EMPTY_VALUE,
+                    )
+                }
+                b"ULT" => match parse_uslt_frame(frame_data, max_text_bytes, &mut meta.legacy_text)
+                {
+                    Some(text) => meta.offer_lyrics(text),
+                    None => Usage::Skipped(EMPTY_VALUE),
+                },
+                b"TT2" | b"TP1" | b"TAL" | b"TSS" | b"TEN" => Usage::Skipped(ALREADY_SET),
+                _ => Usage::Skipped(UNSUPPORTED),
+            };
+            meta.trace(|| {
+                id3_frame_node(frame_id, offset, 6 + frame_size, None, frame_data).usage(usage)
+            });
+
+            offset = frame_data_offset + frame_size;
+            continue;
+        }
+
+        if offset + 10 > end {
+            break;
+        }
+        let frame_id = &tag[offset..offset + 4];
+        if frame_id == [0, 0, 0, 0] {
+            meta.trace(|| InspectNode::new("padding", "padding", offset, end - offset));
+            break;
+        }
+
+        let frame_size = if version == 4 {
+            synchsafe_to_u32(&tag[offset + 4..offset + 8]) as usize
+        } else {
+            be_u32(&tag[offset + 4..offset + 8]) as usize
+        };
+
+        let frame_flags = &tag[offset + 8..offset + 10];
+        let frame_data_offset = offset + 10;
+
+        if frame_data_offset + frame_size > declared_end {
+            meta.warn(
+                "frame-overrun",
+                offset,
+                frame_overrun_message(frame_id, frame_size),
+            );
+        }
+        if frame_size == 0 || frame_data_offset + frame_size > end {
+            meta.trace(|| {
+                id3_frame_node(frame_id, offset, 10 + frame_size, Some(frame_flags), &[])
+                    .usage(Usage::Skipped(FRAME_OVERRUN))
+            });
+            break;
+        }
+
+        // v2.4 : compression (0x08) et chiffrement (0x04) ne sont pas pris en charge ;
+        // désynchronisation (0x02) et indicateur de longueur (0x01) le sont
+        let unsupported_flags = if version == 4 { 0x0C } else { 0x0F };
+        if frame_flags[1] & unsupported_flags != 0 {
+            meta.trace(|| {
+                id3_frame_node(
+                    frame_id,
+                    offset,
+                    10 + frame_size,
+                    Some(frame_flags),
+                    &tag[frame_data_offset..frame_data_offset + frame_size],
+                )
+                .usage(Usage::Skipped(ENCODED_FRAME))
+            });
+            offset = frame_data_offset + frame_size;
+            continue;
+        }
+
+        let (content, origin) = id3_frame_content(
+            bytes,
+            tag_unsync.as_ref(),
+            frame_data_offset..frame_data_offset + frame_size,
+            version == 4 && (flags & 0x80 != 0 || frame_flags[1] & 0x02 != 0),
+            version == 4 && frame_flags[1] & 0x01 != 0,
+        );
+        let frame_data: &[u8] = &content;
+
+        check_id3_text_encoding(frame_id, frame_data, version, offset, meta);
+        let usage = match frame_id {
+            b"TIT2" if meta.title.is_empty() => {
+                meta.title =
+                    parse_id3_text_frame(frame_data, max_text_bytes, &mut meta.legacy_text);
+                Usage::mapped_or(!meta.title.is_empty(), "title", EMPTY_VALUE)
+            }
+            b"TPE1" if meta.artist.is_empty() => {
+                let values =
+                    parse_id3_text_frame(frame_data, max_text_bytes, &mut meta.legacy_text);
+                // ID3v2.3 sépare les artistes par « / », la v2.4 par des octets nuls
+                meta.artist = if version == 3 {
+                    split_slash_values(values)
+                } else {
+                    values
+                };
+                Usage::mapped_or(!meta.artist.is_empty(), "artist", EMPTY_VALUE)
+            }
+            b"TALB" if meta.album.is_empty() => {
+                meta.album =
+                    parse_id3_text_frame(frame_data, max_text_bytes, &mut meta.legacy_text);
+                Usage::mapped_or(!meta.album.is_empty(), "album", EMPTY_VALUE)
+            }
+            b"APIC" => {
+                match id3_frame_picture(&content, &origin, parse_apic_frame, &mut meta.legacy_text)
+                {
+                    Some(picture) => meta.add_picture(picture, offset, options.max_cover_bytes),
+                    None => Usage::Skipped(INVALID_PICTURE),
+                }
+            }
+            b"COMM" | b"TXXX" if meta.gapless.is_none() => {
+                meta.gapless = parse_itunsmpb_frame(frame_data, frame_id == b"COMM");
+                Usage::mapped_or(meta.gapless.is_some(), "gapless", UNSUPPORTED)
+            }
+            b"TSSE" if meta.encoder.settings.is_none() => {
+                meta.encoder.settings = first_text_value(frame_data, max_text_bytes, meta);
+                Usage::mapped_or(
+                    meta.encoder.settings.is_some(),
+                    "encoder.settings",
+                    EMPTY_VALUE,
+                )
+            }
+            b"TENC" if meta.encoder.encoded_by.is_none() => {
+                meta.encoder.encoded_by = first_text_value(frame_data, max_text_bytes, meta);
+                Usage::mapped_or(
+                    meta.encoder.encoded_by.is_some(),
+                    "encoder.encodedBy",
+                    EMPTY_VALUE,
+                )
+            }
+            b"USLT" => match parse_uslt_frame(frame_data, max_text_bytes, &mut meta.legacy_text) {
+                Some(text) => meta.offer_lyrics(text),
+                None => Usage::Skipped(EMPTY_VALUE),
+            },
+            b"SEEK" if version == 4 && frame_data.len() >= 4 => {
+                seek = Some(be_u32(frame_data) as usize);
+                Usage::Mapped("next tag")
+            }
+            b"TIT2" | b"TPE1" | b"TALB" | b"TSSE" | b"TENC" => Usage::Skipped(ALREADY_SET),
+            _ => Usage::Skipped(UNSUPPORTED),
+        };
+        meta.trace(|| {
+            id3_frame_node(
+                frame_id,
+                offset,
+                10 + frame_size,
+                Some(frame_flags),
+                frame_data,
+            )
+            .usage(usage)
+        });
+
+        offset = frame_data_offset + frame_size;
+    }
+
+    seek
+}
+
+/// Origine du contenu d'une frame ID3v2 dans le tag, pour y situer une image
+struct FrameOrigin<'r> {
+    /// Position dans le tag du premier octet d'origine
+    start: usize,
+    /// Position du contenu dans les octets resynchronisés par `map`
+    shift: usize,
+    /// Octets retirés à la resynchronisation, absente si le contenu est repris tel quel
+    map: Option<Cow<'r, UnsyncMap>>,
+}
+
+impl FrameOrigin<'_> {
+    fn raw(start: usize) -> Self {
+        Self {
+            start,
+            shift: 0,
+            map: None,
+        }
+    }
+
+    /// Situer dans le tag des octets désignés dans le contenu de la frame.
+    /// Des octets resynchronisés couvrent aussi les 0x00 retirés.
+    fn locate(&self, span: DataSpan) -> DataSpan {
+        let Some(map) = &self.map else {
+            return DataSpan {
+                offset: self.start + span.offset,
+                ..span
+            };
+        };
+        let start = self.shift + span.offset;
+        let raw = map.raw_range(start..start + span.length);
+        DataSpan {
+            offset: self.start + raw.start,
+            length: raw.len(),
+            unsynchronised: raw.len() != span.length,
+        }
+    }
+}
+
+/// Contenu de la frame située par `range` dans le tag lu (resynchronisé en entier
+/// pour ID3v2.2 / 2.3). Une frame v2.4 désynchronisée est resynchronisée ici, après
+/// son indicateur de longueur. Le contenu reste emprunté à `bytes` s'il est inchangé.
+fn id3_frame_content<'a, 'r>(
+    bytes: &'a [u8],
+    tag_unsync: Option<&'r (Vec<u8>, UnsyncMap)>,
+    range: Range<usize>,
+    frame_unsync: bool,
+    length_indicator: bool,
+) -> (Cow<'a, [u8]>, FrameOrigin<'r>) {
+    if let Some((tag, map)) = tag_unsync {
+        let raw = map.raw_range(range.clone());
+        if raw.len() == range.len() {
+            return (
+                Cow::Borrowed(&bytes[raw.clone()]),
+                FrameOrigin::raw(raw.start),
+            );
+        }
+        let origin = FrameOrigin {
+            start: 0,
+            shift: range.start,
+            map: Some(Cow::Borrowed(map)),
+        };
+        return (Cow::Owned(tag[range].to_vec()), origin);
+    }
+
+    let start = if length_indicator {
+        (range.start + 4).min(range.end)
+    } else {
+        range.start
+    };
+    let raw = &bytes[start..range.end];
+    match frame_unsync.then(|| resynchronise(raw)).flatten() {
+        Some((data, map)) => {
+            let origin = FrameOrigin {
+                start,
+                shift: 0,
+                map: Some(Cow::Owned(map)),
+            };
+            (Cow::Owned(data), origin)
+        }
+        None => (Cow::Borrowed(raw), FrameOrigin::raw(start)),
+    }
+}
+
+/// Image d'une frame APIC / PIC, située dans le tag.
+/// Une frame resynchronisée est copiée : elle n'existe pas telle quelle dans l'entrée.
+fn id3_frame_picture<'a>(
+    content: &Cow<'a, [u8]>,
+    origin: &FrameOrigin,
+    parse: for<'x> fn(&'x [u8], &mut LegacyTextDecoder) -> Option<Picture<'x>>,
+    legacy_text: &mut LegacyTextDecoder,
+) -> Option<Picture<'a>> {
+    let picture = match content {
+        Cow::Borrowed(data) => parse(data, legacy_text)?,
+        Cow::Owned(data) => {
+            let picture = parse(data, legacy_text)?;
+            let span = picture.span;
+            Picture {
+                span,
+                ..picture.into_owned()
+            }
+        }
+    };
+    Some(Picture {
+        span: picture.span.map(|span| origin.locate(span)),
+        ..picture
+    })
+}
+
+fn frame_overrun_message(frame_id: &[u8], frame_size: usize) -> String {
+    let frame_id = String::from_utf8_lossy(frame_id);
+    format!("frame {frame_id} declares {frame_size} bytes, past the end of the tag")
+}
+
+/// Signaler un octet d'encodage inconnu, ou réservé à ID3v2.4 (UTF-16BE, UTF-8)
+fn check_id3_text_encoding(
+    frame_id: &[u8],
+    frame_data: &[u8],
+    version: u8,
+    offset: usize,
+    meta: &mut Metadata<'_>,
+) {
+    let Some(&encoding) = frame_data.first() else {
+        return;
+    };
+    if !has_text_encoding(frame_id) || encoding <= 1 || (encoding <= 3 && version >= 4) {
+        return;
+    }
+    let frame_id = String::from_utf8_lossy(frame_id);
+    let message = if encoding <= 3 {
+        let name = id3_encoding_name(encoding);
+        format!("frame {frame_id} uses {name}, which requires ID3v2.4")
+    } else {
+        format!("frame {frame_id} declares unknown text encoding {encoding}")
+    };
+    meta.warn("invalid-encoding", offset, message);
+}
+
+/// Décoder une frame texte ID3v2 en liste de valeurs.
+/// ID3v2.4 autorise plusieurs valeurs séparées par un caractère nul (1 ou 2 octets selon l'encodage).
+fn parse_id3_text_frame(
+    frame_data: &[u8],
+    max_text_bytes: usize,
+    legacy_text: &mut LegacyTextDecoder,
+) -> Vec<String> {
+    if frame_data.is_empty() {
+        return Vec::new();
+    }
+    let encoding = frame_data[0];
+    let text = &frame_data[1..];
+    let text = if text.len() > max_text_bytes {
+        &text[..max_text_bytes]
+    } else {
+        text
+    };
+
+    let values: Vec<String> = match encoding {
+        0 => text
+            .split(|&b| b == 0)
+            .map(|part| legacy_text.decode(trim_trailing_zeros(part)))
+            .collect(),
+        1 => {
+            // Chaque valeur peut porter son propre BOM ; sinon on garde le boutisme précédent
+            let mut big_endian = false;
+            split_utf16_values(text)
+                .into_iter()
+                .filter_map(|part| {
+                    if part.len() >= 2 && part[0] == 0xFE && part[1] == 0xFF {
+                        big_endian = true;
+                    } else if part.len() >= 2 && part[0] == 0xFF && part[1] == 0xFE {
+                        big_endian = false;
+                    } else if big_endian {
+                        return decode_utf16_be(part);
+                    }
+                    decode_utf16_with_bom(part)
+                })
+                .collect()
+        }
+        2 => split_utf16_values(text)
+            .into_iter()
+            .filter_map(decode_utf16_be)
+            .collect(),
+        3 => text
+            .split(|&b| b == 0)
+            .filter_map(|part| std::str::from_utf8(part).ok())
+            .map(|part| trim_string(part.to_string()))
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    values.into_iter().filter(|v| !v.is_empty()).collect()
+}
+
+/// Lire la valeur iTunSMPB d'une frame COMM (langue sur 3 octets) ou TXXX.
+/// Seul l'ASCII est utile : la description et la valeur sont décodées sans détection d'encodage.
+fn parse_itunsmpb_frame(frame_data: &[u8], has_language: bool) -> Option<GaplessInfo> {
+    let encoding = *frame_data.first()?;
+    let idx = if has_language { 4 } else { 1 };
+    let (desc_end, value_start) = if encoding == 0 || encoding == 3 {
+        let end = find_zero(frame_data, idx)?;
+        (end, end + 1)
+    } else {
+        let end = find_zero_utf16(frame_data, idx)?;
+        (end, end + 2)
+    };
+    let mut ascii = LegacyTextDecoder::new(None);
+    let description = decode_id3_text(encoding, &frame_data[idx..desc_end], &mut ascii)?;
+    if description != "iTunSMPB" {
+        return None;
+    }
+    let value = decode_id3_text(encoding, frame_data.get(value_start..)?, &mut ascii)?;
+    parse_itunsmpb(&value)
+}
+
+/// Décoder la valeur iTunSMPB : champs hexadécimaux « 0 délai remplissage échantillons … »
+fn parse_itunsmpb(value: &str) -> Option<GaplessInfo> {
+    let fields: Vec<u64> = value
+        .split_whitespace()
+        .map(|field| u64::from_str_radix(field, 16))
+        .collect::<Result<_, _>>()
+        .ok()?;
+    if fields.len() < 4 {
+        return None;
+    }
+    Some(GaplessInfo {
+        delay: fields[1] as u32,
+        padding: Some(fields[2] as u32),
+        valid_samples: (fields[3] > 0).then_some(fields[3]),
+        source: "itunsmpb",
+    })
+}
+
+/// Première valeur d'une frame texte (frames à valeur unique : TSSE, TENC…)
+fn first_text_value(
+    frame_data: &[u8],
+    max_text_bytes: usize,
+    meta: &mut Metadata<'_>,
+) -> Option<String> {
+    parse_id3_text_frame(frame_data, max_text_bytes, &mut meta.legacy_text)
+        .into_iter()
+        .next()
+}
+
+/// Découper les valeurs séparées par « / » (convention ID3v2.2 / v2.3 pour les artistes).
+/// Seul un « / » entouré d'espaces sépare deux valeurs : « AC/DC » reste entier.
+fn split_slash_values(values: Vec<String>) -> Vec<String> {
+    values
+        .iter()
+        .flat_map(|value| value.split(" / "))
+        .map(|part| part.trim().to_string())
+        .filter(|part| !part.is_empty())
+        .collect()
+}
+
+fn parse_uslt_frame(
+    frame_data: &[u8],
+    max_text_bytes: usize,
+    legacy_text: &mut LegacyTextDecoder,
+) -> Option<String> {
+    // Structure de la frame USLT :
+    // encodage (1 octet) | langue (3 octets) | descripteur de contenu (terminé par un null) | texte des paroles
+    if frame_data.len() < 5 {
+        return None;
+    }
+    let encoding = frame_data[0];
+    // Ignorer la langue (3 octets)
+    let mut idx = 4;
+
+    // Ignorer le descripteur de contenu (chaîne terminée par un null)
+    if encoding == 0 || encoding == 3 {
+        // Latin1 ou UTF-8 : chercher un octet null unique
+        match find_zero(frame_data, idx) {
+            Some(end) => idx = end + 1,
+            None => return None,
+        }
+    } else {
+        // UTF-16 : chercher deux octets null consécutifs
+        match find_zero_utf16(frame_data, idx) {
+            Some(end) => idx = end + 2,
+            None => return None,
+        }
+    }
+
+    if idx >= frame_data.len() {
+        return None;
+    }
+
+    let text = &frame_data[idx..];
+    let text = if text.len() > max_text_bytes {
+        &text[..max_text_bytes]
+    } else {
+        text
+    };
+
+    decode_id3_text(encoding, text, legacy_text)
+}
+
+/// Décoder une chaîne ID3v2 unique selon son octet d'encodage.
+fn decode_id3_text(
+    encoding: u8,
+    text: &[u8],
+    legacy_text: &mut LegacyTextDecoder,
+) -> Option<String> {
+    match encoding {
+        0 => Some(legacy_text.decode(trim_trailing_zeros(text))),
+        1 => decode_utf16_with_bom(trim_trailing_zeros(text)),
+        2 => decode_utf16_be(trim_trailing_zeros(text)),
+        3 => String::from_utf8(text.to_vec()).ok().map(trim_string),
+        _ => None,
+    }
+}
+
+/// Lire la description (terminée par un caractère nul) d'une frame APIC / PIC.
+/// Renvoie la description et l'index du premier octet de l'image.
+fn read_picture_description(
+    frame_data: &[u8],
+    idx: usize,
+    encoding: u8,
+    legacy_text: &mut LegacyTextDecoder,
+) -> Option<(String, usize)> {
+    let (desc_end, next_idx) = if encoding == 0 || encoding == 3 {
+        let end = find_zero(frame_data, idx)?;
+        (end, end + 1)
+    } else {
+        let end = find_zero_utf16(frame_data, idx)?;
+        (end, end + 2)
+    };
+    if next_idx > frame_data.len() {
+        return None;
+    }
+    let description =
+        decode_id3_text(encoding, &frame_data[idx..desc_end], legacy_text).unwrap_or_default();
+    Some((description, next_idx))
+}
+
+fn parse_apic_frame<'a>(
+    frame_data: &'a [u8],
+    legacy_text: &mut LegacyTextDecoder,
+) -> Option<Picture<'a>> {
+    if frame_data.len() < 4 {
+        return None;
+    }
+    let encoding = frame_data[0];
+    let mut idx = 1;
+
+    let mime_end = find_zero(frame_data, idx)?;
+    let mime = String::from_utf8_lossy(&frame_data[idx..mime_end]).to_string();
+    idx = mime_end + 1;
+
+    if idx >= frame_data.len() {
+        return None;
+    }
+
+    let pic_type = frame_data[idx];
+    idx += 1;
+
+    let (description, next_idx) = read_picture_description(frame_data, idx, encoding, legacy_text)?;
+
+    let img_data = &frame_data[next_idx..];
+    if img_data.is_empty() {
+        return None;
+    }
+
+    let picture = Picture::new(pic_type, mime, description, Cow::Borrowed(img_data));
+    Some(Picture {
+        span: Some(DataSpan::new(next_idx, img_data.len())),
+        ..picture
+    })
+}
+
+fn parse_id3v1(bytes: &[u8], meta: &mut Metadata) {
+    let start = bytes.len() - ID3V1_SIZE;
+    if &bytes[start..start + 3] != b"TAG" {
+        return;
+    }
+    meta.found_mpeg_data = true;
+    // ID3v1.1 : octet nul puis numéro de piste à la fin du commentaire
+    let version = if bytes[start + 125] == 0 && bytes[start + 126] != 0 {
+        "ID3v1.1"
+    } else {
+        "ID3v1"
+    };
+    meta.trace_open(|| InspectNode::new("id3v1", version, start, ID3V1_SIZE));
+
+    let title_raw = &bytes[start + 3..start + 33];
+    let artist_raw = &bytes[start + 33..start + 63];
+    let album_raw = &bytes[start + 63..start + 93];
+
+    let usage = if meta.title.is_empty() {
+        let t = meta.legacy_text.decode(trim_trailing_zeros(title_raw));
+        if !t.is_empty() {
+            meta.title = vec![t];
+        }
+        Usage::mapped_or(!meta.title.is_empty(), "title", EMPTY_VALUE)
+    } else {
+        check_id3v1_value("title", title_raw, start + 3, meta);
+        Usage::Skipped(ALREADY_SET)
+    };
+    meta.trace(|| InspectNode::new("id3v1-field", "title", start + 3, 30).usage(usage));
+    let usage = if meta.artist.is_empty() {
+        let a = meta.legacy_text.decode(trim_trailing_zeros(artist_raw));
+        if !a.is_empty() {
+            meta.artist = vec![a];
+        }
+        Usage::mapped_or(!meta.artist.is_empty(), "artist", EMPTY_VALUE)
+    } else {
+        check_id3v1_value("artist", artist_raw, start + 33, meta);
+        Usage::Skipped(ALREADY_SET)
+    };
+    meta.trace(|| InspectNode::new("id3v1-field", "artist", start + 33, 30).usage(usage));
+    let usage = if meta.album.is_empty() {
+        let al = meta.legacy_text.decode(trim_trailing_zeros(album_raw));
+        if !al.is_empty() {
+            meta.album = vec![al];
+        }
+        Usage::mapped_or(!meta.album.is_empty(), "album", EMPTY_VALUE)
+    } else {
+        check_id3v1_value("album", album_raw, start + 63, meta);
+        Usage::Skipped(ALREADY_SET)
+    };
+    meta.trace(|| InspectNode::new("id3v1-field", "album", start + 63, 30).usage(usage));
+    meta.trace_close();
+}
+
+/// Comparer un champ ID3v1 à la valeur déjà lue dans ID3v2. Le champ ID3v1 est limité
+/// à 30 octets : une valeur ID3v2 plus longue qui commence pareil n'est pas signalée.
+fn check_id3v1_value(field: &str, raw: &[u8], offset: usize, meta: &mut Metadata) {
+    let v1 = trim_string(meta.legacy_text.peek(trim_trailing_zeros(raw)));
+    let current = match field {
+        "title" => &meta.title,
+        "artist" => &meta.artist,
+        _ => &meta.album,
+    };
+    let v2 = current.join(MULTI_VALUE_SEPARATOR);
+    // Les artistes ID3v2.3 ont été séparés sur « / », comme ils le sont souvent dans ID3v1
+    if v1.is_empty() || v2.starts_with(&v1) || current.join("/").starts_with(&v1) {
+        return;
+    }
+    let message = format!("ID3v1 {field} \"{v1}\" differs from ID3v2 value \"{v2}\"");
+    meta.warn("id3v1-mismatch", offset, message);
+}
+
+// ---------------------------------------------------------------------------
+// FLAC
+// ---------------------------------------------------------------------------
+
+/// Analyser les blocs de métadonnées FLAC. `read` renvoie les octets disponibles
+/// au début d'une plage (moins que demandé si le reste n'a pas été lu) ; `size`
+/// est la taille des octets analysés quand celle du fichier n'est pas connue.
+fn parse_flac<'a>(
+    read: impl Fn(Range<usize>) -> &'a [u8],
+    size: usize,
+    options: &ParseOptions,
+    meta: &mut Metadata<'a>,
+) {
+    if read(0..4) != FLAC_SIGNATURE {
+        return;
+    }
+    let file_size = options.file_size.unwrap_or(size as u64);
+    let mut offset = 4;
+    let mut is_last = false;
+
+    while !is_last {
+        let header = read(offset..offset + 4);
+        if header.len() < 4 {
+            break;
+        }
+        is_last = header[0] & 0x80 != 0;
+        let block_type = header[0] & 0x7F;
+        let length =
+            ((header[1] as usize) << 16) | ((header[2] as usize) << 8) | (header[3] as usize);
+        let header_offset = offset;
+        let block_node = move || {
+            InspectNode::new(
+                "flac-block",
+                flac_block_name(block_type),
+                header_offset,
+                4 + length,
+            )
+            .detail("type", block_type)
+            .detail("last", is_last)
+        };
+        offset += 4;
+
+        // Le remplissage n'a pas besoin d'être lu : il peut dépasser l'extrait transmis
+        if block_type == 1 {
+            meta.audio_info.flac_blocks.padding_bytes += length as u64;
+            meta.trace(|| block_node().usage(Usage::Mapped("flac.paddingBytes")));
+            offset += length;
+            continue;
+        }
+        let block = read(offset..offset + length);
+        let past_end = (offset + length) as u64 > file_size;
+        // Une image dont seul l'en-tête a été lu est décrite sans ses octets
+        let partial_picture = (block.len() < length && block_type == 6 && !past_end)
+            .then(|| parse_flac_picture_prefix(block, length))
+            .flatten();
+        if block.len() < length && partial_picture.is_none() {
+            // Seul un bloc qui dépasse le fichier lui-même est réellement tronqué
+            if past_end {
+                let message = format!(
+                    "{} block declares {length} bytes past the end of the file",
+                    flac_block_name(block_type)
+                );
+                meta.warn("truncated-block", header_offset, message);
+            }
+            meta.trace(|| block_node().usage(Usage::Skipped(TRUNCATED_BLOCK)));
+            break;
+        }
+
+        meta.trace_open(block_node);
+
+        let usage = match block_type {
+            0 => {
+                parse_flac_streaminfo(block, &mut meta.audio_info);
+                Usage::Mapped("flac")
+            }
+            2 => match block.get(0..4) {
+                Some(id) => {
+                    meta.audio_info
+                        .flac_blocks
+                        .application_ids
+                        .push(application_id(id));
+                    Usage::Mapped("flac.applicationIds")
+                }
+                None => Usage::Skipped(TRUNCATED_BLOCK),
+            },
+            3 => {
+                parse_flac_seektable(block, &mut meta.audio_info);
+                Usage::Mapped("flac.seekTable")
+            }
+            4 => {
+                // Les clés sont repérées par leur position dans le bloc
+                let previous_base = meta.set_offset_base(Some(offset));
+                parse_vorbis_comment(block, options, meta);
+                meta.set_offset_base(previous_base);
+                Usage::Mapped("tags")
+            }
+            5 => {
+                // Le bloc binaire prime sur un éventuel commentaire CUESHEET
+                let sample_rate = meta.audio_info.sample_rate.unwrap_or(CD_SAMPLE_RATE);
+                match parse_flac_cuesheet(block, sample_rate) {
+                    Some(sheet) => {
+                        meta.cue_sheet = Some(sheet);
+                        Usage::Mapped("cueSheet")
+                    }
+                    None => Usage::Skipped(INVALID_CUE_SHEET),
+                }
+            }
+            6 => match partial_picture.or_else(|| parse_flac_picture(block)) {
+                Some(picture) => {
+                    meta.add_picture(picture.at(offset), header_offset, options.max_cover_bytes)
+                }
+                None => Usage::Skipped(INVALID_PICTURE),
+            },
+            _ => Usage::Skipped(UNSUPPORTED),
+        };
+        meta.trace_usage(usage);
+        meta.trace_close();
+
+        offset += length;
+    }
+
+    // Débit moyen : les trames audio occupent tout le fichier après le dernier bloc de métadonnées
+    if is_last {
+        let audio_info = &mut meta.audio_info;
+        audio_info.flac_blocks.audio_offset = Some(offset as u64);
+        if let Some(sample_rate) = audio_info.sample_rate {
+            audio_info.seek_points = audio_info
+                .flac_blocks
+                .seek_table
+                .iter()
+                .map(|point| SeekPoint {
+                    time_ms: point.sample.saturating_mul(1000) / sample_rate as u64,
+                    offset: (offset as u64).saturating_add(point.offset),
+                })
+                .collect();
+        }
+        // Débit moyen seulement si la taille du fichier est connue : un tampon
+        // tronqué au début du flux donnerait un débit sans rapport
+        if let Some(file_size) = options.file_size
+            && let Some(duration_ms) = meta.audio_info.duration_ms
+            && let Some(bitrate) =
+                (file_size.saturating_sub(offset as u64) * 8).checked_div(duration_ms)
+        {
+            meta.audio_info.bitrate = Some(bitrate as u32);
+        }
+    }
+}
+
+/// Points de la table SEEKTABLE (18 octets chacun) ; les points réservés sont ignorés.
+fn parse_flac_seektable(block: &[u8], audio_info: &mut AudioInfo) {
+    for point in block.chunks_exact(18) {
+        let sample = ((be_u32(&point[0..4]) as u64) << 32) | be_u32(&point[4..8]) as u64;
+        if sample == u64::MAX {
+            continue;
+        }
+        audio_info.flac_blocks.seek_table.push(FlacSeekPoint {
+            sample,
+            offset: ((be_u32(&point[8..12]) as u64) << 32) | be_u32(&point[12..16]) as u64,
+            frame_samples: be_u16(&point[16..18]),
+        });
+    }
+}
+
+/// Identifiant APPLICATION : texte ASCII s'il est imprimable, sinon hexadécimal
+fn application_id(id: &[u8]) -> String {
+    if id.iter().all(|b| b.is_ascii_graphic() || *b == b' ') {
+        id.iter().map(|&b| b as char).collect()
+    } else {
+        format!("0x{:08x}", be_u32(id))
+    }
+}
+
+/// Analyser le bloc STREAMINFO FLAC (34 octets).
+///
+/// Structure (offsets en bits):
+///   0..15   : taille de bloc min
+///   16..31  : taille de bloc max
+///   32..55  : taille de trame min  (24 bits)
+///   56..79  : taille de trame max  (24 bits)
+///   80..99  : taux d'échantillonnage (20 bits)
+///   100..102: canaux - 1           (3 bits)
+///   103..107: bits/échantillon - 1 (5 bits)
+///   108..143: échantillons totaux  (36 bits)
+///   144..271: signature MD5
+fn parse_flac_streaminfo(block: &[u8], audio_info: &mut AudioInfo) {
+    if block.len() < 18 {
+        return;
+    }
+
+    // Taux d'échantillonnage: 20 bits commençant à l'octet 10
+    let sample_rate =
+        ((block[10] as u32) << 12) | ((block[11] as u32) << 4) | ((block[12] as u32) >> 4);
+
+    // Canaux: 3 bits aux bits 100..102 → octet 12 bits [3..1]
+    let channels = ((block[12] >> 1) & 0x07) + 1;
+
+    // Bits par échantillon: 5 bits aux bits 103..107
+    // le bit 103 est le LSB de l'octet 12, les bits 104..107 sont les 4 bits supérieurs de l'octet 13
+    let bps = (((block[12] & 0x01) as u16) << 4) | ((block[13] >> 4) as u16);
+    let bit_depth = bps + 1;
+
+    if sample_rate > 0 {
+        audio_info.sample_rate = Some(sample_rate);
+    }
+    if bit_depth > 0 && bit_depth <= 32 {
+        audio_info.bit_depth = Some(bit_depth);
+    }
+    if channels > 0 && channels <= 8 {
+        audio_info.channels = Some(channels);
+    }
+
+    if block.len() < 34 {
+        return;
+    }
+    // Échantillons totaux : 36 bits, les 4 bits de poids faible de l'octet 13 puis les octets 14..17
+    let total_samples = ((block[13] as u64 & 0x0F) << 32) | be_u32(&block[14..18]) as u64;
+    if total_samples > 0 && sample_rate > 0 {
+        audio_info.duration_ms = Some(total_samples * 1000 / sample_rate as u64);
+    }
+    audio_info.flac = Some(FlacStreamInfo {
+        min_block_size: be_u16(&block[0..2]),
+        max_block_size: be_u16(&block[2..4]),
+        min_frame_size: be_u24(&block[4..7]),
+        max_frame_size: be_u24(&block[7..10]),
+        total_samples,
+        md5: block[18..34].try_into().unwrap_or([0; 16]),
+    });
+}
+
+/// Analyser un bloc Vorbis comment (FLAC, Ogg Vorbis, Opus).
+/// Les images base64 (`METADATA_BLOCK_PICTURE`, `COVERART`) sont repérées avant la
+/// troncature à `max_text_bytes`, puis décodées si elles respectent `max_cover_bytes`.
+fn parse_vorbis_comment(data: &[u8], options: &ParseOptions, meta: &mut Metadata<'_>) {
+    if data.len() < 8 {
+        return;
+    }
+    let mut offset = 0;
+    let vendor_len = le_u32(&data[offset..offset + 4]) as usize;
+    offset += 4;
+    // Chaîne vendeur : bibliothèque ayant écrit le flux (« reference libFLAC 1.4.3 », « libopus 1.4 »)
+    let usage = if meta.encoder.name.is_some() {
+        Usage::Skipped(ALREADY_SET)
+    } else if let Some(vendor) = data.get(offset..offset + vendor_len)
+        && let Ok(vendor) = std::str::from_utf8(vendor)
+        && !vendor.trim().is_empty()
+    {
+        meta.encoder.name = Some(vendor.trim().to_string());
+        Usage::Mapped("encoder.name")
+    } else if data
+        .get(offset..offset + vendor_len)
+        .is_some_and(|vendor| std::str::from_utf8(vendor).is_err())
+    {
+        meta.warn(
+            "invalid-encoding",
+            0,
+            "Vorbis vendor string is not valid UTF-8",
+        );
+        Usage::Skipped(INVALID_UTF8)
+    } else {
+        Usage::Skipped(EMPTY_VALUE)
+    };
+    meta.trace(|| InspectNode::new("vorbis-vendor", "vendor", 0, 4 + vendor_len).usage(usage));
+    offset += vendor_len;
+    if offset + 4 > data.len() {
+        return;
+    }
+    let count = le_u32(&data[offset..offset + 4]) as usize;
+    offset += 4;
+
+    // Les clés TITLE / ARTIST / ALBUM peuvent être répétées : on cumule les valeurs
+    // du bloc, sans mélanger avec celles déjà trouvées ailleurs.
+    let mut title = Vec::new();
+    let mut artist = Vec::new();
+    let mut album = Vec::new();
+    // Ancienne convention : image base64 brute dans COVERART, type MIME dans COVERARTMIME
+    let mut legacy_cover: Option<(usize, Vec<u8>)> = None;
+    let mut legacy_cover_mime = String::new();
+
+    for index in 0..count {
+        let entry_offset = offset;
+        let Some(len) = data
+            .get(offset..offset + 4)
+            .map(|len| le_u32(len) as usize)
+            .filter(|&len| offset + 4 + len <= data.len())
+        else {
+            let message = format!("Vorbis comment declares {count} entries but only {index} fit");
+            meta.warn("truncated-tag", entry_offset, message);
+            break;
+        };
+        offset += 4;
+        let full_entry = &data[offset..offset + len];
+        offset += len;
+        let entry_node = |key: &str| InspectNode::new("vorbis-comment", key, entry_offset, 4 + len);
+
+        let Some(eq) = full_entry.iter().position(|&b| b == b'=') else {
+            meta.trace(|| entry_node("").usage(Usage::Skipped(MISSING_SEPARATOR)));
+            continue;
+        };
+        let key = &full_entry[..eq];
+        let Ok(key_str) = std::str::from_utf8(key) else {
+            meta.warn(
+                "invalid-encoding",
+                entry_offset,
+                "Vorbis comment key is not valid UTF-8",
+            );
+            meta.trace(|| {
+                entry_node(&String::from_utf8_lossy(key)).usage(Usage::Skipped(INVALID_UTF8))
+            });
+            continue;
+        };
+
+        if key_str.eq_ignore_ascii_case("METADATA_BLOCK_PICTURE") {
+            let encoded = &full_entry[eq + 1..];
+            let size = base64_decoded_len(encoded);
+            let usage = if size > options.max_cover_bytes {
+                let message = cover_too_large_message(size, options.max_cover_bytes);
+                meta.warn("cover-too-large", entry_offset, message);
+                Usage::Skipped(COVER_TOO_LARGE)
+            } else if let Some(decoded) = decode_base64(encoded)
+                && let Some(picture) = parse_flac_picture(&decoded)
+            {
+                meta.add_picture(picture.into_owned(), entry_offset, options.max_cover_bytes)
+            } else {
+                Usage::Skipped(INVALID_PICTURE)
+            };
+            meta.trace(|| entry_node(key_str).usage(usage));
+            continue;
+        }
+        if key_str.eq_ignore_ascii_case("CUESHEET") {
+            // Feuille complète, sans troncature à `max_text_bytes`
+            let usage = if meta.cue_sheet.is_some() {
+                Usage::Skipped(ALREADY_SET)
+            } else if let Ok(text) = std::str::from_utf8(&full_entry[eq + 1..]) {
+                let sample_rate = meta.audio_info.sample_rate.unwrap_or(CD_SAMPLE_RATE);
+                meta.cue_sheet = parse_cue_text(text, sample_rate);
+                Usage::mapped_or(meta.cue_sheet.is_some(), "cueSheet", INVALID_CUE_SHEET)
+            } else {
+                Usage::Skipped(INVALID_UTF8)
+            };
+            meta.trace(|| entry_node(key_str).usage(usage));
+            continue;
+        }
+        if key_str.eq_ignore_ascii_case("COVERART") {
+            let encoded = &full_entry[eq + 1..];
+            let size = base64_decoded_len(encoded);
+            // Décodée tout de suite, mais rangée après la boucle : COVERARTMIME peut suivre
+            let usage = if size > options.max_cover_bytes {
+                let message = cover_too_large_message(size, options.max_cover_bytes);
+                meta.warn("cover-too-large", entry_offset, message);
+                Usage::Skipped(COVER_TOO_LARGE)
+            } else if let Some(decoded) = decode_base64(encoded)
+                && !decoded.is_empty()
+            {
+                legacy_cover = Some((entry_offset, decoded));
+                Usage::Mapped("pictures")
+            } else {
+                Usage::Skipped(INVALID_PICTURE)
+            };
+            meta.trace(|| entry_node(key_str).usage(usage));
+            continue;
+        }
+
+        let entry = &full_entry[..len.min(options.max_text_bytes)];
+        let value = entry.get(eq + 1..).unwrap_or_default();
+        let Some(value_str) = std::str::from_utf8(value)
+            .ok()
+            .map(|s| trim_string(s.to_string()))
+        else {
+            // Une valeur coupée par `max_text_bytes` au milieu d'un caractère n'est pas signalée
+            if std::str::from_utf8(&full_entry[eq + 1..]).is_err() {
+                let message = format!("Vorbis comment {key_str} is not valid UTF-8");
+                meta.warn("invalid-encoding", entry_offset, message);
+            }
+            meta.trace(|| entry_node(key_str).usage(Usage::Skipped(INVALID_UTF8)));
+            continue;
+        };
+        let has_value = !value_str.is_empty();
+
+        // Les valeurs TITLE / ARTIST / ALBUM ne sont retenues que si le champ est encore vide
+        let usage = match key_str.to_ascii_uppercase().as_str() {
+            "TITLE" if meta.title.is_empty() => {
+                push_value(&mut title, value_str);
+                Usage::mapped_or(has_value, "title", EMPTY_VALUE)
+            }
+            "ARTIST" if meta.artist.is_empty() => {
+                push_value(&mut artist, value_str);
+                Usage::mapped_or(has_value, "artist", EMPTY_VALUE)
+            }
+            "ALBUM" if meta.album.is_empty() => {
+                push_value(&mut album, value_str);
+                Usage::mapped_or(has_value, "album", EMPTY_VALUE)
+            }
+            "LYRICS" if meta.synced_lyrics.is_none() => {
+                meta.synced_lyrics = Some(value_str);
+                Usage::Mapped("syncedLyrics")
+            }
+            "UNSYNCEDLYRICS" if meta.lyrics.is_none() => {
+                meta.lyrics = Some(value_str);
+                Usage::Mapped("lyrics")
+            }
+            "COVERARTMIME" => {
+                legacy_cover_mime = value_str;
+                Usage::Mapped("pictures")
+            }
+            "ITUNSMPB" if meta.gapless.is_none() => {
+                meta.gapless = parse_itunsmpb(&value_str);
+                Usage::mapped_or(meta.gapless.is_some(), "gapless", EMPTY_VALUE)
+            }
+            "ENCODER" if meta.encoder.settings.is_none() => {
+                meta.encoder.settings = Some(value_str);
+                Usage::Mapped("encoder.settings")
+            }
+            "ENCODED-BY" | "ENCODEDBY" | "ENCODED_BY" if meta.encoder.encoded_by.is_none() => {
+                meta.encoder.encoded_by = Some(value_str);
+                Usage::Mapped("encoder.encodedBy")
+            }
+            "TITLE" | "ARTIST" | "ALBUM" | "LYRICS" | "UNSYNCEDLYRICS" | "ITUNSMPB" | "ENCODER"
+            | "ENCODED-BY" | "ENCODEDBY" | "ENCODED_BY" => Usage::Skipped(ALREADY_SET),
+            _ => Usage::Skipped(UNSUPPORTED),
+        };
+        meta.trace(|| entry_node(key_str).usage(usage));
+    }
+
+    if let Some((cover_offset, decoded)) = legacy_cover {
+        // Sans type explicite, l'image est considérée comme la couverture avant
+        meta.add_picture(
+            Picture::new(
+                FRONT_COVER_TYPE,
+                legacy_cover_mime,
+                String::new(),
+                Cow::Owned(decoded),
+            ),
+            cover_offset,
+            options.max_cover_bytes,
+        );
+    }
+
+    if meta.title.is_empty() {
+        meta.title = title;
+    }
+    if meta.artist.is_empty() {
+        meta.artist = artist;
+    }
+    if meta.album.is_empty() {
+        meta.album = album;
+    }
+}
+
+fn cover_too_large_message(size: usize, max_cover_bytes: usize) -> String {
+    format!("picture of {size} bytes exceeds maxCoverBytes ({max_cover_bytes})")
+}
+
+fn push_value(values: &mut Vec<String>, value: String) {
+    if !value.is_empty() && !values.contains(&value) {
+        values.push(value);
+    }
+}
+
+fn parse_flac_picture(data: &[u8]) -> Option<Picture<'_>> {
+    parse_flac_picture_prefix(data, data.len())
+}
+
+/// Bloc PICTURE de `block_len` octets dont seul le début `data` a été lu : l'en-tête
+/// doit y figurer en entier, l'image peut être incomplète.
+fn parse_flac_picture_prefix(data: &[u8], block_len: usize) -> Option<Picture<'_>> {
+    let mut offset = 0;
+    let pic_type_raw = be_u32(data.get(offset..offset + 4)?);
+    let pic_type = if pic_type_raw > 255 {
+        0
+    } else {
+        pic_type_raw as u8
+    };
+    offset += 4;
+
+    let mime_len = be_u32(data.get(offset..offset + 4)?) as usize;
+    offset += 4;
+    let mime_bytes = data.get(offset..offset.checked_add(mime_len)?)?;
+    let mime = String::from_utf8_lossy(mime_bytes).to_string();
+    offset += mime_len;
+
+    let desc_len = be_u32(data.get(offset..offset + 4)?) as usize;
+    offset += 4;
+    let desc_bytes = data.get(offset..offset.checked_add(desc_len)?)?;
+    let description = trim_string(String::from_utf8_lossy(desc_bytes).to_string());
+    offset += desc_len;
+
+    // Largeur, hauteur, profondeur de couleur, couleurs utilisées (4 × 4 octets)
+    let dimensions = data.get(offset..offset + 4 * 4)?;
+    let width = be_u32(&dimensions[0..4]);
+    let height = be_u32(&dimensions[4..8]);
+    let color_depth = be_u32(&dimensions[8..12]);
+    offset += 4 * 4;
+    let pic_len = be_u32(data.get(offset..offset + 4)?) as usize;
+    offset += 4;
+    if pic_len == 0 || offset.checked_add(pic_len)? > block_len {
+        return None;
+    }
+    let img = &data[offset..(offset + pic_len).min(data.len())];
+    let mut picture = Picture::new(pic_type, mime, description, Cow::Borrowed(img));
+    picture.length = pic_len;
+    picture.span = Some(DataSpan::new(offset, pic_len));
+    // Valeurs déclarées par le bloc, utilisées si l'en-tête n'a rien donné (0 = non renseigné)
+    if picture.width.is_none() && width > 0 {
+        picture.width = Some(width);
+    }
+    if picture.height.is_none() && height > 0 {
+        picture.height = Some(height);
+    }
+    if picture.color_depth.is_none() && color_depth > 0 {
+        picture.color_depth = Some(color_depth);
+    }
+    Some(picture)
+}
+
+// ---------------------------------------------------------------------------
+// OGG (Vorbis, Opus, FLAC)
+// ---------------------------------------------------------------------------
+
+/// Analyser les paquets d'en-tête du premier flux logique d'un fichier Ogg :
+/// identification puis commentaires (Vorbis, Opus) ou blocs de métadonnées (FLAC).
+/// `tail` : fin du fichier, où chercher la dernière page pour la durée
+/// (le tampon entier, sauf en lecture incrémentale).
+fn parse_ogg(bytes: &[u8], tail: &[u8], options: &ParseOptions, meta: &mut Metadata<'_>) {
+    let mut reader = OggPacketReader::new(bytes);
+
+    let Some(first) = reader.next_packet() else {
+        return;
+    };
+    // Les paquets sont réassemblés à partir des pages : ils n'ont pas de position propre
+    meta.trace_open(|| InspectNode::new("ogg-stream", ogg_codec_name(&first), 0, bytes.len()));
+    let previous_base = meta.set_offset_base(None);
+
+    if first.len() >= 30 && first.starts_with(b"\x01vorbis") {
+        // Identification Vorbis : version (4), canaux (1), taux (4), débits max / nominal / min (3 × 4)
+        let channels = first[11];
+        let sample_rate = le_u32(&first[12..16]);
+        let nominal_bitrate = le_u32(&first[20..24]) as i32;
+        set_ogg_audio_info(meta, sample_rate, channels);
+        meta.trace(|| {
+            InspectNode::new("ogg-packet", "identification", 0, first.len())
+                .usage(Usage::Mapped("sampleRate"))
+        });
+        if nominal_bitrate > 0 {
+            meta.audio_info.bitrate = Some(nominal_bitrate as u32 / 1000);
+        }
+        if sample_rate > 0
+            && let Some(granule) = reader
+                .serial
+                .and_then(|serial| last_granule_position(tail, serial))
+        {
+            meta.audio_info.duration_ms = Some(granule.saturating_mul(1000) / sample_rate as u64);
+        }
+        if let Some(comment) = reader.next_packet()
+            && comment.starts_with(b"\x03vorbis")
+        {
+            meta.trace_open(|| InspectNode::new("ogg-packet", "comment", 0, comment.len()));
+            parse_vorbis_comment(&comment[7..], options, meta);
+            meta.trace_close();
+        }
+    } else if first.len() >= 19 && first.starts_with(b"OpusHead") {
+        // Opus décode toujours à 48 kHz, quel que soit le taux d'entrée déclaré
+        set_ogg_audio_info(meta, 48000, first[9]);
+        meta.trace(|| {
+            InspectNode::new("ogg-packet", "OpusHead", 0, first.len())
+                .usage(Usage::Mapped("sampleRate"))
+        });
+        // Pre-skip : échantillons à 48 kHz à ignorer au début du décodage. La position
+        // granulaire de la dernière page les inclut, la durée exacte en découle.
+        let pre_skip = le_u16(&first[10..12]) as u32;
+        let valid_samples = reader
+            .serial
+            .and_then(|serial| last_granule_position(tail, serial))
+            .map(|granule| granule.saturating_sub(pre_skip as u64));
+        if let Some(samples) = valid_samples {
+            meta.audio_info.duration_ms = Some(samples.saturating_mul(1000) / 48000);
+        }
+        meta.gapless = Some(GaplessInfo {
+            delay: pre_skip,
+            padding: None,
+            valid_samples,
+            source: "opus",
+        });
+        if let Some(comment) = reader.next_packet()
+            && comment.starts_with(b"OpusTags")
+        {
+            meta.trace_open(|| InspectNode::new("ogg-packet", "OpusTags", 0, comment.len()));
+            parse_vorbis_comment(&comment[8..], options, meta);
+            meta.trace_close();
+        }
+    } else if first.len() >= 13 + 38
+        && first.starts_with(b"\x7fFLAC")
+        && &first[9..13] == FLAC_SIGNATURE
+    {
+        // Mappage Ogg FLAC : en-tête (9 octets), « fLaC », puis le bloc STREAMINFO complet ;
+        // chaque paquet suivant contient un bloc de métadonnées
+        parse_flac_streaminfo(&first[17..], &mut meta.audio_info);
+        meta.trace(|| {
+            InspectNode::new("flac-block", flac_block_name(0), 0, first.len() - 13)
+                .usage(Usage::Mapped("flac"))
+        });
+        while let Some(packet) = reader.next_packet() {
+            if packet.len() < 4 {
+                break;
+            }
+            let is_last = packet[0] & 0x80 != 0;
+            let block_type = packet[0] & 0x7F;
+            let block = &packet[4..];
+            meta.trace_open(|| {
+                InspectNode::new("flac-block", flac_block_name(block_type), 0, packet.len())
+                    .detail("type", block_type)
+                    .detail("last", is_last)
+            });
+            let usage = match block_type {
+                4 => {
+                    parse_vorbis_comment(block, options, meta);
+                    Usage::Mapped("tags")
+                }
+                6 => match parse_flac_picture(block) {
+                    Some(picture) => {
+                        meta.add_picture(picture.into_owned(), 0, options.max_cover_bytes)
+                    }
+                    None => Usage::Skipped(INVALID_PICTURE),
+                },
+                _ => Usage::Skipped(UNSUPPORTED),
+            };
+            meta.trace_usage(usage);
+            meta.trace_close();
+            if is_last {
+                break;
+            }
+        }
+    }
+    meta.set_offset_base(previous_base);
+    meta.trace_close();
+    for page in reader.corrupt_pages {
+        meta.warn(
+            "ogg-crc-mismatch",
+            page,
+            "Ogg page checksum does not match its content",
+        );
+    }
+}
+
+/// Somme de contrôle d'une page Ogg : CRC-32 (polynôme 0x04C11DB7, sans réflexion)
+/// calculé avec le champ de la somme elle-même mis à zéro
+fn ogg_crc(page: &[u8]) -> u32 {
+    let mut crc = 0u32;
+    for (index, &byte) in page.iter().enumerate() {
+        let byte = if (22..26).contains(&index) { 0 } else { byte };
+        crc ^= (byte as u32) << 24;
+        for _ in 0..8 {
+            crc = if crc & 0x8000_0000 != 0 {
+                (crc << 1) ^ 0x04C1_1DB7
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+/// Codec du flux Ogg, d'après la signature du premier paquet
+fn ogg_codec_name(first: &[u8]) -> &'static str {
+    if first.starts_with(b"\x01vorbis") {
+        "Vorbis"
+    } else if first.starts_with(b"OpusHead") {
+        "Opus"
+    } else if first.starts_with(b"\x7fFLAC") {
+        "FLAC"
+    } else {
+        "unknown"
+    }
+}
+
+/// Position granulaire de la dernière page du flux `serial`. `None` si cette page ne porte pas
+/// le drapeau de fin de flux, c'est-à-dire si `bytes` n'est qu'un extrait du fichier.
+fn last_granule_position(bytes: &[u8], serial: u32) -> Option<u64> {
+    let mut end = bytes.len();
+    while end >= OGG_PAGE_HEADER_SIZE {
+        let start = bytes[..end - OGG_PAGE_HEADER_SIZE + 4]
+            .windows(4)
+            .rposition(|window| window == OGG_SIGNATURE)?;
+        let page = &bytes[start..];
+        let granule = u64::from_le_bytes(page[6..14].try_into().ok()?);
+        if le_u32(&page[14..18]) == serial {
+            let end_of_stream = page[5] & 0x04 != 0;
+            // Une position de -1 signale une page sans paquet terminé
+            return (end_of_stream && granule != u64::MAX).then_some(granule);
+        }
+        end = start + OGG_PAGE_HEADER_SIZE - 1;
+    }
+    None
+}
+
+fn set_ogg_audio_info(meta: &mut Metadata<'_>, sample_rate: u32, channels: u8) {
+    if sample_rate > 0 {
+        meta.audio_info.sample_rate = Some(sample_rate);
+    }
+    if channels > 0 {
+        meta.audio_info.channels = Some(channels);
+    }
+}
+
+/// Réassemblage des paquets Ogg d'un flux logique (le premier rencontré).
+/// Un paquet peut s'étendre sur plusieurs pages : les segments de 255 octets le prolongent.
+struct OggPacketReader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+    serial: Option<u32>,
+    /// Segments de la page courante et position du prochain segment à lire
+    segments: Vec<usize>,
+    segment_index: usize,
+    data_offset: usize,
+    /// Position des pages entièrement lues dont la somme de contrôle ne correspond pas
+    corrupt_pages: Vec<usize>,
+}
+
+impl<'a> OggPacketReader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self {
+            bytes,
+            offset: 0,
+            serial: None,
+            segments: Vec::new(),
+            segment_index: 0,
+            data_offset: 0,
+            corrupt_pages: Vec::new(),
+        }
+    }
+
+    /// Charger la page suivante du flux suivi. Renvoie `false` en fin de tampon.
+    fn next_page(&mut self) -> bool {
+        loop {
+            let start = self.offset;
+            if start + OGG_PAGE_HEADER_SIZE > self.bytes.len()
+                || &self.bytes[start..start + 4] != OGG_SIGNATURE
+            {
+                return false;
+            }
+            let serial = le_u32(&self.bytes[start + 14..start + 18]);
+            let segment_count = self.bytes[start + 26] as usize;
+            let table_end = start + OGG_PAGE_HEADER_SIZE + segment_count;
+            if table_end > self.bytes.len() {
+                return false;
+            }
+            let segments: Vec<usize> = self.bytes[start + OGG_PAGE_HEADER_SIZE..table_end]
+                .iter()
+                .map(|&b| b as usize)
+                .collect();
+            let body_len: usize = segments.iter().sum();
+            self.offset = table_end + body_len;
+
+            // Ignorer les pages des autres flux multiplexés
+            if *self.serial.get_or_insert(serial) != serial {
+                continue;
+            }
+            if let Some(page) = self.bytes.get(start..self.offset)
+                && ogg_crc(page) != le_u32(&page[22..26])
+            {
+                self.corrupt_pages.push(start);
+            }
+            self.segments = segments;
+            self.segment_index = 0;
+            self.data_offset = table_end;
+            return true;
+        }
+    }
+
+    fn next_packet(&mut self) -> Option<Vec<u8>> {
+        let mut packet = Vec::new();
+        loop {
+            if self.segment_index >= self.segments.len() && !self.next_page() {
+                return None;
+            }
+            while self.segment_index < self.segments.len() {
+                let len = self.segments[self.segment_index];
+                self.segment_index += 1;
+                let end = self.data_offset + len;
+                if end > self.bytes.len() {
+                    return None;
+                }
+                packet.extend_from_slice(&self.bytes[self.data_offset..end]);
+                self.data_offset = end;
+                // Un segment de moins de 255 octets termine le paquet
+                if len < 255 {
+                    return Some(packet);
+                }
+            }
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Trame PIC ID3v2.2 (code de format à 3 caractères au lieu d'une chaîne MIME)
+// ---------------------------------------------------------------------------
+
+fn parse_pic_frame<'a>(
+    frame_data: &'a [u8],
+    legacy_text: &mut LegacyTextDecoder,
+) -> Option<Picture<'a>> {
+    if frame_data.len() < 5 {
+        return None;
+    }
+    let encoding = frame_data[0];
+    let format = &frame_data[1..4];
+    let mime = match format {
+        b"PNG" => "image/png",
+        b"JPG" => "image/jpeg",
+        b"GIF" => "image/gif",
+        b"-->" => PICTURE_LINK_MIME,
+        _ => "image/unknown",
+    }
+    .to_string();
+
+    let pic_type = frame_data[4];
+    let idx = 5;
+
+    let (description, next_idx) = read_picture_description(frame_data, idx, encoding, legacy_text)?;
+
+    let img_data = &frame_data[next_idx..];
+    if img_data.is_empty() {
+        return None;
+    }
+
+    let picture = Picture::new(pic_type, mime, description, Cow::Borrowed(img_data));
+    Some(Picture {
+        span: Some(DataSpan::new(next_idx, img_data.len())),
+        ..picture
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Fonctions utilitaires
+// ---------------------------------------------------------------------------
+
+/// Normaliser un type MIME d'image déclaré (casse, alias « image/jpg », abréviations).
+fn normalize_image_mime(declared: &str) -> String {
+    let mime = declared.trim().to_ascii_lowercase();
+    match mime.as_str() {
+        "" => "image/unknown".to_string(),
+        "jpg" | "jpeg" | "image/jpg" | "image/pjpeg" => "image/jpeg".to_string(),
+        "png" | "image/x-png" => "image/png".to_string(),
+        "gif" => "image/gif".to_string(),
+        "webp" => "image/webp".to_string(),
+        "bmp" | "image/x-ms-bmp" | "image/x-bmp" => "image/bmp".to_string(),
+        _ if !mime.contains('/') => format!("image/{mime}"),
+        _ => mime,
+    }
+}
+
+fn synchsafe_to_u32(bytes: &[u8]) -> u32 {
+    if bytes.len() < 4 {
+        return 0;
+    }
+    ((bytes[0] as u32) << 21)
+        | ((bytes[1] as u32) << 14)
+        | ((bytes[2] as u32) << 7)
+        | (bytes[3] as u32)
+}
+
+fn be_u16(bytes: &[u8]) -> u16 {
+    if bytes.len() < 2 {
+        return 0;
+    }
+    ((bytes[0] as u16) << 8) | (bytes[1] as u16)
+}
+
+fn be_u24(bytes: &[u8]) -> u32 {
+    if bytes.len() < 3 {
+        return 0;
+    }
+    ((bytes[0] as u32) << 16) | ((bytes[1] as u32) << 8) | (bytes[2] as u32)
+}
+
+fn le_u16(bytes: &[u8]) -> u16 {
+    if bytes.len() < 2 {
+        return 0;
+    }
+    (bytes[0] as u16) | ((bytes[1] as u16) << 8)
+}
+
+fn be_u32(bytes: &[u8]) -> u32 {
+    if bytes.len() < 4 {
+        return 0;
+    }
+    ((bytes[0] as u32) << 24)
+        | ((bytes[1] as u32) << 16)
+        | ((bytes[2] as u32) << 8)
+        | (bytes[3] as u32)
+}
+
+fn le_u32(bytes: &[u8]) -> u32 {
+    if bytes.len() < 4 {
+        return 0;
+    }
+    (bytes[0] as u32)
+        | ((bytes[1] as u32) << 8)
+        | ((bytes[2] as u32) << 16)
+        | ((bytes[3] as u32) << 24)
+}
+
+fn find_zero(bytes: &[u8], start: usize) -> Option<usize> {
+    bytes
+        .get(start..)?
+        .iter()
+        .position(|&b| b == 0)
+        .map(|p| start + p)
+}
+
+fn find_zero_utf16(bytes: &[u8], start: usize) -> Option<usize> {
+    let mut i = start;
+    while i + 1 < bytes.len() {
+        if bytes[i] == 0 && bytes[i + 1] == 0 {
+            return Some(i);
+        }
+        i += 2;
+    }
+    None
+}
+
+/// Découper un texte UTF-16 sur les caractères nuls alignés (deux octets à 0).
+fn split_utf16_values(bytes: &[u8]) -> Vec<&[u8]> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut i = 0;
+    while i + 1 < bytes.len() {
+        if bytes[i] == 0 && bytes[i + 1] == 0 {
+            parts.push(&bytes[start..i]);
+            start = i + 2;
+        }
+        i += 2;
+    }
+    if start < bytes.len() {
+        parts.push(&bytes[start..]);
+    }
+    parts
+}
+
+/// Taille maximale des données décodées d'une chaîne base64 (avant décodage effectif)
+fn base64_decoded_len(encoded: &[u8]) -> usize {
+    encoded.len() / 4 * 3 + 3
+}
+
+/// Décoder du base64 standard (RFC 4648). Les blancs sont ignorés, le remplissage est facultatif.
+fn decode_base64(encoded: &[u8]) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(base64_decoded_len(encoded));
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    for &c in encoded {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            b'=' => break,
+            b' ' | b'\t' | b'\r' | b'\n' => continue,
+            _ => return None,
+        };
+        buffer = (buffer << 6) | value as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+        }
+    }
+    Some(out)
+}
+
+fn trim_trailing_zeros(bytes: &[u8]) -> &[u8] {
+    let mut end = bytes.len();
+    while end > 0 && (bytes[end - 1] == 0 || bytes[end - 1] == b' ') {
+        end -= 1;
+    }
+    &bytes[..end]
+}
+
+fn trim_string(s: String) -> String {
+    s.trim_matches(|c: char| c == '\0' || c.is_whitespace())
+        .to_string()
+}
+
+fn decode_utf16_with_bom(bytes: &[u8]) -> Option<String> {
+    if bytes.len() < 2 {
+        return None;
+    }
+    if bytes[0] == 0xFF && bytes[1] == 0xFE {
+        decode_utf16_le(&bytes[2..])
+    } else if bytes[0] == 0xFE && bytes[1] == 0xFF {
+        decode_utf16_be(&bytes[2..])
+    } else {
+        decode_utf16_le(bytes)
+    }
+}
+
+fn decode_utf16_le(bytes: &[u8]) -> Option<String> {
+    let mut u16s = Vec::with_capacity(bytes.len() / 2);
+    let mut i = 0;
+    while i + 1 < bytes.len() {
+        let val = (bytes[i] as u16) | ((bytes[i + 1] as u16) << 8);
+        if val == 0 {
+            break;
+        }
+        u16s.push(val);
+        i += 2;
+    }
+    String::from_utf16(&u16s).ok().map(trim_string)
+}
+
+fn decode_utf16_be(bytes: &[u8]) -> Option<String> {
+    let mut u16s = Vec::with_capacity(bytes.len() / 2);
+    let mut i = 0;
+    while i + 1 < bytes.len() {
+        let val = ((bytes[i] as u16) << 8) | (bytes[i + 1] as u16);
+        if val == 0 {
+            break;
+        }
+        u16s.push(val);
+        i += 2;
+    }
+    String::from_utf16(&u16s).ok().map(trim_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn slash_inside_a_name_is_kept() {
+        assert_eq!(split_slash_values(strings(&["AC/DC"])), strings(&["AC/DC"]));
+        assert_eq!(
+            split_slash_values(strings(&["Earth, Wind & Fire/The Emotions"])),
+            strings(&["Earth, Wind & Fire/The Emotions"])
+        );
+    }
+
+    #[test]
+    fn spaced_slash_separates_values() {
+        assert_eq!(
+            split_slash_values(strings(&["Queen / David Bowie", " / "])),
+            strings(&["Queen", "David Bowie"])
+        );
+    }
+
+    /// Tag ID3v2 de `body` (frames, en-tête étendu) ; la taille est synchsafe
+    fn id3v2_tag(version: u8, flags: u8, body: &[u8]) -> Vec<u8> {
+        let size = body.len() as u32;
+        let mut tag = vec![b'I', b'D', b'3', version, 0, flags];
+        tag.extend((0..4).rev().map(|shift| (size >> (7 * shift)) as u8 & 0x7F));
+        tag.extend_from_slice(body);
+        tag
+    }
+
+    /// Frame texte ID3v2.3 ou 2.4 en UTF-8 / ISO-8859-1 (encodage 0 ou 3)
+    fn text_frame(version: u8, id: &[u8; 4], encoding: u8, text: &[u8]) -> Vec<u8> {
+        let size = text.len() as u32 + 1;
+        let mut frame = id.to_vec();
+        if version == 4 {
+            frame.extend((0..4).rev().map(|shift| (size >> (7 * shift)) as u8 & 0x7F));
+        } else {
+            frame.extend_from_slice(&size.to_be_bytes());
+        }
+        frame.extend_from_slice(&[0, 0, encoding]);
+        frame.extend_from_slice(text);
+        frame
+    }
+
+    fn read_id3v2(bytes: &[u8]) -> Metadata<'_> {
+        let mut meta = Metadata::new(None);
+        parse_id3v2(bytes, &ParseOptions::default(), &mut meta);
+        meta
+    }
+
+    /// Tag ID3v2.4 avec pied de page « 3DI »
+    fn id3v24_tag_with_footer(body: &[u8]) -> Vec<u8> {
+        let mut tag = id3v2_tag(4, 0x10, body);
+        let mut footer = tag[..ID3V2_HEADER_SIZE].to_vec();
+        footer[0..3].copy_from_slice(b"3DI");
+        tag.extend(footer);
+        tag
+    }
+
+    fn seek_frame(offset: u32) -> Vec<u8> {
+        let mut frame = b"SEEK\0\0\0\x04\0\0".to_vec();
+        frame.extend_from_slice(&offset.to_be_bytes());
+        frame
+    }
+
+    fn id3v1_tag(title: &str, artist: &str, album: &str) -> Vec<u8> {
+        let mut tag = b"TAG".to_vec();
+        for text in [title, artist, album] {
+            let mut field = text.as_bytes().to_vec();
+            field.resize(30, 0);
+            tag.extend(field);
+        }
+        tag.resize(ID3V1_SIZE, 0);
+        tag
+    }
+
+    #[test]
+    fn appended_tag_is_found_before_ape_lyrics3_and_id3v1() {
+        let mut bytes = vec![0; 64];
+        bytes.extend(id3v24_tag_with_footer(&text_frame(
+            4,
+            b"TIT2",
+            3,
+            b"Appended",
+        )));
+        // APE sans en-tête (pied de page seul), Lyrics3v2 vide, puis ID3v1
+        let mut ape = b"APETAGEX".to_vec();
+        for value in [2000u32, 32, 0, 0] {
+            ape.extend_from_slice(&value.to_le_bytes());
+        }
+        ape.resize(APE_FOOTER_SIZE, 0);
+        bytes.extend(ape);
+        bytes.extend_from_slice(b"LYRICSBEGIN000011LYRICS200");
+        bytes.extend(id3v1_tag("Old title", "Old artist", ""));
+
+        assert_eq!(find_appended_id3v2(&bytes), Some(64));
+        let metadata = parse(&bytes, &ParseOptions::default()).unwrap();
+        assert_eq!(metadata.title.as_deref(), Some("Appended"));
+        // ID3v1 ne complète que les champs absents
+        assert_eq!(metadata.artist.as_deref(), Some("Old artist"));
+    }
+
+    #[test]
+    fn seek_frame_leads_to_the_next_tag() {
+        let mut body = text_frame(4, b"TIT2", 3, b"First");
+        body.extend(seek_frame(100));
+        let mut bytes = id3v2_tag(4, 0, &body);
+        bytes.resize(bytes.len() + 100, 0);
+        let second_start = bytes.len();
+        bytes.extend(id3v2_tag(4, 0, &text_frame(4, b"TPE1", 3, b"Second")));
+
+        let inspection = inspect(&bytes);
+        let offsets: Vec<_> = inspection.nodes.iter().map(|node| node.offset).collect();
+        assert_eq!(offsets, [Some(0), Some(second_start)]);
+        assert_eq!(inspection.metadata.title.as_deref(), Some("First"));
+        assert_eq!(inspection.metadata.artist.as_deref(), Some("Second"));
+    }
+
+    #[test]
+    fn seek_chain_is_capped() {
+        // Les offsets SEEK vont toujours vers l'avant : seule une chaîne sans fin est à craindre
+        let mut bytes = Vec::new();
+        for _ in 0..MAX_CHAINED_ID3_TAGS {
+            bytes.extend(id3v2_tag(4, 0, &seek_frame(0)));
+        }
+        bytes.extend(id3v2_tag(4, 0, &text_frame(4, b"TIT2", 3, b"Too far")));
+
+        let inspection = inspect(&bytes);
+        assert_eq!(inspection.nodes.len(), MAX_CHAINED_ID3_TAGS);
+        assert_eq!(inspection.metadata.title, None);
+    }
+
+    #[test]
+    fn head_tag_wins_over_appended_tag_and_id3v1() {
+        let mut bytes = id3v2_tag(3, 0, &text_frame(3, b"TIT2", 0, b"Head"));
+        bytes.resize(bytes.len() + 64, 0);
+        let mut body = text_frame(4, b"TIT2", 3, b"Tail");
+        body.extend(text_frame(4, b"TPE1", 3, b"Tail artist"));
+        bytes.extend(id3v24_tag_with_footer(&body));
+        bytes.extend(id3v1_tag("V1", "V1 artist", "V1 album"));
+
+        let metadata = parse(&bytes, &ParseOptions::default()).unwrap();
+        assert_eq!(metadata.title.as_deref(), Some("Head"));
+        assert_eq!(metadata.artist.as_deref(), Some("Tail artist"));
+        assert_eq!(metadata.album.as_deref(), Some("V1 album"));
+    }
+
+    #[test]
+    fn extended_header_does_not_extend_the_tag() {
+        for version in [3, 4] {
+            // En-tête étendu minimal : la v2.3 déclare 6 octets après la taille, la v2.4 6 en tout
+            let mut body = if version == 4 {
+                vec![0, 0, 0, 6, 1, 0]
+            } else {
+                vec![0, 0, 0, 6, 0, 0, 0, 0, 0, 0]
+            };
+            body.extend(text_frame(version, b"TIT2", 0, b"Title"));
+            let mut bytes = id3v2_tag(version, 0x40, &body);
+            // Octets qui suivent le tag : ils ne doivent pas être lus comme une frame
+            bytes.extend(text_frame(version, b"TPE1", 0, b"Audio"));
+            let meta = read_id3v2(&bytes);
+            assert_eq!(meta.title, strings(&["Title"]), "v2.{version}");
+            assert!(meta.artist.is_empty(), "v2.{version}");
+        }
+    }
+
+    #[test]
+    fn id3v23_artists_split_on_spaced_slashes_only() {
+        let mut body = text_frame(3, b"TPE1", 3, b"AC/DC / Queen");
+        body.extend(text_frame(3, b"TALB", 3, b"Live / Studio"));
+        let bytes = id3v2_tag(3, 0, &body);
+        let meta = read_id3v2(&bytes);
+        assert_eq!(meta.artist, strings(&["AC/DC", "Queen"]));
+        // Seul TPE1 est multi-valué par « / »
+        assert_eq!(meta.album, strings(&["Live / Studio"]));
+    }
+
+    #[test]
+    fn id3v23_utf16_values_each_carry_a_bom() {
+        let mut text = Vec::new();
+        for value in ["Björk", "Thom"] {
+            text.extend_from_slice(&[0xFF, 0xFE]);
+            text.extend(value.encode_utf16().flat_map(u16::to_le_bytes));
+            text.extend_from_slice(&[0, 0]);
+        }
+        let bytes = id3v2_tag(3, 0, &text_frame(3, b"TPE1", 1, &text));
+        let meta = read_id3v2(&bytes);
+        assert_eq!(meta.artist, strings(&["Björk", "Thom"]));
+    }
+
+    #[test]
+    fn id3v24_values_are_separated_by_nul() {
+        let mut body = text_frame(4, b"TPE1", 3, "Sigur Rós\0AC/DC\0".as_bytes());
+        body.extend(text_frame(4, b"TIT2", 3, b"One\0Two"));
+        let bytes = id3v2_tag(4, 0, &body);
+        let meta = read_id3v2(&bytes);
+        assert_eq!(meta.artist, strings(&["Sigur Rós", "AC/DC"]));
+        assert_eq!(meta.title, strings(&["One", "Two"]));
+    }
+
+    /// Bloc Vorbis comment : chaîne vendeur puis entrées « CLÉ=valeur »
+    fn vorbis_comment(vendor: &[u8], entries: &[&str]) -> Vec<u8> {
+        let mut block = (vendor.len() as u32).to_le_bytes().to_vec();
+        block.extend_from_slice(vendor);
+        block.extend_from_slice(&(entries.len() as u32).to_le_bytes());
+        for entry in entries {
+            block.extend_from_slice(&(entry.len() as u32).to_le_bytes());
+            block.extend_from_slice(entry.as_bytes());
+        }
+        block
+    }
+
+    #[test]
+    fn encoder_comes_from_the_vendor_and_encoder_keys() {
+        let block = vorbis_comment(
+            b"reference libFLAC 1.4.3 20230623",
+            &["ENCODER=flac -8", "ENCODED-BY=EAC", "ENCODER=ignored"],
+        );
+        let mut meta = Metadata::new(None);
+        parse_vorbis_comment(&block, &ParseOptions::default(), &mut meta);
+        let encoder = &meta.encoder;
+        assert_eq!(
+            encoder.name.as_deref(),
+            Some("reference libFLAC 1.4.3 20230623")
+        );
+        assert_eq!(encoder.settings.as_deref(), Some("flac -8"));
+        assert_eq!(encoder.encoded_by.as_deref(), Some("EAC"));
+
+        let mut meta = Metadata::new(None);
+        parse_vorbis_comment(
+            &vorbis_comment(b"\xFF\xFE", &[]),
+            &ParseOptions::default(),
+            &mut meta,
+        );
+        assert!(meta.encoder.name.is_none());
+        assert_eq!(meta.warnings[0].code, "invalid-encoding");
+    }
+
+    #[test]
+    fn id3_encoder_frames() {
+        let mut body = text_frame(3, b"TSSE", 0, b"LAME 64bits version 3.100 (-V 2)");
+        body.extend(text_frame(3, b"TENC", 0, b"iTunes"));
+        let bytes = id3v2_tag(3, 0, &body);
+        let meta = read_id3v2(&bytes);
+        assert_eq!(
+            meta.encoder.settings.as_deref(),
+            Some("LAME 64bits version 3.100 (-V 2)")
+        );
+        assert_eq!(meta.encoder.encoded_by.as_deref(), Some("iTunes"));
+    }
+
+    #[test]
+    fn mpeg_header_flags() {
+        // MPEG-1 Layer III joint stereo (M/S), CRC, copyright, original, emphase 50/15
+        let frame = mpeg::parse_frame_header(&[0xFF, 0xFA, 0x90, 0x6D], 0).unwrap();
+        let info = build_mpeg_info(&frame);
+        assert_eq!(info.format, "MPEG-1 Layer III joint stereo");
+        assert_eq!((info.version.as_str(), info.layer), ("1", 3));
+        assert_eq!(info.emphasis, "50/15");
+        assert!(info.copyright && info.original && info.crc_protected);
+        let extension = info.mode_extension.unwrap();
+        assert!(extension.ms_stereo && !extension.intensity_stereo);
+        assert_eq!(extension.intensity_bound, None);
+
+        // Layer II : intensity stereo à partir de la sous-bande 16, sans CRC
+        let frame = mpeg::parse_frame_header(&[0xFF, 0xFD, 0x90, 0x70], 0).unwrap();
+        let info = build_mpeg_info(&frame);
+        assert_eq!(info.layer, 2);
+        assert!(!info.copyright && !info.original && !info.crc_protected);
+        assert_eq!(info.emphasis, "none");
+        assert_eq!(info.mode_extension.unwrap().intensity_bound, Some(16));
+
+        // Hors joint stereo, pas d'extension de mode
+        let frame = mpeg::parse_frame_header(&[0xFF, 0xFB, 0x90, 0xC0], 0).unwrap();
+        let info = build_mpeg_info(&frame);
+        assert_eq!(info.channel_mode, "mono");
+        assert!(info.mode_extension.is_none());
+    }
+
+    /// Page Ogg du flux 1 contenant `packets`
+    fn ogg_page(flags: u8, granule: u64, sequence: u32, packets: &[&[u8]]) -> Vec<u8> {
+        let mut lacing = Vec::new();
+        for packet in packets {
+            lacing.extend(std::iter::repeat_n(255, packet.len() / 255));
+            lacing.push((packet.len() % 255) as u8);
+        }
+        let mut page = OGG_SIGNATURE.to_vec();
+        page.extend_from_slice(&[0, flags]);
+        page.extend_from_slice(&granule.to_le_bytes());
+        page.extend_from_slice(&1u32.to_le_bytes());
+        page.extend_from_slice(&sequence.to_le_bytes());
+        page.extend_from_slice(&[0; 4]);
+        page.push(lacing.len() as u8);
+        page.extend(lacing);
+        for packet in packets {
+            page.extend_from_slice(packet);
+        }
+        let crc = ogg_crc(&page);
+        page[22..26].copy_from_slice(&crc.to_le_bytes());
+        page
+    }
+
+    /// Fichier Opus stéréo : OpusHead, OpusTags, puis une page de fin à `last_granule`
+    fn opus_file(pre_skip: u16, last_granule: u64) -> Vec<u8> {
+        let mut head = b"OpusHead\x01\x02".to_vec();
+        head.extend_from_slice(&pre_skip.to_le_bytes());
+        head.extend_from_slice(&48_000u32.to_le_bytes());
+        head.extend_from_slice(&[0, 0, 0]);
+        let mut tags = b"OpusTags".to_vec();
+        tags.extend(vorbis_comment(b"libopus 1.4", &[]));
+        let mut bytes = ogg_page(0x02, 0, 0, &[&head]);
+        bytes.extend(ogg_page(0, 0, 1, &[&tags]));
+        bytes.extend(ogg_page(0x04, last_granule, 2, &[&[0xFC; 40]]));
+        bytes
+    }
+
+    #[test]
+    fn opus_duration_excludes_the_pre_skip() {
+        let bytes = opus_file(312, 3 * 48_000 + 312);
+        let metadata = parse(&bytes, &ParseOptions::default()).unwrap();
+        assert_eq!(metadata.audio.sample_rate, Some(48_000));
+        assert_eq!(metadata.audio.duration_ms, Some(3000));
+        let gapless = metadata.gapless.unwrap();
+        assert_eq!(gapless.source, "opus");
+        assert_eq!((gapless.delay, gapless.padding), (312, None));
+        assert_eq!(gapless.valid_samples, Some(144_000));
+        assert_eq!(
+            metadata.encoder.unwrap().name.as_deref(),
+            Some("libopus 1.4")
+        );
+    }
+
+    #[test]
+    fn huge_granule_positions_do_not_overflow() {
+        let bytes = opus_file(0, u64::MAX - 1);
+        let metadata = parse(&bytes, &ParseOptions::default()).unwrap();
+        assert_eq!(metadata.audio.duration_ms, Some(u64::MAX / 48_000));
+
+        let mut ident = b"\x01vorbis".to_vec();
+        ident.extend_from_slice(&[0; 4]);
+        ident.push(2);
+        ident.extend_from_slice(&44_100u32.to_le_bytes());
+        ident.extend_from_slice(&[0; 14]);
+        let mut bytes = ogg_page(0x02, 0, 0, &[&ident]);
+        bytes.extend(ogg_page(0x04, u64::MAX - 1, 1, &[&[0; 8]]));
+        let metadata = parse(&bytes, &ParseOptions::default()).unwrap();
+        assert_eq!(metadata.audio.duration_ms, Some(u64::MAX / 44_100));
+    }
+
+    #[test]
+    fn lame_delay_and_padding_give_valid_samples() {
+        // MPEG-1 Layer III, 128 kbit/s, 44,1 kHz : 417 octets et 1152 échantillons par trame
+        let frame = |body: &[u8]| {
+            let mut frame = vec![0xFF, 0xFB, 0x90, 0x00];
+            frame.extend_from_slice(&[0; 32]);
+            frame.extend_from_slice(body);
+            frame.resize(417, 0);
+            frame
+        };
+        let mut xing = b"Xing".to_vec();
+        xing.extend_from_slice(&1u32.to_be_bytes());
+        xing.extend_from_slice(&100u32.to_be_bytes());
+        xing.extend_from_slice(b"LAME3.100");
+        xing.extend_from_slice(&[0; 12]);
+        // Délai 576, remplissage 1234 sur 12 bits chacun
+        xing.extend_from_slice(&[0x24, 0x04, 0xD2]);
+        let mut bytes = frame(&xing);
+        for _ in 0..3 {
+            bytes.extend(frame(&[]));
+        }
+        let metadata = parse(&bytes, &ParseOptions::default()).unwrap();
+        let gapless = metadata.gapless.unwrap();
+        assert_eq!(gapless.source, "lame");
+        assert_eq!((gapless.delay, gapless.padding), (576, Some(1234)));
+        assert_eq!(gapless.valid_samples, Some(100 * 1152 - 576 - 1234));
+    }
+
+    fn warnings(metadata: &model::Metadata) -> Vec<(&str, Option<usize>)> {
+        metadata
+            .warnings
+            .iter()
+            .map(|warning| (warning.code.as_str(), warning.offset))
+            .collect()
+    }
+
+    #[test]
+    fn truncated_id3_frame_and_tag_are_reported() {
+        // TIT2 annonce 100 octets dans un tag qui n'en contient que 15
+        let mut body = text_frame(3, b"TIT2", 0, b"Title");
+        body[4..8].copy_from_slice(&100u32.to_be_bytes());
+        let bytes = id3v2_tag(3, 0, &body);
+        let metadata = parse(&bytes, &ParseOptions::default()).unwrap();
+        assert_eq!(warnings(&metadata), [("frame-overrun", Some(10))]);
+
+        let bytes = id3v2_tag(3, 0, &text_frame(3, b"TIT2", 0, b"Title"));
+        let metadata = parse(&bytes[..bytes.len() - 2], &ParseOptions::default()).unwrap();
+        assert_eq!(warnings(&metadata)[0], ("truncated-tag", Some(0)));
+    }
+
+    #[test]
+    fn flac_block_past_the_end_of_the_file_is_reported() {
+        let mut bytes = flac_file(&[(4, vorbis_comment(b"", &["TITLE=A"]))], 0);
+        // Le bloc suit la signature (4) et STREAMINFO (4 + 34)
+        bytes[43..46].copy_from_slice(&[0x00, 0xFF, 0xFF]);
+        let metadata = parse(&bytes, &ParseOptions::default()).unwrap();
+        assert_eq!(warnings(&metadata), [("truncated-block", Some(42))]);
+    }
+
+    #[test]
+    fn ogg_page_with_a_bad_checksum_is_reported() {
+        let mut bytes = opus_file(312, 48_000 + 312);
+        let metadata = parse(&bytes, &ParseOptions::default()).unwrap();
+        assert!(metadata.warnings.is_empty());
+
+        // Un octet du vendeur modifié dans la deuxième page (OpusTags)
+        let second_page = bytes[1..]
+            .windows(4)
+            .position(|window| window == OGG_SIGNATURE)
+            .unwrap()
+            + 1;
+        let vendor = bytes.len() - 40 - OGG_PAGE_HEADER_SIZE - 1 - 8;
+        bytes[vendor] ^= 0x20;
+        let metadata = parse(&bytes, &ParseOptions::default()).unwrap();
+        assert_eq!(
+            warnings(&metadata),
+            [("ogg-crc-mismatch", Some(second_page))]
+        );
+        assert_eq!(metadata.audio.duration_ms, Some(1000));
+    }
+
+    /// Début d'un JPEG : marqueur SOI puis segment APP0 ; `len` octets au total
+    fn jpeg_bytes(len: usize) -> Vec<u8> {
+        let mut data = vec![0xFF, 0xD8, 0xFF, 0xE0];
+        data.resize(len, 0x11);
+        data
+    }
+
+    /// Frame APIC (ID3v2.3) en ISO-8859-1
+    fn apic_frame(pic_type: u8, mime: &str, description: &str, data: &[u8]) -> Vec<u8> {
+        let mut body = vec![0];
+        body.extend_from_slice(mime.as_bytes());
+        body.push(0);
+        body.push(pic_type);
+        body.extend_from_slice(description.as_bytes());
+        body.push(0);
+        body.extend_from_slice(data);
+        let mut frame = b"APIC".to_vec();
+        frame.extend_from_slice(&(body.len() as u32).to_be_bytes());
+        frame.extend_from_slice(&[0, 0]);
+        frame.extend(body);
+        frame
+    }
+
+    /// Frame PIC (ID3v2.2) : format d'image sur trois caractères
+    fn pic_frame(pic_type: u8, format: &[u8; 3], description: &str, data: &[u8]) -> Vec<u8> {
+        let mut body = vec![0];
+        body.extend_from_slice(format);
+        body.push(pic_type);
+        body.extend_from_slice(description.as_bytes());
+        body.push(0);
+        body.extend_from_slice(data);
+        let mut frame = b"PIC".to_vec();
+        frame.extend_from_slice(&(body.len() as u32).to_be_bytes()[1..]);
+        frame.extend(body);
+        frame
+    }
+
+    #[test]
+    fn apic_pictures_are_listed_with_type_and_description() {
+        let mut body = apic_frame(4, "image/jpeg", "Back", &jpeg_bytes(2000));
+        body.extend(apic_frame(3, "image/jpg", "Front", &jpeg_bytes(40)));
+        let bytes = id3v2_tag(3, 0, &body);
+        let options = ParseOptions {
+            include_pictures: true,
+            include_picture_data: true,
+            max_cover_bytes: 1000,
+            ..ParseOptions::default()
+        };
+        let metadata = parse(&bytes, &options).unwrap();
+        let listed: Vec<_> = metadata
+            .pictures
+            .iter()
+            .map(|picture| {
+                (
+                    picture.pic_type,
+                    picture.type_name.as_str(),
+                    picture.description.as_str(),
+                    picture.byte_length,
+                    picture.data.as_ref().map(Vec::len),
+                )
+            })
+            .collect();
+        // L'image au-delà de `max_cover_bytes` reste listée, sans ses octets
+        assert_eq!(
+            listed,
+            [
+                (4, "Cover (back)", "Back", 2000, None),
+                (3, "Cover (front)", "Front", 40, Some(40)),
+            ]
+        );
+        assert_eq!(metadata.pictures[1].mime, "image/jpeg");
+        assert_eq!(metadata.cover_type, Some(3));
+        assert_eq!(metadata.warnings[0].code, "cover-too-large");
+
+        let options = ParseOptions {
+            include_picture_data: false,
+            ..options
+        };
+        let metadata = parse(&bytes, &options).unwrap();
+        assert_eq!(metadata.pictures.len(), 2);
+        assert!(
+            metadata
+                .pictures
+                .iter()
+                .all(|picture| picture.data.is_none())
+        );
+
+        let metadata = parse(&bytes, &ParseOptions::default()).unwrap();
+        assert!(metadata.pictures.is_empty());
+        assert_eq!(metadata.cover_mime.as_deref(), Some("image/jpeg"));
+    }
+
+    #[test]
+    fn pic_frames_and_links() {
+        let mut body = pic_frame(8, b"JPG", "Band", &jpeg_bytes(64));
+        body.extend(pic_frame(3, b"-->", "", b"http://example.com/cover.jpg"));
+        let bytes = id3v2_tag(2, 0, &body);
+        let options = ParseOptions {
+            include_pictures: true,
+            ..ParseOptions::default()
+        };
+        let metadata = parse(&bytes, &options).unwrap();
+        let picture = &metadata.pictures[0];
+        assert_eq!(
+            (picture.type_name.as_str(), picture.description.as_str()),
+            ("Artist/performer", "Band")
+        );
+        assert_eq!(picture.mime, "image/jpeg");
+        let link = &metadata.picture_links[0];
+        assert_eq!(link.type_name, "Cover (front)");
+        assert_eq!(link.url, "http://example.com/cover.jpg");
+        // Un lien n'est pas une pochette : à défaut de couverture avant, la première image
+        assert_eq!(metadata.cover_type, Some(8));
+    }
+
+    #[test]
+    fn picture_link_mime_is_not_sniffed() {
+        // Le contenu d'un lien est une URL : ni format reconnu, ni pochette
+        let bytes = id3v2_tag(3, 0, &apic_frame(3, "-->", "", b"cover.png"));
+        let options = ParseOptions {
+            include_pictures: true,
+            ..ParseOptions::default()
+        };
+        let metadata = parse(&bytes, &options).unwrap();
+        assert!(metadata.pictures.is_empty());
+        assert_eq!(metadata.picture_links[0].url, "cover.png");
+        assert_eq!(metadata.cover_mime, None);
+    }
+
+    /// Image dont plusieurs 0xFF appellent la désynchronisation
+    fn unsync_prone_image() -> Vec<u8> {
+        let mut image = jpeg_bytes(48);
+        image.extend_from_slice(&[0xFF, 0x00, 0x12, 0xFF, 0xE1, 0xFF, 0xFF, 0xD9]);
+        image
+    }
+
+    /// Désynchroniser : 0x00 inséré après chaque 0xFF suivi de 0x00, d'un octet ≥ 0xE0 ou final
+    fn unsynchronise(data: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        for (index, &byte) in data.iter().enumerate() {
+            out.push(byte);
+            if byte == 0xFF
+                && data
+                    .get(index + 1)
+                    .is_none_or(|&next| next == 0 || next >= 0xE0)
+            {
+                out.push(0);
+            }
+        }
+        out
+    }
+
+    /// Octets désignés par `cover_ref`, resynchronisés comme le ferait `readDataRef`
+    fn referenced_cover(bytes: &[u8], metadata: &model::Metadata) -> Vec<u8> {
+        let data_ref = metadata.cover_ref.as_ref().unwrap();
+        let start = data_ref.offset as usize;
+        let raw = &bytes[start..start + data_ref.length];
+        match resynchronise(raw) {
+            Some((data, _)) if data_ref.unsynchronised => data,
+            _ => raw.to_vec(),
+        }
+    }
+
+    fn ref_options() -> ParseOptions {
+        ParseOptions {
+            cover_refs: true,
+            ..ParseOptions::default()
+        }
+    }
+
+    #[test]
+    fn cover_ref_in_a_v23_tag_with_tag_unsynchronisation() {
+        let image = unsync_prone_image();
+        let body = unsynchronise(&apic_frame(3, "image/jpeg", "", &image));
+        let bytes = id3v2_tag(3, 0x80, &body);
+        let metadata = parse(&bytes, &ref_options()).unwrap();
+        assert!(metadata.cover_ref.as_ref().unwrap().unsynchronised);
+        assert_eq!(referenced_cover(&bytes, &metadata), image);
+    }
+
+    #[test]
+    fn cover_ref_in_a_v24_frame_with_unsynchronisation_and_length_indicator() {
+        let image = unsync_prone_image();
+        let mut content = vec![0];
+        content.extend_from_slice(b"image/jpeg\0\x03\0");
+        content.extend_from_slice(&image);
+        let synchsafe = |size: usize| {
+            (0..4)
+                .rev()
+                .map(move |shift| (size >> (7 * shift)) as u8 & 0x7F)
+        };
+        // Indicateur de longueur (taille resynchronisée) puis contenu désynchronisé
+        let mut data: Vec<u8> = synchsafe(content.len()).collect();
+        data.extend(unsynchronise(&content));
+        let mut frame = b"APIC".to_vec();
+        frame.extend(synchsafe(data.len()));
+        frame.extend_from_slice(&[0x00, 0x03]);
+        frame.extend(data);
+        let bytes = id3v2_tag(4, 0, &frame);
+        let metadata = parse(&bytes, &ref_options()).unwrap();
+        assert!(metadata.cover_ref.as_ref().unwrap().unsynchronised);
+        assert_eq!(referenced_cover(&bytes, &metadata), image);
+    }
+
+    #[test]
+    fn cover_ref_in_a_flac_picture_block() {
+        let image = unsync_prone_image();
+        let bytes = flac_file(&[(6, flac_picture_block("image/jpeg", "", &image))], 100);
+        let metadata = parse(&bytes, &ref_options()).unwrap();
+        assert!(!metadata.cover_ref.as_ref().unwrap().unsynchronised);
+        assert_eq!(referenced_cover(&bytes, &metadata), image);
+        assert_eq!(metadata.cover_data, None);
+    }
+
+    #[test]
+    fn itunsmpb_comment_gives_gapless_info() {
+        let text = b"engiTunSMPB\0 00000000 00000210 000003C4 0000000000ABCDEF";
+        let bytes = id3v2_tag(3, 0, &text_frame(3, b"COMM", 0, text));
+        let gapless = read_id3v2(&bytes).gapless.unwrap();
+        assert_eq!((gapless.delay, gapless.padding), (0x210, Some(0x3C4)));
+        assert_eq!(gapless.valid_samples, Some(0xABCDEF));
+    }
+
+    #[test]
+    fn comment_shorter_than_its_language_is_ignored() {
+        for text in [&b""[..], b"e", b"eng"] {
+            let bytes = id3v2_tag(3, 0, &text_frame(3, b"COMM", 0, text));
+            assert!(read_id3v2(&bytes).gapless.is_none());
+            let bytes = id3v2_tag(3, 0, &text_frame(3, b"COMM", 1, text));
+            assert!(read_id3v2(&bytes).gapless.is_none());
+        }
+    }
+
+    /// Bloc PICTURE FLAC : couverture avant de 4 × 4 px, 24 bits
+    fn flac_picture_block(mime: &str, description: &str, image: &[u8]) -> Vec<u8> {
+        let mut block = u32::from(FRONT_COVER_TYPE).to_be_bytes().to_vec();
+        for text in [mime, description] {
+            block.extend_from_slice(&(text.len() as u32).to_be_bytes());
+            block.extend_from_slice(text.as_bytes());
+        }
+        for value in [4u32, 4, 24, 0, image.len() as u32] {
+            block.extend_from_slice(&value.to_be_bytes());
+        }
+        block.extend_from_slice(image);
+        block
+    }
+
+    #[test]
+    fn truncated_flac_picture_is_rejected_at_every_length() {
+        let block = flac_picture_block("image/jpeg", "Cover", &[0xAB; 40]);
+        let header_len = block.len() - 40;
+        for len in 0..block.len() {
+            assert!(parse_flac_picture(&block[..len]).is_none(), "{len} octets");
+            // Lecture partielle : l'en-tête suffit, l'image peut manquer
+            let partial = parse_flac_picture_prefix(&block[..len], block.len());
+            assert_eq!(partial.is_some(), len >= header_len, "{len} octets");
+        }
+        let picture = parse_flac_picture(&block).unwrap();
+        assert_eq!(
+            (picture.pic_type, picture.description.as_str()),
+            (3, "Cover")
+        );
+        assert_eq!((picture.width, picture.height), (Some(4), Some(4)));
+        assert_eq!(picture.data.len(), 40);
+    }
+
+    #[test]
+    fn flac_picture_lengths_past_the_block_are_rejected() {
+        // Type MIME qui atteint la fin du bloc : plus de place pour la longueur de la description
+        let mut block = u32::from(FRONT_COVER_TYPE).to_be_bytes().to_vec();
+        block.extend_from_slice(&24u32.to_be_bytes());
+        block.resize(32, b'a');
+        assert!(parse_flac_picture(&block).is_none());
+
+        let mut block = flac_picture_block("image/png", "", &[1, 2, 3]);
+        block[4..8].copy_from_slice(&u32::MAX.to_be_bytes());
+        assert!(parse_flac_picture(&block).is_none());
+        let mut block = flac_picture_block("image/png", "", &[1, 2, 3]);
+        let len_at = block.len() - 7;
+        block[len_at..len_at + 4].copy_from_slice(&u32::MAX.to_be_bytes());
+        assert!(parse_flac_picture(&block).is_none());
+    }
+
+    /// Fichier FLAC : STREAMINFO de 2 s à 44 100 Hz, stéréo 16 bits, les blocs `(type, contenu)`
+    /// de `blocks`, puis `audio_len` octets
+    fn flac_file(blocks: &[(u8, Vec<u8>)], audio_len: usize) -> Vec<u8> {
+        let mut streaminfo = vec![0u8; 34];
+        streaminfo[10..14].copy_from_slice(&[0x0A, 0xC4, 0x42, 0xF0]);
+        streaminfo[14..18].copy_from_slice(&88_200u32.to_be_bytes());
+        let mut bytes = FLAC_SIGNATURE.to_vec();
+        let all = std::iter::once((0, streaminfo)).chain(blocks.iter().cloned());
+        for (index, (block_type, body)) in all.enumerate() {
+            let last = if index == blocks.len() { 0x80 } else { 0 };
+            let len = (body.len() as u32).to_be_bytes();
+            bytes.extend_from_slice(&[last | block_type, len[1], len[2], len[3]]);
+            bytes.extend(body);
+        }
+        bytes.resize(bytes.len() + audio_len, 0xFF);
+        bytes
+    }
+
+    /// Numéro, position et index (numéro, position relative) d'une piste
+    type CueTrackSpec<'a> = (u8, u64, &'a [(u8, u64)]);
+
+    /// Bloc CUESHEET (type 5) : pistes données par leur position et leurs index relatifs
+    fn flac_cuesheet_block(tracks: &[CueTrackSpec]) -> Vec<u8> {
+        // Catalogue, lead-in, drapeau CD et réservé : 395 octets, puis le nombre de pistes
+        let mut block = vec![0; 395];
+        block.push(tracks.len() as u8);
+        for &(number, offset, indices) in tracks {
+            block.extend_from_slice(&offset.to_be_bytes());
+            block.push(number);
+            block.extend_from_slice(&[0; 26]);
+            block.push(indices.len() as u8);
+            for &(index, relative) in indices {
+                block.extend_from_slice(&relative.to_be_bytes());
+                block.extend_from_slice(&[index, 0, 0, 0]);
+            }
+        }
+        block
+    }
+
+    #[test]
+    fn flac_cuesheet_block_gives_tracks_ending_at_total_samples() {
+        let cue_block = flac_cuesheet_block(&[
+            (1, 0, &[(1, 0)]),
+            // Pre-gap de 1470 échantillons (33 ms) avant la piste 2
+            (2, 29_400, &[(0, 0), (1, 1470)]),
+            (3, 60_000, &[(1, 0)]),
+        ]);
+        // Le bloc binaire prime sur le commentaire CUESHEET, même lu avant lui
+        let comment = vorbis_comment(
+            b"",
+            &["CUESHEET=FILE \"a.flac\" WAVE\n  TRACK 09 AUDIO\n    INDEX 01 00:00:00"],
+        );
+        let bytes = flac_file(&[(4, comment), (5, cue_block)], 0);
+        let metadata = parse(&bytes, &ParseOptions::default()).unwrap();
+        let sheet = metadata.cue_sheet.unwrap();
+        let tracks: Vec<_> = sheet
+            .tracks
+            .iter()
+            .map(|track| {
+                (
+                    track.number,
+                    track.start_sample,
+                    track.end_sample,
+                    track.start_ms,
+                    track.end_ms,
+                    track.pre_gap_ms,
+                )
+            })
+            .collect();
+        // Sans lead-out, la dernière piste se termine à `total_samples` (88200)
+        assert_eq!(
+            tracks,
+            [
+                (1, Some(0), Some(30_870), Some(0), Some(700), None),
+                (
+                    2,
+                    Some(30_870),
+                    Some(60_000),
+                    Some(700),
+                    Some(1360),
+                    Some(33)
+                ),
+                (3, Some(60_000), Some(88_200), Some(1360), Some(2000), None),
+            ]
+        );
+        assert_eq!(sheet.tracks[1].indices[0].sample, 29_400);
+    }
+
+    #[test]
+    fn flac_bitrate_needs_the_file_size() {
+        let bytes = flac_file(&[], 64_000);
+        let metadata = parse(&bytes[..1024], &ParseOptions::default()).unwrap();
+        assert_eq!(metadata.audio.duration_ms, Some(2000));
+        assert_eq!(metadata.audio.bitrate, None);
+
+        let options = ParseOptions {
+            file_size: Some(bytes.len() as u64),
+            ..ParseOptions::default()
+        };
+        let metadata = parse(&bytes[..1024], &options).unwrap();
+        assert_eq!(metadata.audio.sample_rate, Some(44_100));
+        assert_eq!(metadata.audio.bit_depth, Some(16));
+        assert_eq!(metadata.audio.bitrate, Some(256));
+    }
+
+    #[test]
+    fn flac_seek_table_application_ids_and_padding() {
+        let mut seek_table = Vec::new();
+        for (sample, offset) in [(0u64, 0u64), (44_100, 32_000), (u64::MAX, 0)] {
+            seek_table.extend_from_slice(&sample.to_be_bytes());
+            seek_table.extend_from_slice(&offset.to_be_bytes());
+            seek_table.extend_from_slice(&4096u16.to_be_bytes());
+        }
+        let blocks = [
+            (3, seek_table),
+            (2, b"riff\x01\x02".to_vec()),
+            (2, vec![0, 0, 0x12, 0x34]),
+            (1, vec![0; 100]),
+        ];
+        let bytes = flac_file(&blocks, 1000);
+        let options = ParseOptions {
+            include_seek_points: true,
+            ..ParseOptions::default()
+        };
+        let metadata = parse(&bytes, &options).unwrap();
+        let flac = metadata.flac.unwrap();
+        // Le point réservé (échantillon 0xFFFF…) est ignoré
+        let points: Vec<_> = flac
+            .seek_table
+            .iter()
+            .map(|point| (point.sample, point.offset, point.time_ms))
+            .collect();
+        assert_eq!(points, [(0, 0, Some(0)), (44_100, 32_000, Some(1000))]);
+        assert_eq!(flac.application_ids, ["riff", "0x00001234"]);
+        assert_eq!(flac.padding_bytes, 100);
+        let audio_offset = (bytes.len() - 1000) as u64;
+        assert_eq!(flac.audio_offset, Some(audio_offset));
+        assert_eq!(metadata.seek_points.len(), 2);
+        assert_eq!(metadata.seek_points[1].offset, audio_offset + 32_000);
+    }
+
+    #[test]
+    fn huge_seek_point_samples_do_not_overflow() {
+        let mut seek_table = (u64::MAX - 1).to_be_bytes().to_vec();
+        seek_table.extend_from_slice(&0u64.to_be_bytes());
+        seek_table.extend_from_slice(&4096u16.to_be_bytes());
+        let bytes = flac_file(&[(3, seek_table)], 1000);
+        let options = ParseOptions {
+            include_seek_points: true,
+            ..ParseOptions::default()
+        };
+        let metadata = parse(&bytes, &options).unwrap();
+        let time_ms = u64::MAX / 44_100;
+        assert_eq!(metadata.seek_points[0].time_ms, time_ms);
+        assert_eq!(metadata.flac.unwrap().seek_table[0].time_ms, Some(time_ms));
+    }
+
+    fn base64(data: &[u8]) -> String {
+        const ALPHABET: &[u8; 64] =
+            b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+        let mut out = String::new();
+        for chunk in data.chunks(3) {
+            let bytes = [
+                chunk[0],
+                *chunk.get(1).unwrap_or(&0),
+                *chunk.get(2).unwrap_or(&0),
+            ];
+            let bits = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+            for index in 0..4 {
+                out.push(if index <= chunk.len() {
+                    ALPHABET[(bits >> (18 - 6 * index) & 0x3F) as usize] as char
+                } else {
+                    '='
+                });
+            }
+        }
+        out
+    }
+
+    /// Fichier FLAC dont le bloc VORBIS_COMMENT contient `entries`
+    fn flac_with_comments(entries: &[&str]) -> Vec<u8> {
+        flac_file(&[(4, vorbis_comment(b"", entries))], 0)
+    }
+
+    fn picture_options(max_cover_bytes: usize) -> ParseOptions {
+        ParseOptions {
+            include_pictures: true,
+            max_cover_bytes,
+            ..ParseOptions::default()
+        }
+    }
+
+    #[test]
+    fn vorbis_pictures_prefer_the_front_cover() {
+        let mut back = flac_picture_block("image/png", "Back", &[0x89; 16]);
+        back[..4].copy_from_slice(&4u32.to_be_bytes());
+        let front = flac_picture_block("image/png", "Front", &jpeg_bytes(24));
+        let back = format!("METADATA_BLOCK_PICTURE={}", base64(&back));
+        let front = format!("metadata_block_picture={}", base64(&front));
+        let bytes = flac_with_comments(&[&back, &front]);
+        let metadata = parse(&bytes, &picture_options(1000)).unwrap();
+        let listed: Vec<_> = metadata
+            .pictures
+            .iter()
+            .map(|picture| (picture.pic_type, picture.description.as_str()))
+            .collect();
+        assert_eq!(listed, [(4, "Back"), (3, "Front")]);
+        // Type MIME déduit de l'en-tête, pas celui déclaré
+        assert_eq!(metadata.cover_mime.as_deref(), Some("image/jpeg"));
+        assert_eq!(metadata.cover_type, Some(3));
+    }
+
+    #[test]
+    fn legacy_coverart_uses_coverartmime() {
+        // COVERARTMIME peut suivre COVERART ; contenu non reconnu : type déclaré conservé
+        let cover = format!("COVERART={}", base64(&[1, 2, 3, 4, 5]));
+        let bytes = flac_with_comments(&[&cover, "COVERARTMIME=GIF"]);
+        let metadata = parse(&bytes, &picture_options(1000)).unwrap();
+        assert_eq!(metadata.cover_mime.as_deref(), Some("image/gif"));
+        assert_eq!(metadata.cover_type, Some(FRONT_COVER_TYPE));
+        assert_eq!(metadata.pictures[0].byte_length, 5);
+    }
+
+    #[test]
+    fn vorbis_pictures_over_max_cover_bytes_are_skipped() {
+        let block = flac_picture_block("image/jpeg", "", &jpeg_bytes(200));
+        let picture = format!("METADATA_BLOCK_PICTURE={}", base64(&block));
+        let cover = format!("COVERART={}", base64(&jpeg_bytes(200)));
+        let bytes = flac_with_comments(&[&picture, &cover]);
+        let metadata = parse(&bytes, &picture_options(100)).unwrap();
+        assert!(metadata.pictures.is_empty());
+        assert_eq!(metadata.cover_mime, None);
+        let codes: Vec<_> = metadata.warnings.iter().map(|w| w.code.as_str()).collect();
+        assert_eq!(codes, ["cover-too-large", "cover-too-large"]);
+    }
+
+    #[test]
+    fn invalid_coverart_is_traced_as_skipped() {
+        let bytes = flac_with_comments(&["COVERART=not*base64", "COVERARTMIME=image/png"]);
+        let inspection = inspect(&bytes);
+        assert_eq!(inspection.metadata.cover_mime, None);
+        fn find<'n>(nodes: &'n [InspectNode], id: &str) -> Option<&'n InspectNode> {
+            nodes.iter().find_map(|node| {
+                (node.id == id)
+                    .then_some(node)
+                    .or_else(|| find(&node.children, id))
+            })
+        }
+        let node = find(&inspection.nodes, "COVERART").unwrap();
+        assert!(matches!(node.usage, Some(Usage::Skipped(INVALID_PICTURE))));
+    }
+
+    #[test]
+    fn inspect_maps_frames_and_gives_skip_reasons() {
+        let mut body = text_frame(3, b"TIT2", 0, b"First");
+        body.extend(text_frame(3, b"TIT2", 0, b"Second"));
+        body.extend(text_frame(3, b"TXXX", 0, b"\0note"));
+        let bytes = id3v2_tag(3, 0, &body);
+        let inspection = inspect(&bytes);
+        assert_eq!(inspection.format, Format::Mpeg);
+        assert_eq!(inspection.metadata.title.as_deref(), Some("First"));
+
+        let tag = &inspection.nodes[0];
+        assert_eq!(
+            (tag.kind, tag.offset, tag.size),
+            ("id3v2", Some(0), bytes.len())
+        );
+        let frames: Vec<_> = tag
+            .children
+            .iter()
+            .map(|node| (node.id.as_str(), node.offset, node.usage))
+            .collect();
+        assert!(matches!(
+            frames[..],
+            [
+                ("TIT2", Some(10), Some(Usage::Mapped("title"))),
+                ("TIT2", Some(26), Some(Usage::Skipped(ALREADY_SET))),
+                ("TXXX", Some(43), Some(Usage::Skipped(UNSUPPORTED))),
+            ]
+        ));
+        assert_eq!(
+            tag.children[0].details,
+            [
+                ("flags", "0x0000".to_string()),
+                ("encoding", "ISO-8859-1 (declared)".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn tag_without_mapped_frame_is_still_recognized() {
+        let bytes = id3v2_tag(3, 0, &text_frame(3, b"TXXX", 0, b""));
+        let metadata = parse(&bytes, &ParseOptions::default()).unwrap();
+        assert_eq!(metadata.title, None);
+        assert!(matches!(
+            parse(&[0x55; 64], &ParseOptions::default()),
+            Err(Error::UnrecognizedFormat)
+        ));
+    }
+
+    #[test]
+    fn oversized_pixel_buffer_dimensions_are_rejected() {
+        let result = extract_palette_rgba(&[0; 16], u32::MAX, u32::MAX);
+        assert!(matches!(result, Err(Error::InvalidPixelBuffer { .. })));
+    }
+
+    #[test]
+    fn pixel_buffer_length_must_match_dimensions() {
+        let pixels = [10, 20, 30, 255].repeat(4);
+        assert!(matches!(
+            extract_palette_rgba(&pixels, 3, 1),
+            Err(Error::InvalidPixelBuffer { length: 16, .. })
+        ));
+        assert!(matches!(
+            extract_palette_rgba(&[], 0, 0),
+            Err(Error::InvalidPixelBuffer { .. })
+        ));
+        let palette = extract_palette_rgba(&pixels, 2, 2).unwrap();
+        assert_eq!(palette.dominant.hex, "#0a141e");
+        assert!(matches!(
+            extract_palette_rgba(&[0; 16], 2, 2),
+            Err(Error::TransparentImage)
+        ));
+    }
+}
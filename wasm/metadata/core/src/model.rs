@@ -0,0 +1,574 @@
+// ---------------------------------------------------------------------------
+// Résultat de l'analyse : structures sérialisées vers JS (serde) et décrites en
+// TypeScript (ts-rs, `pnpm wasm:types` régénère src/types/metadata.ts)
+// ---------------------------------------------------------------------------
+
+use std::ops::Range;
+
+use serde::{Serialize, Serializer};
+use ts_rs::TS;
+
+/// Métadonnées d'un fichier audio
+#[derive(Serialize, TS, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "metadata.ts", rename = "MetadataResult")]
+pub struct Metadata {
+    /// Valeurs jointes par « ; » pour l'affichage
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub artist: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub album: Option<String>,
+    /// Toutes les valeurs des champs multi-valués, dans l'ordre des tags
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[ts(optional, as = "Option<Vec<String>>")]
+    pub titles: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[ts(optional, as = "Option<Vec<String>>")]
+    pub artists: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[ts(optional, as = "Option<Vec<String>>")]
+    pub albums: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub cover_mime: Option<String>,
+    #[serde(with = "serde_bytes", skip_serializing_if = "Option::is_none")]
+    #[ts(optional, type = "Uint8Array")]
+    pub cover_data: Option<Vec<u8>>,
+    /// Emplacement de la pochette dans l'entrée, avec l'option `cover_refs`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub cover_ref: Option<DataRef>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub cover_type: Option<u8>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[ts(optional, as = "Option<Vec<Thumbnail>>")]
+    pub cover_thumbnails: Vec<Thumbnail>,
+    #[serde(flatten)]
+    pub audio: AudioProperties,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub mpeg: Option<MpegInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub flac: Option<FlacInfo>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[ts(optional, as = "Option<Vec<SeekPoint>>")]
+    pub seek_points: Vec<SeekPoint>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub gapless: Option<Gapless>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub cue_sheet: Option<CueSheet>,
+    #[serde(flatten)]
+    pub lyrics: Lyrics,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[ts(optional, as = "Option<Vec<Picture>>")]
+    pub pictures: Vec<Picture>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[ts(optional, as = "Option<Vec<PictureLink>>")]
+    pub picture_links: Vec<PictureLink>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub encoder: Option<Encoder>,
+    /// Encodage détecté pour les textes déclarés ISO-8859-1 (ex. "windows-1251", "Shift_JIS")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub legacy_encoding: Option<String>,
+    /// Anomalies relevées dans les tags, qui n'ont pas empêché l'analyse
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[ts(optional, as = "Option<Vec<Warning>>")]
+    pub warnings: Vec<Warning>,
+}
+
+/// Format du flux audio
+#[derive(Serialize, TS, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "metadata.ts", rename = "MetadataAudioProperties")]
+pub struct AudioProperties {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub sample_rate: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub bit_depth: Option<u16>,
+    /// Débit moyen en kbps, sinon celui de la première trame
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub bitrate: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub channels: Option<u8>,
+    #[serde(with = "js_number::option", skip_serializing_if = "Option::is_none")]
+    #[ts(optional, type = "number")]
+    pub duration_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub bitrate_mode: Option<BitrateMode>,
+}
+
+#[derive(Serialize, TS, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+#[ts(export, export_to = "metadata.ts")]
+pub enum BitrateMode {
+    Cbr,
+    Abr,
+    Vbr,
+}
+
+/// Paroles non synchronisées (USLT, LYRICS) ou au format LRC
+#[derive(Serialize, TS, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "metadata.ts", rename = "MetadataLyrics")]
+pub struct Lyrics {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub lyrics: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub synced_lyrics: Option<String>,
+}
+
+#[derive(Serialize, TS, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "metadata.ts", rename = "MetadataPicture")]
+pub struct Picture {
+    #[serde(rename = "type")]
+    pub pic_type: u8,
+    pub type_name: String,
+    pub description: String,
+    /// Type MIME réel, déduit de l'en-tête de l'image
+    pub mime: String,
+    /// Type MIME écrit dans le tag, s'il diffère du type réel
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub declared_mime: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub width: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub height: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub color_depth: Option<u32>,
+    #[ts(type = "number")]
+    pub byte_length: usize,
+    #[serde(with = "serde_bytes", skip_serializing_if = "Option::is_none")]
+    #[ts(optional, type = "Uint8Array")]
+    pub data: Option<Vec<u8>>,
+    /// Emplacement de l'image dans l'entrée, avec l'option `cover_refs`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub data_ref: Option<DataRef>,
+}
+
+/// Octets d'une image désignés dans le tampon analysé (ou le fichier, pour une
+/// analyse par morceaux) plutôt que copiés
+#[derive(Serialize, TS, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "metadata.ts", rename = "MetadataDataRef")]
+pub struct DataRef {
+    #[serde(with = "js_number")]
+    #[ts(type = "number")]
+    pub offset: u64,
+    #[ts(type = "number")]
+    pub length: usize,
+    /// Octets désynchronisés (ID3v2) : retirer le 0x00 qui suit chaque 0xFF après lecture
+    pub unsynchronised: bool,
+}
+
+/// Image référencée par une URL plutôt qu'intégrée au fichier
+#[derive(Serialize, TS, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "metadata.ts", rename = "MetadataPictureLink")]
+pub struct PictureLink {
+    #[serde(rename = "type")]
+    pub pic_type: u8,
+    pub type_name: String,
+    pub description: String,
+    pub url: String,
+}
+
+#[derive(Serialize, TS, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "metadata.ts", rename = "MetadataThumbnail")]
+pub struct Thumbnail {
+    /// Côté maximal demandé
+    pub size: u32,
+    pub width: u32,
+    pub height: u32,
+    /// "png" : fichier PNG encodé, "rgba" : pixels bruts (width × height × 4)
+    pub format: ThumbnailFormat,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub mime: Option<String>,
+    #[serde(with = "serde_bytes")]
+    #[ts(type = "Uint8Array")]
+    pub data: Vec<u8>,
+}
+
+#[derive(Serialize, TS, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+#[ts(export, export_to = "metadata.ts")]
+pub enum ThumbnailFormat {
+    Png,
+    /// Pixels RVBA 8 bits bruts, prêts pour `ImageData`
+    Rgba,
+}
+
+/// Paramètres de la première trame d'un flux MPEG audio
+#[derive(Serialize, TS, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "metadata.ts", rename = "MetadataMpeg")]
+pub struct MpegInfo {
+    /// Libellé lisible, ex. "MPEG-1 Layer III joint stereo"
+    pub format: String,
+    #[ts(type = r#""1" | "2" | "2.5""#)]
+    pub version: String,
+    #[ts(type = "1 | 2 | 3")]
+    pub layer: u8,
+    #[ts(type = r#""stereo" | "joint stereo" | "dual channel" | "mono""#)]
+    pub channel_mode: String,
+    /// Outils de joint stereo actifs (absent hors joint stereo)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub mode_extension: Option<ModeExtension>,
+    #[ts(type = r#""none" | "50/15" | "ccitt-j17""#)]
+    pub emphasis: String,
+    pub copyright: bool,
+    pub original: bool,
+    pub crc_protected: bool,
+}
+
+#[derive(Serialize, TS, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "metadata.ts", rename = "MetadataModeExtension")]
+pub struct ModeExtension {
+    pub ms_stereo: bool,
+    pub intensity_stereo: bool,
+    /// Layer I / II : première sous-bande codée en intensity stereo
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub intensity_bound: Option<u32>,
+}
+
+/// Champs du bloc STREAMINFO FLAC au-delà du format audio
+#[derive(Serialize, TS, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "metadata.ts", rename = "MetadataFlac")]
+pub struct FlacInfo {
+    /// Tailles de bloc, en échantillons
+    pub min_block_size: u16,
+    pub max_block_size: u16,
+    /// Tailles de trame en octets, absentes si l'encodeur ne les connaissait pas
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub min_frame_size: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub max_frame_size: Option<u32>,
+    /// Échantillons par canal
+    #[serde(with = "js_number::option", skip_serializing_if = "Option::is_none")]
+    #[ts(optional, type = "number")]
+    pub total_samples: Option<u64>,
+    /// Signature MD5 de l'audio décodé, en hexadécimal
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub md5: Option<String>,
+    /// Points de la table SEEKTABLE ; `offset` est relatif à la première trame audio
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[ts(optional, as = "Option<Vec<FlacSeekPoint>>")]
+    pub seek_table: Vec<FlacSeekPoint>,
+    /// Identifiants des blocs APPLICATION ("riff", "ATCH", ou "0x…" s'ils ne sont pas imprimables)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[ts(optional, as = "Option<Vec<String>>")]
+    pub application_ids: Vec<String>,
+    /// Octets de remplissage disponibles pour réécrire les tags sans déplacer l'audio
+    #[serde(with = "js_number")]
+    #[ts(type = "number")]
+    pub padding_bytes: u64,
+    /// Position de la première trame audio dans le fichier
+    #[serde(with = "js_number::option", skip_serializing_if = "Option::is_none")]
+    #[ts(optional, type = "number")]
+    pub audio_offset: Option<u64>,
+}
+
+#[derive(Serialize, TS, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "metadata.ts", rename = "MetadataFlacSeekPoint")]
+pub struct FlacSeekPoint {
+    #[serde(with = "js_number")]
+    #[ts(type = "number")]
+    pub sample: u64,
+    #[serde(with = "js_number")]
+    #[ts(type = "number")]
+    pub offset: u64,
+    pub frame_samples: u16,
+    #[serde(with = "js_number::option", skip_serializing_if = "Option::is_none")]
+    #[ts(optional, type = "number")]
+    pub time_ms: Option<u64>,
+}
+
+#[derive(Serialize, TS, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "metadata.ts", rename = "MetadataSeekPoint")]
+pub struct SeekPoint {
+    #[serde(with = "js_number")]
+    #[ts(type = "number")]
+    pub time_ms: u64,
+    /// Position dans le fichier, en octets
+    #[serde(with = "js_number")]
+    #[ts(type = "number")]
+    pub offset: u64,
+}
+
+/// Échantillons à retirer pour enchaîner les pistes sans blanc
+#[derive(Serialize, TS, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "metadata.ts", rename = "MetadataGapless")]
+pub struct Gapless {
+    /// Délai encodeur ou pre-skip Opus, en échantillons
+    pub delay: u32,
+    /// Remplissage en fin de flux, en échantillons
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub padding: Option<u32>,
+    /// Nombre exact d'échantillons (par canal) à conserver
+    #[serde(with = "js_number::option", skip_serializing_if = "Option::is_none")]
+    #[ts(optional, type = "number")]
+    pub valid_samples: Option<u64>,
+    #[ts(type = r#""lame" | "itunsmpb" | "opus""#)]
+    pub source: String,
+}
+
+#[derive(Serialize, TS, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "metadata.ts", rename = "MetadataEncoder")]
+pub struct Encoder {
+    /// Chaîne vendeur FLAC / Vorbis / Opus, ou version LAME ("LAME3.100")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub name: Option<String>,
+    /// Méthode de débit LAME : "cbr", "abr", "vbr-rh", "vbr-mtrh"…
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub vbr_method: Option<String>,
+    /// Préréglage LAME ("V2", "extreme", "ABR 192")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub preset: Option<String>,
+    /// Fréquence de coupure du passe-bas, en Hz
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub lowpass: Option<u32>,
+    /// Qualité de l'en-tête Xing (0 = meilleure … 100)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub quality: Option<u32>,
+    /// Réglages de l'encodeur (TSSE, ENCODER)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub settings: Option<String>,
+    /// TENC, ENCODED-BY
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub encoded_by: Option<String>,
+}
+
+/// Feuille de montage (bloc FLAC CUESHEET ou commentaire CUESHEET)
+#[derive(Serialize, TS, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "metadata.ts", rename = "MetadataCueSheet")]
+pub struct CueSheet {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub catalog: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub performer: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub file: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub genre: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub date: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub comment: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub album_gain: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub album_peak: Option<f32>,
+    #[serde(with = "js_number::option", skip_serializing_if = "Option::is_none")]
+    #[ts(optional, type = "number")]
+    pub lead_in_samples: Option<u64>,
+    pub is_cd: bool,
+    pub tracks: Vec<CueTrack>,
+    /// Fichier .cue externe : encodage détecté du texte
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub encoding: Option<String>,
+}
+
+/// Morceau virtuel d'une image d'album
+#[derive(Serialize, TS, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "metadata.ts", rename = "MetadataCueTrack")]
+pub struct CueTrack {
+    pub number: u8,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub isrc: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub performer: Option<String>,
+    /// Fichier contenant la piste (feuilles multi-fichiers)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub file: Option<String>,
+    pub audio: bool,
+    pub pre_emphasis: bool,
+    /// Drapeaux CUE autres que PRE : "DCP", "4CH", "SCMS"
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[ts(optional, as = "Option<Vec<String>>")]
+    pub flags: Vec<String>,
+    /// ReplayGain de la piste, en dB et en amplitude crête
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub track_gain: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub track_peak: Option<f32>,
+    #[serde(with = "js_number::option", skip_serializing_if = "Option::is_none")]
+    #[ts(optional, type = "number")]
+    pub start_sample: Option<u64>,
+    #[serde(with = "js_number::option", skip_serializing_if = "Option::is_none")]
+    #[ts(optional, type = "number")]
+    pub start_ms: Option<u64>,
+    /// Début du morceau suivant s'il est dans le même fichier, fin du flux pour le dernier
+    #[serde(with = "js_number::option", skip_serializing_if = "Option::is_none")]
+    #[ts(optional, type = "number")]
+    pub end_sample: Option<u64>,
+    #[serde(with = "js_number::option", skip_serializing_if = "Option::is_none")]
+    #[ts(optional, type = "number")]
+    pub end_ms: Option<u64>,
+    #[serde(with = "js_number::option", skip_serializing_if = "Option::is_none")]
+    #[ts(optional, type = "number")]
+    pub pre_gap_ms: Option<u64>,
+    pub indices: Vec<CueIndex>,
+}
+
+#[derive(Serialize, TS, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "metadata.ts", rename = "MetadataCueIndex")]
+pub struct CueIndex {
+    /// 0 : début du pre-gap, 1 : début du morceau
+    pub number: u8,
+    /// Position en échantillons depuis le début du flux
+    #[serde(with = "js_number")]
+    #[ts(type = "number")]
+    pub sample: u64,
+    #[serde(with = "js_number")]
+    #[ts(type = "number")]
+    pub offset_ms: u64,
+}
+
+/// Anomalie relevée dans les tags, qui n'a pas empêché l'analyse
+#[derive(Serialize, TS, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "metadata.ts", rename = "MetadataWarning")]
+pub struct Warning {
+    #[ts(as = "WarningCode")]
+    pub code: String,
+    pub message: String,
+    /// Position dans le fichier, absente pour les paquets Ogg réassemblés
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional, type = "number")]
+    pub offset: Option<usize>,
+}
+
+/// Plage d'octets `[start, end)` demandée par l'analyse incrémentale
+#[derive(Serialize, TS, Debug, Clone, Copy, Default, PartialEq)]
+#[ts(export, export_to = "metadata.ts", rename = "MetadataByteRange")]
+pub struct ByteRange {
+    #[serde(with = "js_number")]
+    #[ts(type = "number")]
+    pub start: u64,
+    #[serde(with = "js_number")]
+    #[ts(type = "number")]
+    pub end: u64,
+}
+
+impl From<Range<u64>> for ByteRange {
+    fn from(range: Range<u64>) -> Self {
+        Self {
+            start: range.start,
+            end: range.end,
+        }
+    }
+}
+
+/// Codes stables de `Warning::code`, décrits pour TypeScript uniquement
+#[derive(TS)]
+#[ts(export, export_to = "metadata.ts", rename = "MetadataWarningCode")]
+#[ts(rename_all = "kebab-case")]
+#[allow(dead_code)]
+enum WarningCode {
+    TruncatedTag,
+    TruncatedBlock,
+    FrameOverrun,
+    InvalidEncoding,
+    CoverTooLarge,
+    Id3v1Mismatch,
+    UnknownPictureType,
+    UnknownPictureFormat,
+    OggCrcMismatch,
+}
+
+/// Entiers 64 bits exposés comme `number` : au-delà de `Number.MAX_SAFE_INTEGER`,
+/// la valeur devient un flottant arrondi au lieu de faire échouer la sérialisation.
+pub(crate) mod js_number {
+    use super::Serializer;
+
+    const MAX_SAFE_INTEGER: u64 = (1 << 53) - 1;
+
+    pub(crate) fn serialize<S: Serializer>(value: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+        if *value <= MAX_SAFE_INTEGER {
+            serializer.serialize_u64(*value)
+        } else {
+            serializer.serialize_f64(*value as f64)
+        }
+    }
+
+    pub(super) mod option {
+        use super::Serializer;
+
+        pub(crate) fn serialize<S: Serializer>(
+            value: &Option<u64>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            match value {
+                Some(value) => super::serialize(value, serializer),
+                None => serializer.serialize_none(),
+            }
+        }
+    }
+}
@@ -0,0 +1,683 @@
+// ---------------------------------------------------------------------------
+// MP4 / M4A : atomes de premier niveau, propriétés audio de `moov` et tags iTunes
+// (moov/udta/meta/ilst)
+// ---------------------------------------------------------------------------
+
+use std::borrow::Cow;
+
+use crate::inspect::{
+    ALREADY_SET, EMPTY_VALUE, INVALID_PICTURE, InspectNode, NOT_AUDIO_TRACK, UNSUPPORTED, Usage,
+};
+use crate::{
+    DataSpan, FRONT_COVER_TYPE, Metadata, ParseOptions, Picture, be_u16, be_u32, decode_utf16_be,
+    parse_itunsmpb, push_value, trim_string,
+};
+
+/// Type de l'atome qui ouvre un fichier MP4, lu aux octets 4 à 8
+pub(crate) const MP4_SIGNATURE: &[u8; 4] = b"ftyp";
+
+/// Atomes de premier niveau parcourus au plus : un MP4 fragmenté en compte des milliers,
+/// les métadonnées sont dans `moov`, avant le premier fragment
+pub(crate) const MAX_TOP_LEVEL_ATOMS: usize = 64;
+
+/// Codecs sans perte, dont la taille d'échantillon déclarée est la profondeur de bits
+const LOSSLESS_CODECS: [&[u8; 4]; 4] = [b"alac", b"fLaC", b"ipcm", b"lpcm"];
+
+/// Indicateurs de type de l'atome `data` (QuickTime File Format, « well-known types »)
+const DATA_UTF8: u32 = 1;
+const DATA_UTF16: u32 = 2;
+const DATA_JPEG: u32 = 13;
+const DATA_PNG: u32 = 14;
+const DATA_BMP: u32 = 27;
+
+/// En-tête d'un atome
+#[derive(Clone, Copy)]
+pub(crate) struct AtomHeader {
+    pub(crate) kind: [u8; 4],
+    /// 8 octets, ou 16 avec une taille sur 64 bits
+    pub(crate) header_len: u64,
+    /// Taille totale, en-tête compris ; `None` quand l'atome s'étend jusqu'à la fin
+    pub(crate) size: Option<u64>,
+}
+
+/// Lire l'en-tête d'atome qui commence `bytes`
+pub(crate) fn atom_header(bytes: &[u8]) -> Option<AtomHeader> {
+    let kind: [u8; 4] = bytes.get(4..8)?.try_into().ok()?;
+    let (header_len, size) = match be_u32(bytes.get(0..4)?) {
+        0 => (8, None),
+        1 => {
+            let size = u64::from_be_bytes(bytes.get(8..16)?.try_into().ok()?);
+            (16, Some(size))
+        }
+        size => (8, Some(size as u64)),
+    };
+    if size.is_some_and(|size| size < header_len) {
+        return None;
+    }
+    Some(AtomHeader {
+        kind,
+        header_len,
+        size,
+    })
+}
+
+/// Atome enfant : position de l'atome et de son contenu, relatives au contenu de `moov`
+struct Atom<'a> {
+    kind: [u8; 4],
+    offset: usize,
+    size: usize,
+    payload: &'a [u8],
+    payload_offset: usize,
+}
+
+/// Atomes successifs de `data`, qui commence à la position `base` ; le parcours
+/// s'arrête au premier atome invalide ou qui dépasse `data`
+struct Atoms<'a> {
+    data: &'a [u8],
+    base: usize,
+    position: usize,
+}
+
+impl<'a> Atoms<'a> {
+    fn new(data: &'a [u8], base: usize) -> Self {
+        Self {
+            data,
+            base,
+            position: 0,
+        }
+    }
+}
+
+impl<'a> Iterator for Atoms<'a> {
+    type Item = Atom<'a>;
+
+    fn next(&mut self) -> Option<Atom<'a>> {
+        let rest = self.data.get(self.position..)?;
+        let header = atom_header(rest)?;
+        let size = match header.size {
+            Some(size) => usize::try_from(size)
+                .ok()
+                .filter(|&size| size <= rest.len())?,
+            None => rest.len(),
+        };
+        let header_len = header.header_len as usize;
+        let atom = Atom {
+            kind: header.kind,
+            offset: self.base + self.position,
+            size,
+            payload: &rest[header_len..size],
+            payload_offset: self.base + self.position + header_len,
+        };
+        self.position += size;
+        Some(atom)
+    }
+}
+
+fn atom_name(kind: &[u8; 4]) -> String {
+    kind.iter().map(|&b| b as char).collect()
+}
+
+/// Parcourir les atomes de premier niveau d'un fichier (ou de son début) et lire `moov`,
+/// même partiellement présent. `mdat` n'est jamais lu : seule sa taille compte.
+pub(crate) fn parse_mp4<'a>(bytes: &'a [u8], options: &ParseOptions, meta: &mut Metadata<'a>) {
+    let file_size = options.file_size.unwrap_or(bytes.len() as u64);
+    let mut offset = 0u64;
+    let mut count = 0;
+    let mut moov: Option<&'a [u8]> = None;
+    let mut moov_offset = 0;
+    let mut media_bytes = Some(0u64);
+
+    while offset < file_size
+        && let Some(rest) = usize::try_from(offset).ok().and_then(|at| bytes.get(at..))
+        && let Some(header) = atom_header(rest)
+    {
+        count += 1;
+        if count > MAX_TOP_LEVEL_ATOMS {
+            media_bytes = None;
+            break;
+        }
+        let end = header
+            .size
+            .map_or(file_size, |size| offset.saturating_add(size));
+        let size = end.min(file_size) - offset;
+        meta.trace(|| {
+            InspectNode::new(
+                "mp4-atom",
+                atom_name(&header.kind),
+                offset as usize,
+                size as usize,
+            )
+        });
+        match &header.kind {
+            b"moov" if moov.is_none() => {
+                // Lu même s'il dépasse l'extrait transmis : les atomes complets restent exploitables
+                let len = usize::try_from(size).unwrap_or(usize::MAX).min(rest.len());
+                moov = rest.get(header.header_len as usize..len);
+                moov_offset = offset as usize + header.header_len as usize;
+            }
+            b"mdat" => {
+                media_bytes =
+                    media_bytes.map(|total| total + size.saturating_sub(header.header_len))
+            }
+            _ => {}
+        }
+        if end > file_size {
+            let message = format!(
+                "{} atom declares {} bytes past the end of the file",
+                atom_name(&header.kind),
+                end - file_size
+            );
+            meta.warn("truncated-block", offset as usize, message);
+            break;
+        }
+        offset = end;
+    }
+
+    if let Some(moov) = moov {
+        parse_moov(moov, moov_offset, media_bytes, options, meta);
+    }
+}
+
+/// Contenu de l'atome `moov` situé à `offset` : durée, format du flux audio et tags iTunes.
+/// `media_bytes` (octets des atomes `mdat`) donne le débit moyen.
+pub(crate) fn parse_moov<'a>(
+    moov: &'a [u8],
+    offset: usize,
+    media_bytes: Option<u64>,
+    options: &ParseOptions,
+    meta: &mut Metadata<'a>,
+) {
+    let previous_base = meta.set_offset_base(meta.offset_base.map(|base| base + offset));
+    let mut movie_duration_ms = None;
+
+    for atom in Atoms::new(moov, 0) {
+        let node = || InspectNode::new("mp4-atom", atom_name(&atom.kind), atom.offset, atom.size);
+        match &atom.kind {
+            b"mvhd" => {
+                movie_duration_ms = media_duration_ms(atom.payload);
+                meta.trace(|| {
+                    node().usage(Usage::mapped_or(
+                        movie_duration_ms.is_some(),
+                        "durationMs",
+                        EMPTY_VALUE,
+                    ))
+                });
+            }
+            b"trak" => {
+                let usage = if meta.audio_info.sample_rate.is_some() {
+                    Usage::Skipped(ALREADY_SET)
+                } else if parse_trak(atom.payload, meta) {
+                    Usage::Mapped("audio")
+                } else {
+                    Usage::Skipped(NOT_AUDIO_TRACK)
+                };
+                meta.trace(|| node().usage(usage));
+            }
+            b"udta" => {
+                meta.trace_open(node);
+                for child in Atoms::new(atom.payload, atom.payload_offset) {
+                    if &child.kind == b"meta" {
+                        parse_meta(&child, options, meta);
+                    }
+                }
+                meta.trace_close();
+            }
+            _ => meta.trace(|| node().usage(Usage::Skipped(UNSUPPORTED))),
+        }
+    }
+
+    let audio_info = &mut meta.audio_info;
+    if audio_info.duration_ms.is_none() {
+        audio_info.duration_ms = movie_duration_ms;
+    }
+    if let Some(duration_ms) = audio_info.duration_ms
+        && let Some(bitrate) = media_bytes.and_then(|bytes| (bytes * 8).checked_div(duration_ms))
+    {
+        audio_info.bitrate = Some(bitrate as u32);
+    }
+    meta.set_offset_base(previous_base);
+}
+
+/// Durée (ms) d'un atome `mvhd` ou `mdhd` : échelle de temps puis durée, sur 32 bits
+/// en version 0 et 64 bits en version 1
+fn media_duration_ms(payload: &[u8]) -> Option<u64> {
+    let (timescale, duration) = match payload.first()? {
+        0 => (
+            be_u32(payload.get(12..16)?),
+            Some(be_u32(payload.get(16..20)?) as u64).filter(|&d| d != u32::MAX as u64),
+        ),
+        1 => (
+            be_u32(payload.get(20..24)?),
+            Some(u64::from_be_bytes(payload.get(24..32)?.try_into().ok()?))
+                .filter(|&d| d != u64::MAX),
+        ),
+        _ => return None,
+    };
+    let duration = duration.filter(|&d| d > 0 && timescale > 0)?;
+    Some((duration as u128 * 1000 / timescale as u128) as u64)
+}
+
+/// Piste `trak` : seule une piste son (`hdlr` « soun ») renseigne le format audio.
+/// Renvoie `true` si c'en était une.
+fn parse_trak(trak: &[u8], meta: &mut Metadata<'_>) -> bool {
+    let Some(mdia) = Atoms::new(trak, 0).find(|atom| &atom.kind == b"mdia") else {
+        return false;
+    };
+    let mut is_sound = false;
+    let mut duration_ms = None;
+    let mut timescale = None;
+    let mut stbl = None;
+    for atom in Atoms::new(mdia.payload, 0) {
+        match &atom.kind {
+            b"hdlr" => is_sound = atom.payload.get(8..12) == Some(b"soun"),
+            b"mdhd" => {
+                duration_ms = media_duration_ms(atom.payload);
+                let at = if atom.payload.first() == Some(&1) {
+                    20
+                } else {
+                    12
+                };
+                timescale = atom.payload.get(at..at + 4).map(be_u32);
+            }
+            b"minf" => {
+                stbl = Atoms::new(atom.payload, 0)
+                    .find(|child| &child.kind == b"stbl")
+                    .map(|child| child.payload);
+            }
+            _ => {}
+        }
+    }
+    if !is_sound {
+        return false;
+    }
+
+    let audio_info = &mut meta.audio_info;
+    audio_info.duration_ms = duration_ms;
+    // Première description d'échantillons de `stsd` (après version, drapeaux et nombre d'entrées)
+    let entry = stbl
+        .and_then(|stbl| Atoms::new(stbl, 0).find(|atom| &atom.kind == b"stsd"))
+        .and_then(|stsd| Atoms::new(stsd.payload.get(8..)?, 0).next());
+    if let Some(entry) = entry
+        && entry.payload.len() >= 28
+    {
+        // Réservé (6), référence (2), version (2), révision (2), fournisseur (4), puis
+        // canaux, taille d'échantillon, identifiant de compression (2), taille de paquet (2)
+        // et taux d'échantillonnage en virgule fixe 16.16
+        let channels = be_u16(&entry.payload[16..18]);
+        let sample_size = be_u16(&entry.payload[18..20]);
+        let sample_rate = be_u32(&entry.payload[24..28]) >> 16;
+        if channels > 0 && channels <= u8::MAX as u16 {
+            audio_info.channels = Some(channels as u8);
+        }
+        if LOSSLESS_CODECS.contains(&&entry.kind) && sample_size > 0 {
+            audio_info.bit_depth = Some(sample_size);
+        }
+        // Au-delà de 65535 Hz, le taux ne tient que dans l'échelle de temps de la piste
+        audio_info.sample_rate = Some(sample_rate)
+            .filter(|&rate| rate > 0)
+            .or(timescale.filter(|&rate| rate > 0));
+    } else {
+        audio_info.sample_rate = timescale.filter(|&rate| rate > 0);
+    }
+    true
+}
+
+/// Atome `meta` : drapeaux (omis par certains encodeurs QuickTime), `hdlr`, puis `ilst`
+fn parse_meta<'a>(atom: &Atom<'a>, options: &ParseOptions, meta: &mut Metadata<'a>) {
+    let skip = if atom.payload.get(4..8) == Some(b"hdlr") {
+        0
+    } else {
+        4
+    };
+    let Some(children) = atom.payload.get(skip..) else {
+        return;
+    };
+    meta.trace_open(|| InspectNode::new("mp4-atom", "meta", atom.offset, atom.size));
+    for child in Atoms::new(children, atom.payload_offset + skip) {
+        if &child.kind == b"ilst" {
+            meta.trace_open(|| InspectNode::new("mp4-atom", "ilst", child.offset, child.size));
+            parse_ilst(child.payload, child.payload_offset, options, meta);
+            meta.trace_close();
+        }
+    }
+    meta.trace_close();
+}
+
+/// Valeur d'un atome `data` : indicateur de type et octets, après les 8 octets
+/// de version, type et langue
+struct DataValue<'a> {
+    data_type: u32,
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+fn data_values<'a>(item: &Atom<'a>) -> Vec<DataValue<'a>> {
+    Atoms::new(item.payload, item.payload_offset)
+        .filter(|atom| &atom.kind == b"data" && atom.payload.len() >= 8)
+        .map(|atom| DataValue {
+            data_type: be_u32(&atom.payload[0..4]) & 0x00FF_FFFF,
+            bytes: &atom.payload[8..],
+            offset: atom.payload_offset + 8,
+        })
+        .collect()
+}
+
+/// Texte UTF-8 ou UTF-16BE, tronqué à `max_text_bytes`
+fn data_text(value: &DataValue, max_text_bytes: usize) -> Option<String> {
+    let bytes = &value.bytes[..value.bytes.len().min(max_text_bytes)];
+    let text = match value.data_type {
+        DATA_UTF8 => trim_string(String::from_utf8_lossy(bytes).into_owned()),
+        DATA_UTF16 => decode_utf16_be(bytes)?,
+        _ => return None,
+    };
+    (!text.is_empty()).then_some(text)
+}
+
+/// Éléments de la liste `ilst` : titre, artiste, album, paroles, pochettes, outil
+/// d'encodage et valeurs libres (`----`) comme iTunSMPB
+fn parse_ilst<'a>(ilst: &'a [u8], base: usize, options: &ParseOptions, meta: &mut Metadata<'a>) {
+    let max_text_bytes = options.max_text_bytes;
+    for item in Atoms::new(ilst, base) {
+        let name = atom_name(&item.kind);
+        let values = data_values(&item);
+        let texts = || -> Vec<String> {
+            values
+                .iter()
+                .filter_map(|value| data_text(value, max_text_bytes))
+                .collect()
+        };
+        let usage = match &item.kind {
+            b"\xA9nam" | b"\xA9ART" | b"\xA9alb" => {
+                let (field, name) = match item.kind[1] {
+                    b'n' => (&mut meta.title, "title"),
+                    b'A' => (&mut meta.artist, "artist"),
+                    _ => (&mut meta.album, "album"),
+                };
+                if field.is_empty() {
+                    for text in texts() {
+                        push_value(field, text);
+                    }
+                    Usage::mapped_or(!field.is_empty(), name, EMPTY_VALUE)
+                } else {
+                    Usage::Skipped(ALREADY_SET)
+                }
+            }
+            b"\xA9lyr" => match texts().into_iter().next() {
+                Some(text) => meta.offer_lyrics(text),
+                None => Usage::Skipped(EMPTY_VALUE),
+            },
+            b"\xA9too" if meta.encoder.name.is_none() => {
+                meta.encoder.name = texts().into_iter().next();
+                Usage::mapped_or(meta.encoder.name.is_some(), "encoder.name", EMPTY_VALUE)
+            }
+            b"\xA9enc" if meta.encoder.encoded_by.is_none() => {
+                meta.encoder.encoded_by = texts().into_iter().next();
+                Usage::mapped_or(
+                    meta.encoder.encoded_by.is_some(),
+                    "encoder.encodedBy",
+                    EMPTY_VALUE,
+                )
+            }
+            b"\xA9too" | b"\xA9enc" => Usage::Skipped(ALREADY_SET),
+            b"covr" => {
+                let mut usage = Usage::Skipped(INVALID_PICTURE);
+                for value in &values {
+                    let mime = match value.data_type {
+                        DATA_JPEG => "image/jpeg",
+                        DATA_PNG => "image/png",
+                        DATA_BMP => "image/bmp",
+                        _ => "",
+                    };
+                    if value.bytes.is_empty() {
+                        continue;
+                    }
+                    let mut picture = Picture::new(
+                        FRONT_COVER_TYPE,
+                        mime.to_string(),
+                        String::new(),
+                        Cow::Borrowed(value.bytes),
+                    );
+                    picture.span = Some(DataSpan::new(value.offset, value.bytes.len()));
+                    usage = meta.add_picture(picture, item.offset, options.max_cover_bytes);
+                }
+                usage
+            }
+            b"----" => parse_freeform(&item, meta),
+            _ => Usage::Skipped(UNSUPPORTED),
+        };
+        meta.trace(|| InspectNode::new("mp4-item", name, item.offset, item.size).usage(usage));
+    }
+}
+
+/// Valeur libre `----` : domaine (`mean`), nom (`name`) puis `data`.
+/// Seul iTunSMPB (délai et remplissage de l'encodeur AAC) est lu.
+fn parse_freeform(item: &Atom, meta: &mut Metadata<'_>) -> Usage {
+    let name = Atoms::new(item.payload, 0)
+        .find(|atom| &atom.kind == b"name")
+        .and_then(|atom| atom.payload.get(4..))
+        .map(String::from_utf8_lossy);
+    if name.as_deref() != Some("iTunSMPB") {
+        return Usage::Skipped(UNSUPPORTED);
+    }
+    if meta.gapless.is_some() {
+        return Usage::Skipped(ALREADY_SET);
+    }
+    meta.gapless = data_values(item)
+        .first()
+        .and_then(|value| data_text(value, usize::MAX))
+        .and_then(|text| parse_itunsmpb(&text));
+    Usage::mapped_or(meta.gapless.is_some(), "gapless", EMPTY_VALUE)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::parse;
+
+    fn atom(kind: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut bytes = ((body.len() + 8) as u32).to_be_bytes().to_vec();
+        bytes.extend_from_slice(kind);
+        bytes.extend_from_slice(body);
+        bytes
+    }
+
+    fn data(data_type: u32, value: &[u8]) -> Vec<u8> {
+        let mut body = data_type.to_be_bytes().to_vec();
+        body.extend_from_slice(&[0; 4]);
+        body.extend_from_slice(value);
+        atom(b"data", &body)
+    }
+
+    /// Atome `udta/meta/ilst` contenant `items`, à passer en `extra` de `m4a`
+    pub(crate) fn udta(items: &[Vec<u8>]) -> Vec<u8> {
+        let mut meta = vec![0; 4];
+        meta.extend(atom(b"hdlr", &[0; 25]));
+        meta.extend(atom(b"ilst", &items.concat()));
+        atom(b"udta", &atom(b"meta", &meta))
+    }
+
+    pub(crate) fn title_item(title: &str) -> Vec<u8> {
+        atom(b"\xA9nam", &data(DATA_UTF8, title.as_bytes()))
+    }
+
+    /// Valeur libre `----` du domaine iTunes
+    fn freeform(name: &str, value: &str) -> Vec<u8> {
+        let mut mean = vec![0; 4];
+        mean.extend_from_slice(b"com.apple.iTunes");
+        let mut name_body = vec![0; 4];
+        name_body.extend_from_slice(name.as_bytes());
+        let body = [
+            atom(b"mean", &mean),
+            atom(b"name", &name_body),
+            data(DATA_UTF8, value.as_bytes()),
+        ]
+        .concat();
+        atom(b"----", &body)
+    }
+
+    /// Piste AAC stéréo à 44 100 Hz de 2 s
+    fn audio_track() -> Vec<u8> {
+        let mut mdhd = vec![0; 12];
+        mdhd.extend_from_slice(&44_100u32.to_be_bytes());
+        mdhd.extend_from_slice(&88_200u32.to_be_bytes());
+        let mut hdlr = vec![0; 8];
+        hdlr.extend_from_slice(b"soun");
+        let mut mp4a = vec![0; 16];
+        mp4a.extend_from_slice(&2u16.to_be_bytes());
+        mp4a.extend_from_slice(&16u16.to_be_bytes());
+        mp4a.extend_from_slice(&[0; 4]);
+        mp4a.extend_from_slice(&(44_100u32 << 16).to_be_bytes());
+        let mut stsd = vec![0, 0, 0, 0, 0, 0, 0, 1];
+        stsd.extend(atom(b"mp4a", &mp4a));
+        let stbl = atom(b"stbl", &atom(b"stsd", &stsd));
+        let mdia = [
+            atom(b"mdhd", &mdhd),
+            atom(b"hdlr", &hdlr),
+            atom(b"minf", &stbl),
+        ]
+        .concat();
+        atom(b"trak", &atom(b"mdia", &mdia))
+    }
+
+    /// Fichier M4A minimal : `moov` (piste audio puis `extra`) avant ou après
+    /// un `mdat` de `media_len` octets
+    pub(crate) fn m4a(extra: &[u8], moov_last: bool, media_len: usize) -> Vec<u8> {
+        let moov = atom(b"moov", &[audio_track(), extra.to_vec()].concat());
+        let mut bytes = atom(b"ftyp", b"M4A \0\0\0\0");
+        let mdat = atom(b"mdat", &vec![0x21; media_len]);
+        if moov_last {
+            bytes.extend(mdat);
+            bytes.extend(moov);
+        } else {
+            bytes.extend(moov);
+            bytes.extend(mdat);
+        }
+        bytes
+    }
+
+    #[test]
+    fn audio_properties_come_from_the_sound_track() {
+        let bytes = m4a(&[], true, 32_000);
+        let metadata = parse(&bytes, &ParseOptions::default()).unwrap();
+        let audio = &metadata.audio;
+        assert_eq!(audio.sample_rate, Some(44_100));
+        assert_eq!(audio.channels, Some(2));
+        assert_eq!(audio.bit_depth, None);
+        assert_eq!(audio.duration_ms, Some(2000));
+        assert_eq!(audio.bitrate, Some(128));
+    }
+
+    #[test]
+    fn moov_position_does_not_change_the_result() {
+        let options = ParseOptions::default();
+        let first = parse(&m4a(&[], false, 1000), &options).unwrap();
+        let last = parse(&m4a(&[], true, 1000), &options).unwrap();
+        assert_eq!(first, last);
+    }
+
+    #[test]
+    fn truncated_atom_is_reported() {
+        let mut bytes = m4a(&[], false, 1000);
+        bytes.truncate(bytes.len() - 100);
+        let metadata = parse(&bytes, &ParseOptions::default()).unwrap();
+        assert_eq!(metadata.audio.sample_rate, Some(44_100));
+        assert_eq!(metadata.warnings[0].code, "truncated-block");
+    }
+
+    #[test]
+    fn ilst_items_give_tags_lyrics_and_encoder() {
+        let utf16: Vec<u8> = "Álbum".encode_utf16().flat_map(u16::to_be_bytes).collect();
+        let extra = udta(&[
+            // Valeurs multiples d'un même élément, sans doublon
+            atom(
+                b"\xA9nam",
+                &[data(DATA_UTF8, b"Title "), data(DATA_UTF8, b"Title")].concat(),
+            ),
+            atom(
+                b"\xA9ART",
+                &[data(DATA_UTF8, b"One"), data(DATA_UTF8, b"Two")].concat(),
+            ),
+            atom(b"\xA9alb", &data(DATA_UTF16, &utf16)),
+            atom(b"\xA9lyr", &data(DATA_UTF8, b"[00:01.00]Line")),
+            atom(b"\xA9too", &data(DATA_UTF8, b"Lavf60.16.100")),
+            atom(b"\xA9enc", &data(DATA_UTF8, b"iTunes")),
+            atom(b"\xA9day", &data(DATA_UTF8, b"2024")),
+        ]);
+        let metadata = parse(&m4a(&extra, false, 1000), &ParseOptions::default()).unwrap();
+        assert_eq!(metadata.titles, ["Title"]);
+        assert_eq!(metadata.artists, ["One", "Two"]);
+        assert_eq!(metadata.album.as_deref(), Some("Álbum"));
+        assert_eq!(
+            metadata.lyrics.synced_lyrics.as_deref(),
+            Some("[00:01.00]Line")
+        );
+        let encoder = metadata.encoder.unwrap();
+        assert_eq!(encoder.name.as_deref(), Some("Lavf60.16.100"));
+        assert_eq!(encoder.encoded_by.as_deref(), Some("iTunes"));
+    }
+
+    #[test]
+    fn quicktime_meta_without_flags_is_read() {
+        let mut meta = atom(b"hdlr", &[0; 25]);
+        meta.extend(atom(b"ilst", &title_item("Title")));
+        let extra = atom(b"udta", &atom(b"meta", &meta));
+        let metadata = parse(&m4a(&extra, false, 1000), &ParseOptions::default()).unwrap();
+        assert_eq!(metadata.title.as_deref(), Some("Title"));
+    }
+
+    #[test]
+    fn covr_values_are_pictures() {
+        let jpeg = [0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x10];
+        let covr = [data(DATA_JPEG, &jpeg), data(DATA_PNG, b"\x89PNG\r\n\x1a\n")].concat();
+        let extra = udta(&[atom(b"covr", &covr)]);
+        let bytes = m4a(&extra, true, 1000);
+        let options = ParseOptions {
+            include_pictures: true,
+            cover_refs: true,
+            ..ParseOptions::default()
+        };
+        let metadata = parse(&bytes, &options).unwrap();
+        let mimes: Vec<_> = metadata.pictures.iter().map(|p| p.mime.as_str()).collect();
+        assert_eq!(mimes, ["image/jpeg", "image/png"]);
+        assert_eq!(metadata.cover_mime.as_deref(), Some("image/jpeg"));
+        // La référence désigne les octets de l'image dans le fichier, `moov` en fin de fichier
+        let cover_ref = metadata.cover_ref.unwrap();
+        let start = cover_ref.offset as usize;
+        assert_eq!(bytes[start..start + cover_ref.length], jpeg);
+    }
+
+    #[test]
+    fn itunsmpb_freeform_gives_gapless_info() {
+        let smpb = " 00000000 00000840 000001CA 0000000000015888";
+        let extra = udta(&[
+            freeform("iTunNORM", " 00000000"),
+            freeform("iTunSMPB", smpb),
+        ]);
+        let metadata = parse(&m4a(&extra, false, 1000), &ParseOptions::default()).unwrap();
+        let gapless = metadata.gapless.unwrap();
+        assert_eq!((gapless.delay, gapless.padding), (0x840, Some(0x1CA)));
+        assert_eq!(gapless.valid_samples, Some(0x15888));
+        assert_eq!(gapless.source, "itunsmpb");
+    }
+
+    #[test]
+    fn tags_after_mdat_match_tags_before_it() {
+        let extra = udta(&[title_item("Title")]);
+        let options = ParseOptions::default();
+        let first = parse(&m4a(&extra, false, 1000), &options).unwrap();
+        let last = parse(&m4a(&extra, true, 1000), &options).unwrap();
+        assert_eq!(last.title.as_deref(), Some("Title"));
+        assert_eq!(first, last);
+    }
+
+    #[test]
+    fn atom_header_sizes() {
+        let header =
+            atom_header(&[0, 0, 0, 1, b'm', b'd', b'a', b't', 0, 0, 0, 1, 0, 0, 0, 0]).unwrap();
+        assert_eq!((header.header_len, header.size), (16, Some(1 << 32)));
+        let header = atom_header(&[0, 0, 0, 0, b'm', b'd', b'a', b't']).unwrap();
+        assert_eq!((header.header_len, header.size), (8, None));
+        assert!(atom_header(&[0, 0, 0, 4, b'f', b'r', b'e', b'e']).is_none());
+        assert!(atom_header(&[0, 0, 0, 1, b'm', b'd', b'a', b't']).is_none());
+    }
+}
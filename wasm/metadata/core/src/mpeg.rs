@@ -0,0 +1,320 @@
+// ---------------------------------------------------------------------------
+// Trames audio MPEG : décodage des en-têtes et recherche de synchronisation
+// ---------------------------------------------------------------------------
+
+use crate::{MPEG_BITRATES, MPEG_SAMPLE_RATES};
+
+/// Trames suivantes qui doivent confirmer une synchronisation candidate
+const SYNC_CONFIRMATIONS: usize = 2;
+/// Distance maximale entre deux en-têtes en format libre (640 kbps à 8 kHz, Layer III MPEG2.5)
+const MAX_FREE_FORMAT_FRAME_LEN: usize = 5760;
+
+/// En-tête d'une trame audio MPEG
+#[derive(Clone, Copy)]
+pub(crate) struct MpegFrame {
+    pub(crate) offset: usize,
+    /// 0 = MPEG2.5, 2 = MPEG2, 3 = MPEG1
+    pub(crate) version_idx: usize,
+    /// 1 = Layer III, 2 = Layer II, 3 = Layer I
+    pub(crate) layer_idx: usize,
+    pub(crate) sample_rate: u32,
+    /// Débit de la trame ; déduit de l'écart entre trames en format libre
+    pub(crate) bitrate_kbps: u32,
+    /// 0 = stéréo, 1 = joint stereo, 2 = double canal, 3 = mono
+    pub(crate) channel_mode: u8,
+    /// Joint stereo : bandes en intensity stereo (Layer I/II) ou outils actifs (Layer III)
+    pub(crate) mode_extension: u8,
+    /// 0 = aucune, 1 = 50/15 µs, 3 = CCITT J.17
+    pub(crate) emphasis: u8,
+    pub(crate) copyright: bool,
+    /// Original (par opposition à une copie)
+    pub(crate) original: bool,
+    /// Un CRC de 16 bits suit l'en-tête
+    pub(crate) protected: bool,
+    pub(crate) padding: bool,
+    /// Format libre : index de débit 0, débit fixé par l'encodeur hors table
+    pub(crate) free_format: bool,
+    /// Longueur totale de la trame (padding compris) ; 0 tant qu'un format libre n'est pas résolu
+    pub(crate) frame_len: usize,
+}
+
+impl MpegFrame {
+    /// Position des données après l'en-tête et le CRC éventuel, relative au début de la trame
+    pub(crate) fn data_offset(&self) -> usize {
+        if self.protected { 6 } else { 4 }
+    }
+
+    /// Nombre d'échantillons par canal et par trame
+    pub(crate) fn samples_per_frame(&self) -> u32 {
+        match (self.layer_idx, self.version_idx) {
+            (3, _) => 384,
+            (1, 0 | 2) => 576,
+            _ => 1152,
+        }
+    }
+
+    /// Taille des informations annexes Layer III
+    pub(crate) fn side_info_len(&self) -> usize {
+        match (self.version_idx == 3, self.channel_mode == 3) {
+            (true, true) => 17,
+            (true, false) => 32,
+            (false, true) => 9,
+            (false, false) => 17,
+        }
+    }
+
+    pub(crate) fn version_name(&self) -> &'static str {
+        match self.version_idx {
+            3 => "1",
+            2 => "2",
+            _ => "2.5",
+        }
+    }
+
+    /// Numéro de couche : 1, 2 ou 3
+    pub(crate) fn layer(&self) -> u8 {
+        4 - self.layer_idx as u8
+    }
+
+    pub(crate) fn emphasis_name(&self) -> &'static str {
+        match self.emphasis {
+            1 => "50/15",
+            3 => "ccitt-j17",
+            _ => "none",
+        }
+    }
+
+    /// Libellé lisible, par exemple « MPEG-1 Layer III joint stereo »
+    pub(crate) fn format_name(&self) -> String {
+        let layer = ["I", "II", "III"][self.layer() as usize - 1];
+        format!(
+            "MPEG-{} Layer {layer} {}",
+            self.version_name(),
+            self.channel_mode_name()
+        )
+    }
+
+    pub(crate) fn channel_count(&self) -> usize {
+        if self.channel_mode == 3 { 1 } else { 2 }
+    }
+
+    pub(crate) fn channel_mode_name(&self) -> &'static str {
+        match self.channel_mode {
+            0 => "stereo",
+            1 => "joint stereo",
+            2 => "dual channel",
+            _ => "mono",
+        }
+    }
+
+    /// Taille d'un « slot » : 4 octets en Layer I, 1 octet sinon
+    fn slot_size(&self) -> usize {
+        if self.layer_idx == 3 { 4 } else { 1 }
+    }
+
+    /// Octets de données par trame et par bit/s de débit, multipliés par le taux d'échantillonnage
+    fn slots_coefficient(&self) -> u32 {
+        match (self.layer_idx, self.version_idx == 3) {
+            (3, _) => 12,
+            (1, false) => 72,
+            _ => 144,
+        }
+    }
+
+    pub(crate) fn padding_len(&self) -> usize {
+        if self.padding { self.slot_size() } else { 0 }
+    }
+
+    /// Deux trames d'un même flux partagent version, couche et taux d'échantillonnage
+    pub(crate) fn is_compatible(&self, other: &MpegFrame) -> bool {
+        self.version_idx == other.version_idx
+            && self.layer_idx == other.layer_idx
+            && self.sample_rate == other.sample_rate
+            && self.free_format == other.free_format
+    }
+}
+
+/// Décoder l'en-tête de trame situé à `offset`, sans le confronter aux trames suivantes.
+pub(crate) fn parse_frame_header(bytes: &[u8], offset: usize) -> Option<MpegFrame> {
+    let header = bytes.get(offset..offset + 4)?;
+    // Mot de synchronisation : 11 bits à 1
+    if header[0] != 0xFF || header[1] & 0xE0 != 0xE0 {
+        return None;
+    }
+    let b1 = header[1];
+    let b2 = header[2];
+    let b3 = header[3];
+
+    let version_idx = ((b1 >> 3) & 0x03) as usize;
+    let layer_idx = ((b1 >> 1) & 0x03) as usize;
+    let bitrate_idx = ((b2 >> 4) & 0x0F) as usize;
+    let sr_idx = ((b2 >> 2) & 0x03) as usize;
+    let emphasis = b3 & 0x03;
+
+    // Valeurs réservées : version 1, couche 0, taux 3, débit 15, accentuation 2
+    if version_idx == 1 || layer_idx == 0 || sr_idx == 3 || bitrate_idx == 15 || emphasis == 2 {
+        return None;
+    }
+    let sample_rate = MPEG_SAMPLE_RATES[version_idx][sr_idx];
+
+    // Déterminer l'index de la table de débit binaire
+    let bitrate_table_idx = match (version_idx == 3, layer_idx) {
+        (true, 3) => 0,  // V1, Layer I
+        (true, 2) => 1,  // V1, Layer II
+        (true, _) => 2,  // V1, Layer III
+        (false, 3) => 3, // V2/V2.5, Layer I
+        (false, _) => 4, // V2/V2.5, Layer II & III
+    };
+    let bitrate_kbps = MPEG_BITRATES[bitrate_table_idx][bitrate_idx] as u32;
+
+    let mut frame = MpegFrame {
+        offset,
+        version_idx,
+        layer_idx,
+        sample_rate,
+        bitrate_kbps,
+        channel_mode: (b3 >> 6) & 0x03,
+        mode_extension: (b3 >> 4) & 0x03,
+        emphasis,
+        copyright: b3 & 0x08 != 0,
+        original: b3 & 0x04 != 0,
+        protected: b1 & 0x01 == 0,
+        padding: (b2 >> 1) & 0x01 != 0,
+        free_format: bitrate_idx == 0,
+        frame_len: 0,
+    };
+    if !frame.free_format {
+        let slots = frame.slots_coefficient() * bitrate_kbps * 1000 / sample_rate;
+        frame.frame_len = slots as usize * frame.slot_size() + frame.padding_len();
+    }
+    Some(frame)
+}
+
+/// Rechercher, dans les `scan_bytes` octets suivant `start`, la première trame dont
+/// la longueur mène à des en-têtes compatibles. Une candidate impossible à confirmer
+/// faute de données (tampon tronqué) n'est retenue qu'en dernier recours.
+pub(crate) fn find_mpeg_frame(bytes: &[u8], start: usize, scan_bytes: usize) -> Option<MpegFrame> {
+    let scan_limit = start
+        .saturating_add(scan_bytes)
+        .min(bytes.len().saturating_sub(3));
+    let mut unconfirmed = None;
+
+    for offset in start..scan_limit {
+        let Some(mut frame) = parse_frame_header(bytes, offset) else {
+            continue;
+        };
+        if frame.free_format && !resolve_free_format(bytes, &mut frame) {
+            continue;
+        }
+        match confirm_sync(bytes, &frame) {
+            Some(true) => return Some(frame),
+            None if unconfirmed.is_none() => unconfirmed = Some(frame),
+            _ => {}
+        }
+    }
+    unconfirmed
+}
+
+/// `Some(true)` si les trames suivantes concordent, `Some(false)` si l'une d'elles est invalide,
+/// `None` si le tampon s'arrête avant la première trame suivante.
+fn confirm_sync(bytes: &[u8], frame: &MpegFrame) -> Option<bool> {
+    let mut current = *frame;
+    for confirmed in 0..SYNC_CONFIRMATIONS {
+        let next_offset = current.offset + current.frame_len;
+        if next_offset + 4 > bytes.len() {
+            return (confirmed > 0).then_some(true);
+        }
+        let Some(next) = next_frame(bytes, frame, next_offset) else {
+            return Some(false);
+        };
+        current = next;
+    }
+    Some(true)
+}
+
+/// Lire la trame suivante d'un flux dont `reference` est une trame déjà validée.
+/// En format libre, sa longueur reprend celle de la référence, au padding près.
+pub(crate) fn next_frame(bytes: &[u8], reference: &MpegFrame, offset: usize) -> Option<MpegFrame> {
+    let mut next = parse_frame_header(bytes, offset)?;
+    if !next.is_compatible(reference) {
+        return None;
+    }
+    if next.free_format {
+        next.bitrate_kbps = reference.bitrate_kbps;
+        next.frame_len = reference.frame_len - reference.padding_len() + next.padding_len();
+    }
+    Some(next)
+}
+
+/// Format libre : la longueur de trame est l'écart jusqu'au prochain en-tête compatible,
+/// le débit s'en déduit.
+fn resolve_free_format(bytes: &[u8], frame: &mut MpegFrame) -> bool {
+    let min_offset = frame.offset + frame.data_offset() + 1;
+    let max_offset = (frame.offset + MAX_FREE_FORMAT_FRAME_LEN).min(bytes.len().saturating_sub(3));
+    let Some(next_offset) = (min_offset..max_offset).find(|&offset| {
+        parse_frame_header(bytes, offset).is_some_and(|next| next.is_compatible(frame))
+    }) else {
+        return false;
+    };
+
+    frame.frame_len = next_offset - frame.offset;
+    let slots = ((frame.frame_len - frame.padding_len()) / frame.slot_size()) as u64;
+    let divisor = frame.slots_coefficient() as u64 * 1000;
+    frame.bitrate_kbps = ((slots * frame.sample_rate as u64 + divisor / 2) / divisor) as u32;
+    frame.bitrate_kbps > 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// MPEG1 Layer III, 44,1 kHz, sans CRC ni padding ; débit 128 kbps (417 octets)
+    const HEADER_128: [u8; 4] = [0xFF, 0xFB, 0x90, 0x00];
+    /// Même trame en format libre (index de débit 0)
+    const HEADER_FREE: [u8; 4] = [0xFF, 0xFB, 0x00, 0x00];
+
+    fn frames(header: [u8; 4], frame_len: usize, count: usize) -> Vec<u8> {
+        let mut frame = header.to_vec();
+        frame.resize(frame_len, 0);
+        frame.repeat(count)
+    }
+
+    #[test]
+    fn decodes_frame_length() {
+        let frame = parse_frame_header(&HEADER_128, 0).unwrap();
+        assert_eq!((frame.bitrate_kbps, frame.sample_rate), (128, 44100));
+        assert_eq!(frame.frame_len, 417);
+        assert_eq!(frame.samples_per_frame(), 1152);
+    }
+
+    #[test]
+    fn false_sync_is_not_confirmed() {
+        // Une fausse synchronisation dans le tag précède le flux réel
+        let mut bytes = vec![0; 1000];
+        bytes[10..14].copy_from_slice(&HEADER_128);
+        bytes.extend(frames(HEADER_128, 417, 3));
+        let frame = find_mpeg_frame(&bytes, 0, 4096).unwrap();
+        assert_eq!(frame.offset, 1000);
+    }
+
+    #[test]
+    fn unconfirmed_frame_is_a_last_resort() {
+        let mut bytes = HEADER_128.to_vec();
+        bytes.resize(100, 0);
+        assert_eq!(find_mpeg_frame(&bytes, 0, 4096).map(|f| f.offset), Some(0));
+        assert!(find_mpeg_frame(&bytes, 1, 4096).is_none());
+    }
+
+    #[test]
+    fn free_format_length_comes_from_the_next_header() {
+        let mut bytes = vec![0; 7];
+        bytes.extend(frames(HEADER_FREE, 600, 4));
+        let frame = find_mpeg_frame(&bytes, 0, 4096).unwrap();
+        assert!(frame.free_format);
+        assert_eq!((frame.offset, frame.frame_len), (7, 600));
+        // 600 octets × 44 100 / 144 000 ≈ 184 kbps
+        assert_eq!(frame.bitrate_kbps, 184);
+        let next = next_frame(&bytes, &frame, frame.offset + frame.frame_len).unwrap();
+        assert_eq!(next.frame_len, 600);
+    }
+}
@@ -0,0 +1,428 @@
+// ---------------------------------------------------------------------------
+// Parcours complet d'un flux MPEG : contrôle qualité trame par trame
+// ---------------------------------------------------------------------------
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+use ts_rs::TS;
+
+use crate::be_u16;
+use crate::model::js_number;
+use crate::mpeg::{MpegFrame, find_mpeg_frame, next_frame};
+use crate::xing::{parse_vbri_header, parse_xing_header};
+
+/// Polynôme du CRC-16 des trames MPEG (x¹⁶ + x¹⁵ + x² + 1)
+const CRC_POLYNOMIAL: u16 = 0x8005;
+/// Nombre de sous-bandes d'une trame Layer I / Layer II
+const SUBBANDS: usize = 32;
+
+/// Bits d'allocation par sous-bande des tables Layer II (ISO 11172-3 B.2a à B.2d, ISO 13818-3 B.1)
+const LAYER2_ALLOCATION_A: [u8; 27] = [
+    4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 2, 2, 2, 2,
+];
+const LAYER2_ALLOCATION_B: [u8; 30] = [
+    4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 2, 2, 2, 2, 2, 2, 2,
+];
+const LAYER2_ALLOCATION_C: [u8; 8] = [4, 4, 3, 3, 3, 3, 3, 3];
+const LAYER2_ALLOCATION_D: [u8; 12] = [4, 4, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3];
+const LAYER2_ALLOCATION_LSF: [u8; 30] = [
+    4, 4, 4, 4, 3, 3, 3, 3, 3, 3, 3, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2,
+];
+
+/// Zone du flux qui n'a pas pu être lue comme une suite de trames
+#[derive(Serialize, TS, Debug, Clone, PartialEq)]
+#[ts(export, export_to = "metadata.ts", rename = "MpegSyncError")]
+pub struct SyncError {
+    #[ts(type = "number")]
+    pub offset: usize,
+    #[ts(type = "number")]
+    pub length: usize,
+    /// « junk » avant la première trame ou après la dernière, « sync-lost » quand
+    /// une trame n'est pas suivie d'un en-tête valide, « truncated » pour une trame incomplète
+    #[ts(type = r#""junk" | "sync-lost" | "truncated""#)]
+    pub kind: &'static str,
+}
+
+#[derive(Serialize, TS, Debug, Clone, PartialEq)]
+#[ts(export, export_to = "metadata.ts", rename = "MpegChannelModeChange")]
+pub struct ChannelModeChange {
+    /// Position de la première trame dans le nouveau mode
+    #[ts(type = "number")]
+    pub offset: usize,
+    #[ts(type = "string")]
+    pub from: &'static str,
+    #[ts(type = "string")]
+    pub to: &'static str,
+}
+
+/// Nombre de trames à un débit donné (kbps)
+#[derive(Serialize, TS, Debug, Clone, PartialEq)]
+#[ts(export, export_to = "metadata.ts", rename = "MpegBitrateCount")]
+pub struct BitrateCount {
+    pub bitrate: u32,
+    pub frames: u32,
+}
+
+/// Rapport de contrôle qualité produit par le parcours de toutes les trames d'un MP3
+#[derive(Serialize, TS, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "metadata.ts", rename = "MpegFrameScan")]
+pub struct FrameScan {
+    /// Début et fin du flux audio, tags exclus
+    #[ts(type = "number")]
+    pub audio_start: usize,
+    #[ts(type = "number")]
+    pub audio_end: usize,
+    /// Taux d'échantillonnage et mode de canaux de la première trame (ou trame d'information)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub sample_rate: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional, type = "string")]
+    pub channel_mode: Option<&'static str>,
+    /// Trame Xing / Info / VBRI, qui ne contient pas d'audio et n'est pas décomptée
+    #[serde(rename = "infoFrameOffset", skip_serializing_if = "Option::is_none")]
+    #[ts(optional, type = "number")]
+    pub info_frame: Option<usize>,
+    pub frames: u32,
+    /// Échantillons par canal
+    #[serde(with = "js_number")]
+    #[ts(type = "number")]
+    pub samples: u64,
+    #[serde(with = "js_number")]
+    #[ts(type = "number")]
+    pub duration_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub min_bitrate: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub max_bitrate: Option<u32>,
+    /// Débit moyen en kbps, d'après la taille réelle des trames
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub avg_bitrate: Option<u32>,
+    /// Nombre de trames par débit, par débit croissant
+    pub bitrate_histogram: Vec<BitrateCount>,
+    pub channel_mode_changes: Vec<ChannelModeChange>,
+    /// « none », « partial » ou « all » selon la part de trames protégées par un CRC
+    #[ts(type = r#""none" | "partial" | "all""#)]
+    pub crc_protection: &'static str,
+    #[serde(rename = "crcProtectedFrames")]
+    pub protected_frames: u32,
+    /// Positions des trames protégées dont le CRC ne correspond pas au contenu
+    #[ts(type = "Array<number>")]
+    pub crc_errors: Vec<usize>,
+    pub sync_errors: Vec<SyncError>,
+}
+
+/// Parcourir toutes les trames entre `start` et `end`, en se resynchronisant
+/// après chaque zone illisible.
+pub(crate) fn scan_frames(bytes: &[u8], start: usize, end: usize) -> FrameScan {
+    let bytes = &bytes[..end.min(bytes.len())];
+    let mut scan = FrameScan {
+        audio_start: start,
+        audio_end: bytes.len(),
+        sample_rate: None,
+        channel_mode: None,
+        info_frame: None,
+        frames: 0,
+        samples: 0,
+        duration_ms: 0,
+        min_bitrate: None,
+        max_bitrate: None,
+        avg_bitrate: None,
+        bitrate_histogram: Vec::new(),
+        channel_mode_changes: Vec::new(),
+        crc_protection: "none",
+        protected_frames: 0,
+        crc_errors: Vec::new(),
+        sync_errors: Vec::new(),
+    };
+    // Le taux d'échantillonnage peut changer après une resynchronisation
+    let mut duration_s = 0.0f64;
+    let mut audio_bytes = 0u64;
+    let mut histogram: BTreeMap<u32, u32> = BTreeMap::new();
+    let mut reference: Option<MpegFrame> = None;
+    let mut previous: Option<MpegFrame> = None;
+    let mut offset = start;
+
+    while offset + 4 <= bytes.len() {
+        let frame = match reference.and_then(|r| next_frame(bytes, &r, offset)) {
+            Some(frame) => frame,
+            None => {
+                let kind = if previous.is_some() {
+                    "sync-lost"
+                } else {
+                    "junk"
+                };
+                let Some(found) = find_mpeg_frame(bytes, offset, bytes.len() - offset) else {
+                    break;
+                };
+                if found.offset > offset {
+                    scan.sync_errors.push(SyncError {
+                        offset,
+                        length: found.offset - offset,
+                        kind,
+                    });
+                }
+                reference = Some(found);
+                found
+            }
+        };
+        let frame_end = frame.offset + frame.frame_len;
+        if frame_end > bytes.len() {
+            scan.sync_errors.push(SyncError {
+                offset: frame.offset,
+                length: bytes.len() - frame.offset,
+                kind: "truncated",
+            });
+            offset = bytes.len();
+            break;
+        }
+
+        if scan.sample_rate.is_none() {
+            scan.sample_rate = Some(frame.sample_rate);
+            scan.channel_mode = Some(frame.channel_mode_name());
+            if is_info_frame(&bytes[frame.offset..frame_end], &frame) {
+                scan.info_frame = Some(frame.offset);
+                offset = frame_end;
+                continue;
+            }
+        }
+
+        scan.frames += 1;
+        scan.samples += frame.samples_per_frame() as u64;
+        audio_bytes += frame.frame_len as u64;
+        duration_s += frame.samples_per_frame() as f64 / frame.sample_rate as f64;
+        *histogram.entry(frame.bitrate_kbps).or_insert(0) += 1;
+        if let Some(prev) = previous
+            && prev.channel_mode != frame.channel_mode
+        {
+            scan.channel_mode_changes.push(ChannelModeChange {
+                offset: frame.offset,
+                from: prev.channel_mode_name(),
+                to: frame.channel_mode_name(),
+            });
+        }
+        if frame.protected {
+            scan.protected_frames += 1;
+            if verify_crc(&bytes[frame.offset..frame_end], &frame) == Some(false) {
+                scan.crc_errors.push(frame.offset);
+            }
+        }
+        previous = Some(frame);
+        offset = frame_end;
+    }
+
+    if offset < bytes.len() {
+        scan.sync_errors.push(SyncError {
+            offset,
+            length: bytes.len() - offset,
+            kind: "junk",
+        });
+    }
+    scan.duration_ms = (duration_s * 1000.0).round() as u64;
+    scan.min_bitrate = histogram.keys().next().copied();
+    scan.max_bitrate = histogram.keys().next_back().copied();
+    scan.avg_bitrate = (audio_bytes * 8)
+        .checked_div(scan.duration_ms)
+        .map(|bitrate| bitrate as u32);
+    scan.bitrate_histogram = histogram
+        .into_iter()
+        .map(|(bitrate, frames)| BitrateCount { bitrate, frames })
+        .collect();
+    scan.crc_protection = match scan.protected_frames {
+        0 => "none",
+        n if n == scan.frames => "all",
+        _ => "partial",
+    };
+    scan
+}
+
+/// Trame d'en-tête Xing / Info ou VBRI placée en tête du flux par l'encodeur
+fn is_info_frame(frame_bytes: &[u8], frame: &MpegFrame) -> bool {
+    frame.layer_idx == 1
+        && (parse_xing_header(frame_bytes, frame.data_offset(), frame.side_info_len()).is_some()
+            || parse_vbri_header(frame_bytes, frame.data_offset()).is_some())
+}
+
+/// Vérifier le CRC-16 d'une trame protégée. Il couvre les deux derniers octets
+/// de l'en-tête puis les informations annexes (Layer III) ou l'allocation de bits
+/// (Layer I / II). `None` si la trame est trop courte pour être vérifiée.
+fn verify_crc(frame_bytes: &[u8], frame: &MpegFrame) -> Option<bool> {
+    let stored = be_u16(frame_bytes.get(4..6)?);
+    let data = frame_bytes.get(6..)?;
+    let protected_bits = match frame.layer_idx {
+        1 => frame.side_info_len() * 8,
+        2 => layer2_protected_bits(data, frame)?,
+        _ => layer1_protected_bits(frame),
+    };
+
+    let mut crc = 0xFFFF;
+    crc_update(&mut crc, frame_bytes[2], 8);
+    crc_update(&mut crc, frame_bytes[3], 8);
+    let full_bytes = protected_bits / 8;
+    for &byte in data.get(..full_bytes)? {
+        crc_update(&mut crc, byte, 8);
+    }
+    if protected_bits % 8 > 0 {
+        crc_update(&mut crc, *data.get(full_bytes)?, protected_bits % 8);
+    }
+    Some(crc == stored)
+}
+
+fn crc_update(crc: &mut u16, byte: u8, bits: usize) {
+    for i in 0..bits {
+        let bit = (byte >> (7 - i)) & 0x01;
+        let top = (*crc >> 15) as u8;
+        *crc <<= 1;
+        if top ^ bit != 0 {
+            *crc ^= CRC_POLYNOMIAL;
+        }
+    }
+}
+
+/// Première sous-bande codée en intensity stereo (joint stereo), `limit` sinon
+fn joint_stereo_bound(frame: &MpegFrame, limit: usize) -> usize {
+    if frame.channel_mode == 1 {
+        ((frame.mode_extension as usize + 1) * 4).min(limit)
+    } else {
+        limit
+    }
+}
+
+/// Layer I : 4 bits d'allocation par sous-bande et par canal, partagés au-delà de la borne
+fn layer1_protected_bits(frame: &MpegFrame) -> usize {
+    let bound = joint_stereo_bound(frame, SUBBANDS);
+    4 * (bound * frame.channel_count() + (SUBBANDS - bound))
+}
+
+/// Layer II : allocation de bits (table choisie d'après le débit par canal) puis
+/// 2 bits de sélection des facteurs d'échelle par sous-bande allouée
+fn layer2_protected_bits(data: &[u8], frame: &MpegFrame) -> Option<usize> {
+    let allocation = layer2_allocation_table(frame);
+    let channels = frame.channel_count();
+    let bound = joint_stereo_bound(frame, allocation.len());
+    let mut reader = BitReader { data, position: 0 };
+    let mut allocated = 0;
+
+    for (subband, &bits) in allocation.iter().enumerate() {
+        if subband < bound {
+            for _ in 0..channels {
+                if reader.read(bits as usize)? != 0 {
+                    allocated += 1;
+                }
+            }
+        } else if reader.read(bits as usize)? != 0 {
+            // Allocation commune aux deux canaux
+            allocated += channels;
+        }
+    }
+    Some(reader.position + 2 * allocated)
+}
+
+fn layer2_allocation_table(frame: &MpegFrame) -> &'static [u8] {
+    if frame.version_idx != 3 {
+        return &LAYER2_ALLOCATION_LSF;
+    }
+    let channel_bitrate = frame.bitrate_kbps / frame.channel_count() as u32;
+    if (frame.sample_rate == 48000 && channel_bitrate >= 56) || (56..=80).contains(&channel_bitrate)
+    {
+        &LAYER2_ALLOCATION_A
+    } else if frame.sample_rate != 48000 && channel_bitrate >= 96 {
+        &LAYER2_ALLOCATION_B
+    } else if frame.sample_rate != 32000 && channel_bitrate <= 48 {
+        &LAYER2_ALLOCATION_C
+    } else {
+        &LAYER2_ALLOCATION_D
+    }
+}
+
+struct BitReader<'a> {
+    data: &'a [u8],
+    /// Position en bits
+    position: usize,
+}
+
+impl BitReader<'_> {
+    fn read(&mut self, bits: usize) -> Option<u32> {
+        let mut value = 0u32;
+        for _ in 0..bits {
+            let byte = *self.data.get(self.position / 8)?;
+            let bit = (byte >> (7 - self.position % 8)) & 0x01;
+            value = (value << 1) | bit as u32;
+            self.position += 1;
+        }
+        Some(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Trame MPEG1 Layer III stéréo protégée par un CRC, à 128 kbps (417 octets)
+    /// ou 160 kbps (522 octets) ; `corrupt` altère les informations annexes après le calcul
+    fn protected_frame(bitrate_index: u8, corrupt: bool) -> Vec<u8> {
+        let len = if bitrate_index == 9 { 417 } else { 522 };
+        let mut frame = vec![0xFF, 0xFA, bitrate_index << 4, 0x00, 0, 0];
+        frame.extend((0..32).map(|i| i as u8 * 7));
+        let mut crc = 0xFFFF;
+        for &byte in frame[2..4].iter().chain(&frame[6..38]) {
+            crc_update(&mut crc, byte, 8);
+        }
+        frame[4..6].copy_from_slice(&crc.to_be_bytes());
+        if corrupt {
+            frame[10] ^= 0x01;
+        }
+        frame.resize(len, 0);
+        frame
+    }
+
+    #[test]
+    fn histogram_crc_errors_and_leading_junk() {
+        let mut bytes = vec![0; 20];
+        for (index, corrupt) in [(9, false), (10, false), (9, true), (9, false)] {
+            bytes.extend(protected_frame(index, corrupt));
+        }
+        let scan = scan_frames(&bytes, 0, bytes.len());
+        assert_eq!(scan.frames, 4);
+        assert_eq!(scan.samples, 4 * 1152);
+        assert_eq!(
+            scan.bitrate_histogram,
+            [
+                BitrateCount {
+                    bitrate: 128,
+                    frames: 3
+                },
+                BitrateCount {
+                    bitrate: 160,
+                    frames: 1
+                },
+            ]
+        );
+        assert_eq!((scan.min_bitrate, scan.max_bitrate), (Some(128), Some(160)));
+        assert_eq!(scan.crc_protection, "all");
+        assert_eq!(scan.crc_errors, [20 + 417 + 522]);
+        assert_eq!(
+            scan.sync_errors,
+            [SyncError {
+                offset: 0,
+                length: 20,
+                kind: "junk"
+            }]
+        );
+    }
+
+    #[test]
+    fn truncated_last_frame_is_reported() {
+        let mut bytes = protected_frame(9, false).repeat(3);
+        bytes.truncate(417 * 2 + 100);
+        let scan = scan_frames(&bytes, 0, bytes.len());
+        assert_eq!(scan.frames, 2);
+        assert_eq!(scan.sync_errors[0].kind, "truncated");
+        assert_eq!(scan.sync_errors[0].offset, 834);
+    }
+}
@@ -0,0 +1,321 @@
+// ---------------------------------------------------------------------------
+// Palette de couleurs extraite d'une pochette (median cut)
+// ---------------------------------------------------------------------------
+
+use serde::Serialize;
+use ts_rs::TS;
+
+use crate::image::{RgbaImage, resize_to_fit};
+
+/// Côté maximal de l'image analysée : largement suffisant pour une palette
+const PALETTE_SAMPLE_SIZE: u32 = 64;
+/// Nombre de teintes produites par le median cut
+const PALETTE_MAX_COLORS: usize = 12;
+/// Les pixels plus transparents que ce seuil sont ignorés
+const PALETTE_MIN_ALPHA: u8 = 128;
+
+#[derive(Clone, Copy, PartialEq)]
+struct Rgb {
+    r: u8,
+    g: u8,
+    b: u8,
+}
+
+impl Rgb {
+    /// Saturation et luminosité HSL, entre 0 et 1
+    fn saturation_lightness(&self) -> (f32, f32) {
+        let r = self.r as f32 / 255.0;
+        let g = self.g as f32 / 255.0;
+        let b = self.b as f32 / 255.0;
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let lightness = (max + min) / 2.0;
+        let delta = max - min;
+        let saturation = if delta == 0.0 {
+            0.0
+        } else {
+            delta / (1.0 - (2.0 * lightness - 1.0).abs())
+        };
+        (saturation, lightness)
+    }
+
+    /// Luminance relative WCAG 2.x
+    fn relative_luminance(&self) -> f32 {
+        let linear = |c: u8| {
+            let c = c as f32 / 255.0;
+            if c <= 0.03928 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            }
+        };
+        0.2126 * linear(self.r) + 0.7152 * linear(self.g) + 0.0722 * linear(self.b)
+    }
+
+    fn contrast_ratio(&self, other: &Rgb) -> f32 {
+        let a = self.relative_luminance();
+        let b = other.relative_luminance();
+        (a.max(b) + 0.05) / (a.min(b) + 0.05)
+    }
+}
+
+const WHITE: Rgb = Rgb {
+    r: 255,
+    g: 255,
+    b: 255,
+};
+const BLACK: Rgb = Rgb { r: 0, g: 0, b: 0 };
+
+/// Teinte moyenne d'une boîte du median cut et nombre de pixels représentés
+struct Swatch {
+    color: Rgb,
+    population: usize,
+}
+
+#[derive(Serialize, TS, Debug, Clone, PartialEq)]
+#[ts(export, export_to = "metadata.ts")]
+pub struct PaletteColor {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    /// « #rrggbb »
+    pub hex: String,
+}
+
+impl From<Rgb> for PaletteColor {
+    fn from(color: Rgb) -> Self {
+        Self {
+            r: color.r,
+            g: color.g,
+            b: color.b,
+            hex: format!("#{:02x}{:02x}{:02x}", color.r, color.g, color.b),
+        }
+    }
+}
+
+#[derive(Serialize, TS, Debug, Clone, PartialEq)]
+#[ts(export, export_to = "metadata.ts")]
+pub struct PaletteSwatch {
+    #[serde(flatten)]
+    #[ts(flatten)]
+    pub color: PaletteColor,
+    #[ts(type = "number")]
+    pub population: usize,
+}
+
+#[derive(Serialize, TS, Debug, Clone, PartialEq)]
+#[ts(export, export_to = "metadata.ts", rename = "CoverPalette")]
+pub struct Palette {
+    pub dominant: PaletteColor,
+    pub vibrant: PaletteColor,
+    pub muted: PaletteColor,
+    /// Noir ou blanc, celui qui contraste le plus avec la couleur dominante
+    pub text: PaletteColor,
+    /// Teintes triées par population décroissante
+    pub swatches: Vec<PaletteSwatch>,
+}
+
+/// Calculer la palette d'une image décodée. `None` si elle est entièrement transparente.
+pub(crate) fn extract_palette(image: &RgbaImage) -> Option<Palette> {
+    let sample = resize_to_fit(image, PALETTE_SAMPLE_SIZE);
+    let pixels: Vec<[u8; 3]> = sample
+        .pixels
+        .chunks_exact(4)
+        .filter(|px| px[3] >= PALETTE_MIN_ALPHA)
+        .map(|px| [px[0], px[1], px[2]])
+        .collect();
+    if pixels.is_empty() {
+        return None;
+    }
+
+    // Une teinte très présente peut être coupée en deux boîtes identiques : on les fusionne
+    let mut swatches: Vec<Swatch> = Vec::new();
+    for swatch in median_cut(pixels, PALETTE_MAX_COLORS) {
+        match swatches.iter_mut().find(|s| s.color == swatch.color) {
+            Some(existing) => existing.population += swatch.population,
+            None => swatches.push(swatch),
+        }
+    }
+    swatches.sort_by_key(|swatch| std::cmp::Reverse(swatch.population));
+
+    let dominant = swatches[0].color;
+    let vibrant = pick_swatch(&swatches, |s, l| {
+        (s >= 0.35 && (0.3..=0.75).contains(&l)).then(|| s * (1.0 - (l - 0.5).abs()))
+    })
+    .or_else(|| pick_swatch(&swatches, |s, _| Some(s)))
+    .unwrap_or(dominant);
+    let muted = pick_swatch(&swatches, |s, l| {
+        (s <= 0.4 && (0.25..=0.75).contains(&l)).then(|| (1.0 - s) * (1.0 - (l - 0.5).abs()))
+    })
+    .or_else(|| pick_swatch(&swatches, |s, _| Some(1.0 - s)))
+    .unwrap_or(dominant);
+
+    let text = if WHITE.contrast_ratio(&dominant) >= BLACK.contrast_ratio(&dominant) {
+        WHITE
+    } else {
+        BLACK
+    };
+
+    Some(Palette {
+        dominant: dominant.into(),
+        vibrant: vibrant.into(),
+        muted: muted.into(),
+        text: text.into(),
+        swatches: swatches
+            .into_iter()
+            .map(|swatch| PaletteSwatch {
+                color: swatch.color.into(),
+                population: swatch.population,
+            })
+            .collect(),
+    })
+}
+
+/// Choisir la teinte de meilleur score. `score` reçoit (saturation, luminosité)
+/// et renvoie `None` pour écarter une teinte ; la population départage légèrement.
+fn pick_swatch(swatches: &[Swatch], score: impl Fn(f32, f32) -> Option<f32>) -> Option<Rgb> {
+    let total: usize = swatches.iter().map(|s| s.population).sum();
+    swatches
+        .iter()
+        .filter_map(|swatch| {
+            let (saturation, lightness) = swatch.color.saturation_lightness();
+            let weight = swatch.population as f32 / total.max(1) as f32;
+            score(saturation, lightness).map(|value| (value + 0.25 * weight, swatch.color))
+        })
+        .max_by(|a, b| a.0.total_cmp(&b.0))
+        .map(|(_, color)| color)
+}
+
+/// Découper récursivement l'espace RVB : la boîte de plus grande étendue est coupée
+/// à la médiane de son canal le plus étendu, jusqu'à obtenir `max_colors` boîtes.
+fn median_cut(pixels: Vec<[u8; 3]>, max_colors: usize) -> Vec<Swatch> {
+    let mut boxes = vec![pixels];
+
+    while boxes.len() < max_colors {
+        let candidate = boxes
+            .iter()
+            .enumerate()
+            .filter(|(_, pixels)| pixels.len() > 1)
+            .map(|(index, pixels)| {
+                let (channel, range) = widest_channel(pixels);
+                (index, channel, range)
+            })
+            .filter(|&(_, _, range)| range > 0)
+            .max_by_key(|&(_, _, range)| range);
+        let Some((index, channel, _)) = candidate else {
+            break;
+        };
+
+        let mut pixels = boxes.swap_remove(index);
+        pixels.sort_unstable_by_key(|px| px[channel]);
+        let upper = pixels.split_off(pixels.len() / 2);
+        boxes.push(pixels);
+        boxes.push(upper);
+    }
+
+    boxes
+        .into_iter()
+        .filter(|pixels| !pixels.is_empty())
+        .map(|pixels| {
+            let mut sum = [0usize; 3];
+            for px in &pixels {
+                sum[0] += px[0] as usize;
+                sum[1] += px[1] as usize;
+                sum[2] += px[2] as usize;
+            }
+            let n = pixels.len();
+            Swatch {
+                color: Rgb {
+                    r: ((sum[0] + n / 2) / n) as u8,
+                    g: ((sum[1] + n / 2) / n) as u8,
+                    b: ((sum[2] + n / 2) / n) as u8,
+                },
+                population: n,
+            }
+        })
+        .collect()
+}
+
+/// Canal (0 = R, 1 = V, 2 = B) de plus grande étendue et valeur de cette étendue
+fn widest_channel(pixels: &[[u8; 3]]) -> (usize, u8) {
+    let mut min = [u8::MAX; 3];
+    let mut max = [u8::MIN; 3];
+    for px in pixels {
+        for c in 0..3 {
+            min[c] = min[c].min(px[c]);
+            max[c] = max[c].max(px[c]);
+        }
+    }
+    (0..3)
+        .map(|c| (c, max[c] - min[c]))
+        .max_by_key(|&(_, range)| range)
+        .unwrap_or((0, 0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NAVY: [u8; 4] = [20, 30, 60, 255];
+    const RED: [u8; 4] = [220, 40, 40, 255];
+    const GREY: [u8; 4] = [128, 128, 128, 255];
+
+    /// Image d'une ligne composée de plages de pixels identiques
+    fn image(runs: &[([u8; 4], usize)]) -> RgbaImage {
+        let pixels: Vec<u8> = runs
+            .iter()
+            .flat_map(|&(pixel, count)| pixel.repeat(count))
+            .collect();
+        RgbaImage {
+            width: (pixels.len() / 4) as u32,
+            height: 1,
+            pixels,
+        }
+    }
+
+    #[test]
+    fn dominant_vibrant_and_muted_colors() {
+        // Les pixels transparents sont ignorés
+        let palette = extract_palette(&image(&[
+            (NAVY, 36),
+            (RED, 16),
+            (GREY, 8),
+            ([0, 255, 0, 0], 4),
+        ]))
+        .unwrap();
+        assert_eq!(palette.dominant.hex, "#141e3c");
+        assert_eq!(palette.vibrant.hex, "#dc2828");
+        assert_eq!(palette.muted.hex, "#808080");
+        let populations: Vec<_> = palette
+            .swatches
+            .iter()
+            .map(|swatch| (swatch.color.hex.as_str(), swatch.population))
+            .collect();
+        assert_eq!(
+            populations,
+            [("#141e3c", 36), ("#dc2828", 16), ("#808080", 8)]
+        );
+    }
+
+    #[test]
+    fn text_color_contrasts_with_the_dominant_color() {
+        let dark = extract_palette(&image(&[(NAVY, 4)])).unwrap();
+        assert_eq!(dark.text.hex, "#ffffff");
+        let light = extract_palette(&image(&[([240, 230, 200, 255], 4), (NAVY, 1)])).unwrap();
+        assert_eq!(light.text.hex, "#000000");
+    }
+
+    #[test]
+    fn single_color_falls_back_to_the_dominant_color() {
+        // Teinte trop sombre pour être vive ou atténuée : repli sur la seule disponible
+        let palette = extract_palette(&image(&[(NAVY, 8)])).unwrap();
+        assert_eq!(palette.vibrant, palette.dominant);
+        assert_eq!(palette.muted, palette.dominant);
+        assert_eq!(palette.swatches.len(), 1);
+    }
+
+    #[test]
+    fn transparent_image_has_no_palette() {
+        assert!(extract_palette(&image(&[([255, 255, 255, 127], 16)])).is_none());
+    }
+}
@@ -0,0 +1,505 @@
+// ---------------------------------------------------------------------------
+// Analyse incrémentale : le parseur reçoit des morceaux du fichier à des positions
+// quelconques et indique la prochaine plage d'octets dont il a besoin
+// ---------------------------------------------------------------------------
+
+use std::ops::Range;
+
+use crate::mp4::{MAX_TOP_LEVEL_ATOMS, atom_header, parse_moov};
+use crate::{
+    Error, FLAC_SIGNATURE, Format, ID3V1_SIZE, ID3V2_HEADER_SIZE, Metadata, OGG_PAGE_HEADER_SIZE,
+    OGG_SIGNATURE, ParseOptions, be_u32, build_metadata, id3v2_tag_len, is_id3v2_header, model,
+    parse_flac, parse_mp3_head, parse_mp3_trailing, parse_ogg, synchsafe_to_u32, trailing_tag_len,
+};
+
+/// Taille minimale d'une lecture demandée : les petits éléments voisins arrivent ensemble
+const MIN_REQUEST_BYTES: u64 = 16 * 1024;
+
+/// Marge lue au-delà de la fenêtre de recherche de la première trame MPEG : une synchronisation
+/// n'est retenue que si les trames suivantes (jusqu'à 5760 octets en format libre) sont valides.
+const MPEG_SYNC_CONFIRM_BYTES: u64 = 8192;
+
+/// Début d'un bloc PICTURE lu avant de décider s'il faut le reste : en-tête du bloc
+/// et en-tête de l'image, pour ses dimensions
+const PICTURE_PREFIX_BYTES: u64 = 64 * 1024;
+
+/// Taille maximale d'une page Ogg : en-tête, table de 255 segments de 255 octets chacun
+const MAX_OGG_PAGE_SIZE: u64 = OGG_PAGE_HEADER_SIZE as u64 + 255 + 255 * 255;
+
+/// Parseur à états pour les fichiers lus par morceaux (`File.slice`, requêtes HTTP `Range`).
+///
+/// `request()` renvoie la plage `[début, fin)` à lire ensuite, `feed()` la transmet et renvoie
+/// la suivante ; quand il n'y a plus rien à lire, `finish()` produit le résultat. Seuls les
+/// octets utiles sont demandés : blocs de métadonnées FLAC (sans le remplissage ni le corps
+/// d'une image trop grande pour servir de pochette), pages d'en-tête Ogg et dernière page,
+/// tag ID3v2 de tête et début du flux MPEG, tags de fin (ID3v2 ajouté, APE, Lyrics3, ID3v1),
+/// en-têtes des atomes MP4 de premier niveau et atome `moov`, qu'il précède ou suive `mdat`.
+pub struct StreamParser {
+    file_size: u64,
+    options: ParseOptions,
+    /// Morceaux reçus, triés par position et fusionnés quand ils se touchent
+    chunks: Vec<Chunk>,
+}
+
+struct Chunk {
+    start: u64,
+    data: Vec<u8>,
+}
+
+impl Chunk {
+    fn end(&self) -> u64 {
+        self.start + self.data.len() as u64
+    }
+}
+
+/// Régions du fichier à analyser, une fois toutes reçues
+enum Plan {
+    /// Blocs de métadonnées, lus dans les morceaux reçus ; seul le début d'une image
+    /// non demandée en entier est disponible
+    Flac,
+    /// Pages d'en-tête, puis fin du fichier pour la position granulaire de la dernière page
+    Ogg { head_end: u64, tail_start: u64 },
+    /// Tag de tête et début du flux, puis tags de fin à partir de `tail_start`
+    Mpeg { head_end: u64, tail_start: u64 },
+    /// Contenu de l'atome `moov` s'il existe, octets des atomes `mdat` pour le débit
+    Mp4 {
+        moov: Option<Range<u64>>,
+        media_bytes: Option<u64>,
+    },
+}
+
+impl StreamParser {
+    pub fn new(file_size: u64, options: ParseOptions) -> Self {
+        Self {
+            file_size,
+            options: ParseOptions {
+                file_size: Some(file_size),
+                ..options
+            },
+            chunks: Vec::new(),
+        }
+    }
+
+    /// Prochaine plage à lire, ou `None` quand le résultat peut être produit
+    pub fn request(&self) -> Option<Range<u64>> {
+        let missing = self.plan().err()?;
+        // Lire un peu plus loin : les blocs et pages qui suivent sont souvent demandés ensuite
+        let end = missing
+            .end
+            .max(missing.start + MIN_REQUEST_BYTES)
+            .min(self.file_size);
+        Some(missing.start..end)
+    }
+
+    /// Transmettre les octets lus à la position `offset` ; renvoie la plage suivante à lire.
+    /// Les morceaux peuvent arriver dans n'importe quel ordre et se recouvrir.
+    pub fn feed(&mut self, offset: u64, chunk: &[u8]) -> Option<Range<u64>> {
+        self.insert(offset, chunk);
+        self.request()
+    }
+
+    /// Analyser les régions reçues. Échoue si une plage demandée manque encore
+    /// ou si le fichier n'est pas reconnu.
+    pub fn finish(&self) -> Result<model::Metadata, Error> {
+        let plan = self.plan().map_err(|missing| Error::MissingBytes {
+            start: missing.start,
+            end: missing.end,
+        })?;
+        let options = &self.options;
+        let format = Format::detect(self.slice(0..ID3V2_HEADER_SIZE as u64));
+        let mut meta = Metadata::new(options.fallback_encoding);
+        match plan {
+            Plan::Flac => {
+                let read =
+                    |range: Range<usize>| self.available(range.start as u64..range.end as u64);
+                parse_flac(read, self.file_size as usize, options, &mut meta);
+            }
+            Plan::Ogg {
+                head_end,
+                tail_start,
+            } => {
+                let head = self.slice(0..head_end);
+                let tail = self.slice(tail_start..self.file_size);
+                parse_ogg(head, tail, options, &mut meta);
+            }
+            Plan::Mpeg {
+                head_end,
+                tail_start,
+            } => {
+                parse_mp3_head(self.slice(0..head_end), tail_start, options, &mut meta);
+                let tail = self.slice(tail_start..self.file_size);
+                parse_mp3_trailing(tail, tail_start as usize, options, &mut meta);
+            }
+            Plan::Mp4 { moov, media_bytes } => {
+                if let Some(moov) = moov {
+                    let offset = moov.start as usize;
+                    parse_moov(self.slice(moov), offset, media_bytes, options, &mut meta);
+                }
+            }
+        }
+        if format == Format::Mpeg && !meta.found_mpeg_data {
+            return Err(Error::UnrecognizedFormat);
+        }
+        Ok(build_metadata(&meta, options))
+    }
+
+    /// Régions à analyser, ou première plage manquante pour les déterminer
+    fn plan(&self) -> Result<Plan, Range<u64>> {
+        let header = self.need(0..ID3V2_HEADER_SIZE as u64)?;
+        match Format::detect(header) {
+            Format::Flac => self.plan_flac(),
+            Format::Ogg => self.plan_ogg(),
+            Format::Mp4 => self.plan_mp4(),
+            Format::Mpeg => self.plan_mpeg(),
+        }
+    }
+
+    fn plan_flac(&self) -> Result<Plan, Range<u64>> {
+        let mut offset = FLAC_SIGNATURE.len() as u64;
+        while offset + 4 <= self.file_size {
+            let header = self.need(offset..offset + 4)?;
+            let is_last = header[0] & 0x80 != 0;
+            let block_type = header[0] & 0x7F;
+            let length = (header[1] as u64) << 16 | (header[2] as u64) << 8 | header[3] as u64;
+            let body = offset + 4..offset + 4 + length;
+            match block_type {
+                // Le remplissage n'est jamais lu
+                1 => {}
+                6 if length > PICTURE_PREFIX_BYTES => {
+                    // Une image trop grande pour la pochette, ou désignée par son emplacement
+                    // sans miniature à produire, n'est décrite que par son en-tête
+                    let prefix = self.need(body.start..body.start + PICTURE_PREFIX_BYTES)?;
+                    let needs_data =
+                        !self.options.cover_refs || !self.options.thumbnail_sizes.is_empty();
+                    if needs_data
+                        && flac_picture_data_len(prefix)
+                            .is_none_or(|len| len <= self.options.max_cover_bytes as u64)
+                    {
+                        self.need(body.clone())?;
+                    }
+                }
+                _ => {
+                    self.need(body.clone())?;
+                }
+            }
+            offset = body.end;
+            if is_last {
+                break;
+            }
+        }
+        Ok(Plan::Flac)
+    }
+
+    fn plan_ogg(&self) -> Result<Plan, Range<u64>> {
+        let header_size = OGG_PAGE_HEADER_SIZE as u64;
+        let mut offset = 0;
+        while offset + header_size <= self.file_size {
+            let header = self.need(offset..offset + header_size)?;
+            if &header[0..4] != OGG_SIGNATURE {
+                break;
+            }
+            let granule = u64::from_le_bytes(header[6..14].try_into().unwrap_or_default());
+            let table_start = offset + header_size;
+            let table = self.need(table_start..table_start + header[26] as u64)?;
+            let page_end =
+                table_start + table.len() as u64 + table.iter().map(|&len| len as u64).sum::<u64>();
+            // Les pages d'en-tête ont une position granulaire nulle (ou -1 quand aucun paquet
+            // ne s'y termine) : la première page audio clôt les en-têtes
+            if granule != 0 && granule != u64::MAX {
+                break;
+            }
+            self.need(offset..page_end)?;
+            offset = page_end;
+        }
+        let tail_start = self.file_size.saturating_sub(MAX_OGG_PAGE_SIZE);
+        self.need(tail_start..self.file_size)?;
+        Ok(Plan::Ogg {
+            head_end: offset.min(self.file_size),
+            tail_start,
+        })
+    }
+
+    fn plan_mp4(&self) -> Result<Plan, Range<u64>> {
+        let mut offset = 0;
+        let mut moov = None;
+        let mut media_bytes = Some(0);
+        let mut count = 0;
+        // Seuls les en-têtes sont lus : `mdat` est sauté, où que se trouve `moov`
+        while offset < self.file_size {
+            let Some(header) = atom_header(self.need(offset..offset + 16)?) else {
+                break;
+            };
+            count += 1;
+            if count > MAX_TOP_LEVEL_ATOMS {
+                media_bytes = None;
+                break;
+            }
+            let end = header
+                .size
+                .map_or(self.file_size, |size| offset.saturating_add(size))
+                .min(self.file_size);
+            match &header.kind {
+                b"moov" if moov.is_none() => {
+                    let body = (offset + header.header_len).min(end)..end;
+                    self.need(body.clone())?;
+                    moov = Some(body);
+                }
+                b"mdat" => {
+                    media_bytes = media_bytes
+                        .map(|total| total + (end - offset).saturating_sub(header.header_len))
+                }
+                _ => {}
+            }
+            offset = end;
+        }
+        Ok(Plan::Mp4 { moov, media_bytes })
+    }
+
+    fn plan_mpeg(&self) -> Result<Plan, Range<u64>> {
+        let header = self.need(0..ID3V2_HEADER_SIZE as u64)?;
+        let tag_len = if is_id3v2_header(header, 0) {
+            id3v2_tag_len(header) as u64
+        } else {
+            0
+        };
+        let head_end = tag_len + self.options.mpeg_scan_bytes as u64 + MPEG_SYNC_CONFIRM_BYTES;
+        self.need(0..head_end)?;
+
+        // Tags de fin retirés un à un (ID3v1, APE, Lyrics3v2), le plus souvent dans une seule lecture
+        self.need(self.file_size.saturating_sub(MIN_REQUEST_BYTES)..self.file_size)?;
+        let mut end = self.file_size;
+        loop {
+            let window = self.need(end.saturating_sub(ID3V1_SIZE as u64)..end)?;
+            match trailing_tag_len(window).and_then(|len| end.checked_sub(len as u64)) {
+                Some(start) => end = start,
+                None => break,
+            }
+        }
+
+        // Tag ID3v2.4 ajouté juste avant, repéré par son pied de page « 3DI »
+        let mut tail_start = end;
+        let footer_size = ID3V2_HEADER_SIZE as u64;
+        if end >= 2 * footer_size {
+            let footer = self.need(end - footer_size..end)?;
+            let size = synchsafe_to_u32(&footer[6..10]) as u64;
+            if &footer[0..3] == b"3DI"
+                && let Some(start) = end.checked_sub(2 * footer_size + size)
+                && is_id3v2_header(self.need(start..start + footer_size)?, 0)
+            {
+                tail_start = start;
+            }
+        }
+        self.need(tail_start..self.file_size)?;
+        Ok(Plan::Mpeg {
+            head_end: head_end.min(self.file_size),
+            tail_start,
+        })
+    }
+
+    /// Octets de `range` (bornée à la taille du fichier), ou la partie qui manque encore
+    fn need(&self, range: Range<u64>) -> Result<&[u8], Range<u64>> {
+        let end = range.end.min(self.file_size);
+        let start = range.start.min(end);
+        if start == end {
+            return Ok(&[]);
+        }
+        match self
+            .chunks
+            .iter()
+            .find(|chunk| chunk.start <= start && start < chunk.end())
+        {
+            Some(chunk) if chunk.end() >= end => {
+                Ok(&chunk.data[(start - chunk.start) as usize..(end - chunk.start) as usize])
+            }
+            Some(chunk) => Err(chunk.end()..end),
+            None => Err(start..end),
+        }
+    }
+
+    /// Octets d'une région déjà vérifiée par `plan()`
+    fn slice(&self, range: Range<u64>) -> &[u8] {
+        self.need(range).unwrap_or_default()
+    }
+
+    /// Octets reçus au début de `range`, jusqu'à la fin du morceau qui la contient
+    fn available(&self, range: Range<u64>) -> &[u8] {
+        let end = range.end.min(self.file_size);
+        match self
+            .chunks
+            .iter()
+            .find(|chunk| chunk.start <= range.start && range.start < chunk.end())
+        {
+            Some(chunk) if range.start < end => {
+                let stop = end.min(chunk.end());
+                &chunk.data[(range.start - chunk.start) as usize..(stop - chunk.start) as usize]
+            }
+            _ => &[],
+        }
+    }
+
+    /// Ranger un morceau, fusionné avec ceux qu'il chevauche ou prolonge
+    fn insert(&mut self, offset: u64, data: &[u8]) {
+        let end = offset.saturating_add(data.len() as u64).min(self.file_size);
+        if offset >= end {
+            return;
+        }
+        let data = &data[..(end - offset) as usize];
+        let mut touching: Vec<Chunk> = self
+            .chunks
+            .extract_if(.., |chunk| chunk.start <= end && chunk.end() >= offset)
+            .collect();
+        // Le premier morceau touché, s'il commence avant, est prolongé sur place
+        let mut merged = match touching.first() {
+            Some(first) if first.start <= offset => touching.remove(0),
+            _ => Chunk {
+                start: offset,
+                data: Vec::new(),
+            },
+        };
+        let merged_end = touching
+            .iter()
+            .map(Chunk::end)
+            .fold(end.max(merged.end()), u64::max);
+        merged.data.resize((merged_end - merged.start) as usize, 0);
+        for chunk in &touching {
+            let at = (chunk.start - merged.start) as usize;
+            merged.data[at..at + chunk.data.len()].copy_from_slice(&chunk.data);
+        }
+        let at = (offset - merged.start) as usize;
+        merged.data[at..at + data.len()].copy_from_slice(data);
+        let position = self
+            .chunks
+            .partition_point(|chunk| chunk.start < merged.start);
+        self.chunks.insert(position, merged);
+    }
+}
+
+/// Taille des données d'un bloc PICTURE FLAC, d'après le début du bloc
+fn flac_picture_data_len(block: &[u8]) -> Option<u64> {
+    let mime_len = be_u32(block.get(4..8)?) as usize;
+    let desc_at = 8 + mime_len;
+    let desc_len = be_u32(block.get(desc_at..desc_at + 4)?) as usize;
+    // Largeur, hauteur, profondeur de couleur, couleurs utilisées (4 × 4 octets)
+    let len_at = desc_at + 4 + desc_len + 16;
+    Some(be_u32(block.get(len_at..len_at + 4)?) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    /// Fichier FLAC minimal : STREAMINFO puis un bloc PICTURE de `image_len` octets
+    fn flac_with_picture(image_len: usize) -> Vec<u8> {
+        let mut bytes = FLAC_SIGNATURE.to_vec();
+        bytes.extend_from_slice(&[0x00, 0, 0, 34]);
+        let mut streaminfo = [0u8; 34];
+        // 44 100 Hz, 2 canaux, 16 bits
+        streaminfo[10..13].copy_from_slice(&[0x0A, 0xC4, 0x42]);
+        streaminfo[13] = 0xF0;
+        bytes.extend_from_slice(&streaminfo);
+
+        let mime = b"image/jpeg";
+        let mut block = 3u32.to_be_bytes().to_vec();
+        block.extend_from_slice(&(mime.len() as u32).to_be_bytes());
+        block.extend_from_slice(mime);
+        block.extend_from_slice(&0u32.to_be_bytes());
+        block.extend_from_slice(&[0; 16]);
+        block.extend_from_slice(&(image_len as u32).to_be_bytes());
+        let mut image = vec![0x5A; image_len];
+        image[..3].copy_from_slice(&[0xFF, 0xD8, 0xFF]);
+        block.extend_from_slice(&image);
+        let len = (block.len() as u32).to_be_bytes();
+        bytes.extend_from_slice(&[0x86, len[1], len[2], len[3]]);
+        bytes.extend_from_slice(&block);
+        bytes
+    }
+
+    /// Lire les plages demandées jusqu'au résultat ; renvoie aussi le nombre d'octets lus
+    fn stream(bytes: &[u8], options: ParseOptions) -> (model::Metadata, u64) {
+        let size = bytes.len() as u64;
+        let mut parser = StreamParser::new(size, options);
+        let mut read = 0;
+        let mut next = parser.request();
+        while let Some(range) = next {
+            read += range.end - range.start;
+            next = parser.feed(
+                range.start,
+                &bytes[range.start as usize..range.end as usize],
+            );
+        }
+        (parser.finish().unwrap(), read)
+    }
+
+    #[test]
+    fn large_flac_picture_is_described_without_its_body() {
+        let bytes = flac_with_picture(500_000);
+        let options = ParseOptions {
+            include_pictures: true,
+            cover_refs: true,
+            ..ParseOptions::default()
+        };
+        let (metadata, read) = stream(&bytes, options.clone());
+        assert!(read < 100_000, "{read} bytes read");
+        assert_eq!(metadata, parse(&bytes, &options).unwrap());
+        let picture = &metadata.pictures[0];
+        assert_eq!(picture.byte_length, 500_000);
+        assert!(picture.data.is_none());
+        let data_ref = picture.data_ref.as_ref().unwrap();
+        assert_eq!(data_ref.length, 500_000);
+        assert_eq!(data_ref.offset as usize + data_ref.length, bytes.len());
+    }
+
+    #[test]
+    fn trailing_moov_is_read_without_mdat() {
+        let tags = crate::mp4::tests::udta(&[crate::mp4::tests::title_item("Title")]);
+        let bytes = crate::mp4::tests::m4a(&tags, true, 500_000);
+        let options = ParseOptions::default();
+        let (metadata, read) = stream(&bytes, options.clone());
+        assert!(read < 100_000, "{read} bytes read");
+        assert_eq!(metadata, parse(&bytes, &options).unwrap());
+        assert_eq!(metadata.audio.sample_rate, Some(44_100));
+        assert_eq!(metadata.title.as_deref(), Some("Title"));
+        assert_eq!(metadata.audio.bitrate, Some(2000));
+    }
+
+    fn chunks(parser: &StreamParser) -> Vec<(u64, Vec<u8>)> {
+        parser
+            .chunks
+            .iter()
+            .map(|chunk| (chunk.start, chunk.data.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn insert_merges_touching_chunks() {
+        let mut parser = StreamParser::new(20, ParseOptions::default());
+        parser.insert(10, &[3, 3]);
+        parser.insert(2, &[1, 1]);
+        assert_eq!(chunks(&parser), [(2, vec![1, 1]), (10, vec![3, 3])]);
+
+        // Contigu au premier, recouvre le début du second
+        parser.insert(4, &[2, 2, 2, 2, 2, 2, 2]);
+        assert_eq!(chunks(&parser), [(2, vec![1, 1, 2, 2, 2, 2, 2, 2, 2, 3])]);
+
+        // Les octets reçus en dernier l'emportent ; la fin du fichier borne le morceau
+        parser.insert(0, &[0; 2]);
+        parser.insert(11, &[4; 20]);
+        assert_eq!(
+            chunks(&parser),
+            [(0, [vec![0, 0, 1, 1], vec![2; 7], vec![4; 9]].concat())]
+        );
+        parser.insert(25, &[5]);
+        assert_eq!(parser.chunks.len(), 1);
+    }
+
+    #[test]
+    fn need_reports_the_missing_part() {
+        let mut parser = StreamParser::new(100, ParseOptions::default());
+        parser.insert(0, &[7; 10]);
+        assert_eq!(parser.need(2..6), Ok(&[7u8; 4][..]));
+        assert_eq!(parser.need(5..30), Err(10..30));
+        assert_eq!(parser.need(40..200), Err(40..100));
+        assert_eq!(parser.available(5..30), &[7; 5]);
+    }
+}
@@ -0,0 +1,78 @@
+// ---------------------------------------------------------------------------
+// Désynchronisation ID3v2 : un 0x00 est inséré après chaque 0xFF qui pourrait
+// passer pour une synchronisation MPEG ; la lecture doit le retirer
+// ---------------------------------------------------------------------------
+
+use std::ops::Range;
+
+/// Positions des 0x00 retirés à la resynchronisation, pour retrouver
+/// l'emplacement d'origine d'un octet décodé
+#[derive(Clone)]
+pub(crate) struct UnsyncMap {
+    /// Pour chaque 0x00 retiré, position dans les octets décodés de l'octet qui le suivait
+    removed: Vec<usize>,
+}
+
+impl UnsyncMap {
+    /// Nombre d'octets retirés
+    pub(crate) fn removed(&self) -> usize {
+        self.removed.len()
+    }
+
+    /// Position d'origine de l'octet décodé `position`
+    fn raw_position(&self, position: usize) -> usize {
+        position + self.removed.partition_point(|&next| next <= position)
+    }
+
+    /// Plage d'origine couvrant les octets décodés `range`, 0x00 retirés compris
+    pub(crate) fn raw_range(&self, range: Range<usize>) -> Range<usize> {
+        let start = self.raw_position(range.start);
+        if range.is_empty() {
+            return start..start;
+        }
+        start..self.raw_position(range.end - 1) + 1
+    }
+}
+
+/// Retirer le 0x00 qui suit chaque 0xFF.
+/// `None` si les octets sont inchangés : ils peuvent alors être empruntés tels quels.
+pub(crate) fn resynchronise(raw: &[u8]) -> Option<(Vec<u8>, UnsyncMap)> {
+    let first = raw.windows(2).position(|pair| pair == [0xFF, 0x00])?;
+    let mut data = Vec::with_capacity(raw.len());
+    let mut removed = Vec::new();
+    data.extend_from_slice(&raw[..=first]);
+    let mut previous = 0xFF;
+    for &byte in &raw[first + 1..] {
+        if previous == 0xFF && byte == 0x00 {
+            removed.push(data.len());
+        } else {
+            data.push(byte);
+        }
+        previous = byte;
+    }
+    Some((data, UnsyncMap { removed }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unchanged_bytes_are_borrowed() {
+        assert!(resynchronise(&[0x41, 0xFF, 0xE0, 0x00]).is_none());
+    }
+
+    #[test]
+    fn only_the_zero_after_ff_is_removed() {
+        // FF 00 00 : le premier 0x00 a été inséré, le second appartient aux données
+        let raw = [0xFF, 0x00, 0x00, 0x41, 0xFF, 0x00, 0xE0];
+        let (data, map) = resynchronise(&raw).unwrap();
+        assert_eq!(data, [0xFF, 0x00, 0x41, 0xFF, 0xE0]);
+        assert_eq!(map.removed(), 2);
+        assert_eq!(map.raw_range(0..data.len()), 0..raw.len());
+        assert_eq!(map.raw_range(1..2), 2..3);
+        assert_eq!(map.raw_range(2..3), 3..4);
+        assert_eq!(map.raw_range(3..5), 4..7);
+        assert_eq!(map.raw_range(2..2), 3..3);
+    }
+}
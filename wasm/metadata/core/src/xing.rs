@@ -0,0 +1,314 @@
+// ---------------------------------------------------------------------------
+// En-têtes Xing / Info / VBRI de la première trame MPEG et extension LAME
+// ---------------------------------------------------------------------------
+
+use crate::model::BitrateMode;
+use crate::{be_u16, be_u32};
+
+/// Taille de l'extension LAME qui suit l'en-tête Xing
+const LAME_TAG_SIZE: usize = 36;
+
+pub(crate) struct XingHeader {
+    /// « Info » : fichier encodé à débit constant
+    pub(crate) is_info: bool,
+    /// Nombre de trames audio, sans la trame portant l'en-tête
+    pub(crate) frames: Option<u32>,
+    /// Taille du flux audio en octets, en-tête compris
+    pub(crate) bytes: Option<u32>,
+    /// Position (sur 256) dans le flux audio de chaque centième de la durée
+    pub(crate) toc: Option<[u8; 100]>,
+    /// Qualité déclarée par l'encodeur (0 = meilleure … 100)
+    pub(crate) quality: Option<u32>,
+    pub(crate) lame: Option<LameTag>,
+}
+
+/// Extension « LAME » (également écrite par FFmpeg sous le nom « Lavc » / « Lavf »)
+pub(crate) struct LameTag {
+    /// Chaîne d'identification, par exemple « LAME3.100 »
+    pub(crate) version: String,
+    pub(crate) vbr_method: Option<&'static str>,
+    /// Mode de débit déduit de la méthode
+    pub(crate) bitrate_mode: Option<BitrateMode>,
+    /// Fréquence de coupure du filtre passe-bas, en Hz
+    pub(crate) lowpass_hz: Option<u32>,
+    pub(crate) preset: Option<String>,
+    /// Échantillons ajoutés par l'encodeur au début du flux
+    pub(crate) delay: u32,
+    /// Échantillons de remplissage ajoutés à la fin du flux
+    pub(crate) padding: u32,
+}
+
+/// Lire l'en-tête Xing / Info d'une trame. `side_info_offset` est la position
+/// des données après l'en-tête de trame (et le CRC éventuel), `side_info_len`
+/// la taille des informations annexes qui précèdent l'en-tête Xing.
+pub(crate) fn parse_xing_header(
+    frame: &[u8],
+    side_info_offset: usize,
+    side_info_len: usize,
+) -> Option<XingHeader> {
+    let mut offset = side_info_offset + side_info_len;
+    let magic = frame.get(offset..offset + 4)?;
+    if magic != b"Xing" && magic != b"Info" {
+        return None;
+    }
+    let flags = be_u32(frame.get(offset + 4..offset + 8)?);
+    offset += 8;
+
+    // Champs présents selon les drapeaux : trames (1), octets (2), table TOC (4), qualité (8)
+    let mut frames = None;
+    if flags & 0x01 != 0 {
+        frames = frame.get(offset..offset + 4).map(be_u32);
+        offset += 4;
+    }
+    let mut bytes = None;
+    if flags & 0x02 != 0 {
+        bytes = frame.get(offset..offset + 4).map(be_u32);
+        offset += 4;
+    }
+    let mut toc = None;
+    if flags & 0x04 != 0 {
+        toc = frame
+            .get(offset..offset + 100)
+            .and_then(|table| table.try_into().ok());
+        offset += 100;
+    }
+    let mut quality = None;
+    if flags & 0x08 != 0 {
+        quality = frame.get(offset..offset + 4).map(be_u32);
+        offset += 4;
+    }
+
+    let lame = frame
+        .get(offset..offset + LAME_TAG_SIZE)
+        .and_then(parse_lame_tag);
+
+    Some(XingHeader {
+        is_info: magic == b"Info",
+        frames: frames.filter(|&n| n > 0),
+        bytes: bytes.filter(|&n| n > 0),
+        toc,
+        quality,
+        lame,
+    })
+}
+
+fn parse_lame_tag(tag: &[u8]) -> Option<LameTag> {
+    let prefix = &tag[0..4];
+    if prefix != b"LAME" && prefix != b"Lavc" && prefix != b"Lavf" && prefix != b"L3.9" {
+        return None;
+    }
+    let version: String = tag[0..9]
+        .iter()
+        .take_while(|&&b| b.is_ascii_graphic() || b == b' ')
+        .map(|&b| b as char)
+        .collect();
+    let version = version.trim_end().to_string();
+
+    let method = tag[9] & 0x0F;
+    let vbr_method = match method {
+        1 => Some("cbr"),
+        2 => Some("abr"),
+        3 => Some("vbr-rh"),
+        4 => Some("vbr-mtrh"),
+        5 => Some("vbr-mt"),
+        6 => Some("vbr-4"),
+        8 => Some("cbr-2pass"),
+        9 => Some("abr-2pass"),
+        _ => None,
+    };
+    let bitrate_mode = match method {
+        1 | 8 => Some(BitrateMode::Cbr),
+        2 | 9 => Some(BitrateMode::Abr),
+        3..=6 => Some(BitrateMode::Vbr),
+        _ => None,
+    };
+    let lowpass_hz = match tag[10] {
+        0 => None,
+        value => Some(value as u32 * 100),
+    };
+
+    // Préréglage sur 11 bits, dans les deux derniers octets avant la longueur de la musique
+    let preset_code = (((tag[26] & 0x07) as u16) << 8) | tag[27] as u16;
+    let abr_bitrate = tag[20];
+    let preset = lame_preset_name(preset_code).or_else(|| {
+        // Anciens LAME : seul le débit ABR est renseigné
+        (matches!(method, 2 | 9) && abr_bitrate > 0).then(|| format!("ABR {abr_bitrate}"))
+    });
+
+    // Délai et remplissage : deux valeurs de 12 bits
+    let delay = ((tag[21] as u32) << 4) | (tag[22] as u32 >> 4);
+    let padding = ((tag[22] as u32 & 0x0F) << 8) | tag[23] as u32;
+
+    Some(LameTag {
+        version,
+        vbr_method,
+        bitrate_mode,
+        lowpass_hz,
+        preset,
+        delay,
+        padding,
+    })
+}
+
+fn lame_preset_name(code: u16) -> Option<String> {
+    let name = match code {
+        0 => return None,
+        8..=320 => return Some(format!("ABR {code}")),
+        // 410 = V9 … 500 = V0
+        410..=500 if code.is_multiple_of(10) => return Some(format!("V{}", (500 - code) / 10)),
+        1000 => "r3mix",
+        1001 => "standard",
+        1002 => "extreme",
+        1003 => "insane",
+        1004 => "standard fast",
+        1005 => "extreme fast",
+        1006 => "medium",
+        1007 => "medium fast",
+        _ => return None,
+    };
+    Some(name.to_string())
+}
+
+/// En-tête VBRI de l'encodeur Fraunhofer, toujours 32 octets après l'en-tête de trame
+pub(crate) struct VbriHeader {
+    pub(crate) bytes: u32,
+    pub(crate) frames: u32,
+    /// Taille en octets de chaque segment de `frames_per_entry` trames
+    pub(crate) toc: Vec<u32>,
+    pub(crate) frames_per_entry: u32,
+}
+
+pub(crate) fn parse_vbri_header(frame: &[u8], data_offset: usize) -> Option<VbriHeader> {
+    let offset = data_offset + 32;
+    let header = frame.get(offset..offset + 26)?;
+    if &header[0..4] != b"VBRI" {
+        return None;
+    }
+    // Version (2), délai (2), qualité (2)
+    let bytes = be_u32(&header[10..14]);
+    let frames = be_u32(&header[14..18]);
+    let entries = be_u16(&header[18..20]) as usize;
+    let scale = be_u16(&header[20..22]) as u32;
+    let entry_size = be_u16(&header[22..24]) as usize;
+    let frames_per_entry = be_u16(&header[24..26]) as u32;
+
+    let mut toc = Vec::new();
+    if (1..=4).contains(&entry_size)
+        && let Some(table) = frame.get(offset + 26..offset + 26 + entries * entry_size)
+    {
+        // Une entrée qui déborde une fois mise à l'échelle rend toute la table inutilisable
+        toc = table
+            .chunks_exact(entry_size)
+            .map(|entry| {
+                entry
+                    .iter()
+                    .fold(0u32, |acc, &b| (acc << 8) | b as u32)
+                    .checked_mul(scale)
+            })
+            .collect::<Option<_>>()
+            .unwrap_or_default();
+    }
+
+    (frames > 0).then_some(VbriHeader {
+        bytes,
+        frames,
+        toc,
+        frames_per_entry,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Trame MPEG1 Layer III stéréo : en-tête, 32 octets d'informations annexes, puis `header`
+    fn frame_with(header: &[u8]) -> Vec<u8> {
+        let mut frame = vec![0xFF, 0xFB, 0x90, 0x00];
+        frame.extend_from_slice(&[0; 32]);
+        frame.extend_from_slice(header);
+        frame.resize(417, 0);
+        frame
+    }
+
+    fn lame_tag() -> [u8; LAME_TAG_SIZE] {
+        let mut tag = [0; LAME_TAG_SIZE];
+        tag[0..9].copy_from_slice(b"LAME3.100");
+        // Révision 0, méthode 4 (vbr-mtrh), passe-bas 19,5 kHz
+        tag[9] = 0x04;
+        tag[10] = 195;
+        // Délai 576, remplissage 1234
+        tag[21..24].copy_from_slice(&[0x24, 0x04, 0xD2]);
+        // Préréglage V2 (480)
+        tag[26..28].copy_from_slice(&[0x01, 0xE0]);
+        tag
+    }
+
+    #[test]
+    fn reads_xing_fields_and_lame_tag() {
+        let mut header = b"Xing".to_vec();
+        header.extend_from_slice(&0x0Fu32.to_be_bytes());
+        header.extend_from_slice(&1000u32.to_be_bytes());
+        header.extend_from_slice(&417_000u32.to_be_bytes());
+        header.extend((0..100).map(|i| (i * 256 / 100) as u8));
+        header.extend_from_slice(&78u32.to_be_bytes());
+        header.extend_from_slice(&lame_tag());
+
+        let xing = parse_xing_header(&frame_with(&header), 4, 32).unwrap();
+        assert!(!xing.is_info);
+        assert_eq!(xing.frames, Some(1000));
+        assert_eq!(xing.bytes, Some(417_000));
+        assert_eq!(xing.toc.map(|toc| toc[50]), Some(128));
+        assert_eq!(xing.quality, Some(78));
+        let lame = xing.lame.unwrap();
+        assert_eq!(lame.version, "LAME3.100");
+        assert_eq!(lame.vbr_method, Some("vbr-mtrh"));
+        assert_eq!(lame.bitrate_mode, Some(BitrateMode::Vbr));
+        assert_eq!(lame.lowpass_hz, Some(19500));
+        assert_eq!(lame.preset.as_deref(), Some("V2"));
+        assert_eq!((lame.delay, lame.padding), (576, 1234));
+    }
+
+    #[test]
+    fn info_header_without_optional_fields() {
+        let mut header = b"Info".to_vec();
+        header.extend_from_slice(&0u32.to_be_bytes());
+        let xing = parse_xing_header(&frame_with(&header), 4, 32).unwrap();
+        assert!(xing.is_info);
+        assert!(xing.frames.is_none() && xing.toc.is_none());
+        // Zéros à la place de l'extension : aucune signature reconnue
+        assert!(xing.lame.is_none());
+        assert!(parse_xing_header(&frame_with(b"Nope"), 4, 32).is_none());
+    }
+
+    /// En-tête VBRI avec une table de `entry_size` octets par entrée
+    fn vbri(scale: u16, entry_size: u16, entries: &[u32]) -> Vec<u8> {
+        let mut header = b"VBRI".to_vec();
+        header.extend_from_slice(&[0, 1, 0, 0, 0, 75]);
+        header.extend_from_slice(&400_000u32.to_be_bytes());
+        header.extend_from_slice(&1000u32.to_be_bytes());
+        header.extend_from_slice(&(entries.len() as u16).to_be_bytes());
+        header.extend_from_slice(&scale.to_be_bytes());
+        header.extend_from_slice(&entry_size.to_be_bytes());
+        header.extend_from_slice(&100u16.to_be_bytes());
+        for entry in entries {
+            header.extend_from_slice(&entry.to_be_bytes()[4 - entry_size as usize..]);
+        }
+        header
+    }
+
+    #[test]
+    fn reads_vbri_table() {
+        let vbri_header = parse_vbri_header(&frame_with(&vbri(2, 2, &[1000, 3000])), 4).unwrap();
+        assert_eq!((vbri_header.bytes, vbri_header.frames), (400_000, 1000));
+        assert_eq!(vbri_header.frames_per_entry, 100);
+        assert_eq!(vbri_header.toc, [2000, 6000]);
+    }
+
+    #[test]
+    fn overflowing_vbri_table_is_dropped() {
+        let frame = frame_with(&vbri(u16::MAX, 4, &[10, u32::MAX / 2]));
+        let vbri_header = parse_vbri_header(&frame, 4).unwrap();
+        assert_eq!(vbri_header.frames, 1000);
+        assert!(vbri_header.toc.is_empty());
+    }
+}
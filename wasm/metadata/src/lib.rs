@@ -1,66 +1,17 @@
+use std::ops::Range;
+
 use js_sys::{Array, Object, Uint8Array};
+use rta_metadata::cuesheet::{CD_SAMPLE_RATE, CueIndex, CueSheet, CueTrack};
+use rta_metadata::model::{ByteRange, ThumbnailFormat};
+use rta_metadata::stream::StreamParser;
+use rta_metadata::{
+    CueSheetOptions, MAX_COVER_BYTES, MAX_TEXT_BYTES, MAX_THUMBNAIL_SIZE, ParseOptions,
+    encoding_for_label,
+};
+use serde::Serialize;
 use wasm_bindgen::JsCast;
 use wasm_bindgen::prelude::*;
 
-const ID3V2_HEADER_SIZE: usize = 10;
-const ID3V1_SIZE: usize = 128;
-const FLAC_SIGNATURE: &[u8; 4] = b"fLaC";
-
-const MAX_TEXT_BYTES: usize = 16 * 1024;
-const MAX_COVER_BYTES: usize = 4 * 1024 * 1024;
-
-/// Tables de recherche de taux d'échantillonnage MPEG indexées par [version_index][sr_index]
-/// version_index: 0 = MPEG2.5, 1 = réservé, 2 = MPEG2, 3 = MPEG1
-const MPEG_SAMPLE_RATES: [[u32; 3]; 4] = [
-    [11025, 12000, 8000],  // MPEG2.5
-    [0, 0, 0],             // réservé
-    [22050, 24000, 16000], // MPEG2
-    [44100, 48000, 32000], // MPEG1
-];
-
-/// Tables de recherche de débit binaire MPEG (kbps)
-/// L'index 0 est inutilisé (format libre), l'index 15 est invalide
-/// [version_layer_combo][bitrate_index]
-/// Combos: 0 = V1/L1, 1 = V1/L2, 2 = V1/L3, 3 = V2/L1, 4 = V2/L2+L3
-const MPEG_BITRATES: [[u16; 15]; 5] = [
-    // V1, Layer I
-    [
-        0, 32, 64, 96, 128, 160, 192, 224, 256, 288, 320, 352, 384, 416, 448,
-    ],
-    // V1, Layer II
-    [
-        0, 32, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320, 384,
-    ],
-    // V1, Layer III
-    [
-        0, 32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320,
-    ],
-    // V2, Layer I
-    [
-        0, 32, 48, 56, 64, 80, 96, 112, 128, 144, 160, 176, 192, 224, 256,
-    ],
-    // V2, Layer II & III
-    [0, 8, 16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 144, 160],
-];
-
-struct AudioInfo {
-    sample_rate: Option<u32>,
-    bit_depth: Option<u16>,
-    bitrate: Option<u32>,
-    channels: Option<u8>,
-}
-
-impl AudioInfo {
-    fn new() -> Self {
-        Self {
-            sample_rate: None,
-            bit_depth: None,
-            bitrate: None,
-            channels: None,
-        }
-    }
-}
-
 #[wasm_bindgen]
 pub fn parse_metadata(bytes: &[u8]) -> JsValue {
     parse_metadata_with_limits(bytes, MAX_TEXT_BYTES, MAX_COVER_BYTES)
@@ -72,957 +23,332 @@ pub fn parse_metadata_with_limits(
     max_text_bytes: usize,
     max_cover_bytes: usize,
 ) -> JsValue {
-    let mut title: Option<String> = None;
-    let mut artist: Option<String> = None;
-    let mut album: Option<String> = None;
-    let mut cover_mime: Option<String> = None;
-    let mut cover_data: Option<Vec<u8>> = None;
-    let mut cover_type: Option<u8> = None;
-    let mut lyrics: Option<String> = None;
-    let mut synced_lyrics: Option<String> = None;
-    let mut audio_info = AudioInfo::new();
-
-    if bytes.len() >= 4 && &bytes[0..4] == FLAC_SIGNATURE {
-        parse_flac(
-            bytes,
-            max_text_bytes,
-            max_cover_bytes,
-            &mut title,
-            &mut artist,
-            &mut album,
-            &mut cover_mime,
-            &mut cover_data,
-            &mut cover_type,
-            &mut lyrics,
-            &mut synced_lyrics,
-            &mut audio_info,
-        );
-    } else {
-        parse_mp3(
-            bytes,
-            max_text_bytes,
-            max_cover_bytes,
-            &mut title,
-            &mut artist,
-            &mut album,
-            &mut cover_mime,
-            &mut cover_data,
-            &mut cover_type,
-            &mut lyrics,
-            &mut synced_lyrics,
-            &mut audio_info,
-        );
-    }
-
-    build_result_object(
-        &title,
-        &artist,
-        &album,
-        &cover_mime,
-        &cover_data,
-        &cover_type,
-        &lyrics,
-        &synced_lyrics,
-        &audio_info,
+    parse_with_options(
+        bytes,
+        &ParseOptions::with_limits(max_text_bytes, max_cover_bytes),
     )
 }
 
+/// Variante acceptant un objet d'options (`WasmParseOptions` dans `metadataParser.ts`),
+/// toutes facultatives :
+/// - `maxTextBytes`, `maxCoverBytes` : limites par texte et par image
+/// - `includePictures`, `includePictureData` : liste `pictures`, avec ou sans les octets
+/// - `includeCoverData` : octets de la pochette dans `coverData`
+/// - `coverRefs` : emplacement des images (`coverRef`, `dataRef`) plutôt que leurs octets
+/// - `thumbnailSizes`, `thumbnailFormat` (`"png"` ou `"rgba"`) : miniatures à produire
+/// - `fallbackEncoding` : page de code des textes ID3 déclarés ISO-8859-1
+/// - `fileSize` : taille du fichier complet quand `bytes` n'en est qu'un extrait
+/// - `includeSeekPoints` : remplir `seekPoints`
+/// - `mpegScanBytes` : octets examinés pour trouver la première trame MPEG
 #[wasm_bindgen]
-pub fn parse_metadata_batch(
-    buffers: Array,
-    max_text_bytes: usize,
-    max_cover_bytes: usize,
-) -> Array {
-    let out = Array::new();
-    let len = buffers.length();
-    for i in 0..len {
-        let value = buffers.get(i);
-        if let Some(u8a) = value.dyn_ref::<Uint8Array>() {
-            let mut vec = vec![0u8; u8a.length() as usize];
-            u8a.copy_to(&mut vec[..]);
-            let meta = parse_metadata_with_limits(&vec, max_text_bytes, max_cover_bytes);
-            out.push(&meta);
-        } else {
-            out.push(&JsValue::NULL);
-        }
-    }
-    out
+pub fn parse_metadata_with_options(bytes: &[u8], options: &JsValue) -> JsValue {
+    parse_with_options(bytes, &parse_options_from_js(options))
 }
 
-fn build_result_object(
-    title: &Option<String>,
-    artist: &Option<String>,
-    album: &Option<String>,
-    cover_mime: &Option<String>,
-    cover_data: &Option<Vec<u8>>,
-    cover_type: &Option<u8>,
-    lyrics: &Option<String>,
-    synced_lyrics: &Option<String>,
-    audio_info: &AudioInfo,
-) -> JsValue {
-    let obj = Object::new();
-    if let Some(value) = title {
-        set_prop(&obj, "title", &JsValue::from_str(value));
-    }
-    if let Some(value) = artist {
-        set_prop(&obj, "artist", &JsValue::from_str(value));
-    }
-    if let Some(value) = album {
-        set_prop(&obj, "album", &JsValue::from_str(value));
-    }
-    if let Some(value) = cover_mime {
-        set_prop(&obj, "coverMime", &JsValue::from_str(value));
-    }
-    if let Some(value) = cover_data {
-        let array = Uint8Array::from(value.as_slice());
-        set_prop(&obj, "coverData", &array.into());
-    }
-    if let Some(value) = cover_type {
-        set_prop(&obj, "coverType", &JsValue::from_f64(*value as f64));
-    }
-    if let Some(value) = lyrics {
-        set_prop(&obj, "lyrics", &JsValue::from_str(value));
+/// Format non reconnu : objet vide, comme pour un fichier sans tag
+fn parse_with_options(bytes: &[u8], options: &ParseOptions) -> JsValue {
+    match rta_metadata::parse(bytes, options) {
+        Ok(metadata) => to_js_value(&metadata),
+        Err(_) => Object::new().into(),
     }
-    if let Some(value) = synced_lyrics {
-        set_prop(&obj, "syncedLyrics", &JsValue::from_str(value));
-    }
-    if let Some(sr) = audio_info.sample_rate {
-        set_prop(&obj, "sampleRate", &JsValue::from_f64(sr as f64));
-    }
-    if let Some(bd) = audio_info.bit_depth {
-        set_prop(&obj, "bitDepth", &JsValue::from_f64(bd as f64));
-    }
-    if let Some(br) = audio_info.bitrate {
-        set_prop(&obj, "bitrate", &JsValue::from_f64(br as f64));
-    }
-    if let Some(ch) = audio_info.channels {
-        set_prop(&obj, "channels", &JsValue::from_f64(ch as f64));
-    }
-    obj.into()
 }
 
-fn set_prop(obj: &Object, key: &str, value: &JsValue) {
-    let _ = js_sys::Reflect::set(obj, &JsValue::from_str(key), value);
+/// Vue « débogueur de tags » : chaque élément structurel rencontré (tag, frame, bloc, clé,
+/// trame de synchronisation) avec sa position, le champ qu'il a alimenté ou la raison
+/// pour laquelle il a été écarté. Renvoie `{ format, nodes, metadata }`.
+#[wasm_bindgen]
+pub fn inspect(bytes: &[u8]) -> JsValue {
+    to_js_value(&rta_metadata::inspect(bytes))
 }
 
-// ---------------------------------------------------------------------------
-// MP3 / ID3
-// ---------------------------------------------------------------------------
-
-fn parse_mp3(
-    bytes: &[u8],
+#[wasm_bindgen]
+pub fn parse_metadata_batch(
+    buffers: Array,
     max_text_bytes: usize,
     max_cover_bytes: usize,
-    title: &mut Option<String>,
-    artist: &mut Option<String>,
-    album: &mut Option<String>,
-    cover_mime: &mut Option<String>,
-    cover_data: &mut Option<Vec<u8>>,
-    cover_type: &mut Option<u8>,
-    lyrics: &mut Option<String>,
-    synced_lyrics: &mut Option<String>,
-    audio_info: &mut AudioInfo,
-) {
-    let mut mpeg_scan_start: usize = 0;
-
-    if bytes.len() >= ID3V2_HEADER_SIZE && &bytes[0..3] == b"ID3" {
-        let tag_size = synchsafe_to_u32(&bytes[6..10]) as usize;
-        mpeg_scan_start = ID3V2_HEADER_SIZE + tag_size;
-
-        parse_id3v2(
-            bytes,
-            max_text_bytes,
-            max_cover_bytes,
-            title,
-            artist,
-            album,
-            cover_mime,
-            cover_data,
-            cover_type,
-            lyrics,
-            synced_lyrics,
-        );
-    }
-
-    // Essayer de trouver le premier en-tête de trame MPEG pour le taux d'échantillonnage / débit / canaux
-    parse_mpeg_frame_header(bytes, mpeg_scan_start, audio_info);
-
-    if (title.is_none() || artist.is_none() || album.is_none()) && bytes.len() >= ID3V1_SIZE {
-        parse_id3v1(bytes, title, artist, album);
-    }
+) -> Array {
+    parse_batch_with_options(
+        &buffers,
+        &ParseOptions::with_limits(max_text_bytes, max_cover_bytes),
+        &[],
+    )
 }
 
-/// Rechercher une trame de synchronisation audio MPEG valide à partir de `start`.
-/// Extrait sample_rate, bitrate (kbps), bit_depth (toujours 16 pour MP3), channels.
-fn parse_mpeg_frame_header(bytes: &[u8], start: usize, audio_info: &mut AudioInfo) {
-    if audio_info.sample_rate.is_some() {
-        return;
-    }
-
-    let end = bytes.len().saturating_sub(3);
-    let mut i = start;
-
-    // Limiter la recherche à 8 Ko à partir du début pour éviter de scanner des fichiers énormes
-    let scan_limit = (start + 8192).min(end);
-
-    while i < scan_limit {
-        // Rechercher le mot de synchronisation: 11 bits à 1 = 0xFF suivi d'un octet avec les 3 bits supérieurs à 1
-        if bytes[i] != 0xFF || (bytes[i + 1] & 0xE0) != 0xE0 {
-            i += 1;
-            continue;
-        }
-
-        let b1 = bytes[i + 1];
-        let b2 = bytes[i + 2];
-        let b3 = bytes[i + 3];
-
-        let version_idx = ((b1 >> 3) & 0x03) as usize;
-        let layer_idx = ((b1 >> 1) & 0x03) as usize;
-        let bitrate_idx = ((b2 >> 4) & 0x0F) as usize;
-        let sr_idx = ((b2 >> 2) & 0x03) as usize;
-        let channel_mode = (b3 >> 6) & 0x03;
-
-        // Valider: version != réservé(1), layer != réservé(0), sr != réservé(3), bitrate != libre(0) ou invalide(15)
-        if version_idx == 1
-            || layer_idx == 0
-            || sr_idx == 3
-            || bitrate_idx == 0
-            || bitrate_idx == 15
-        {
-            i += 1;
-            continue;
-        }
-
-        let sample_rate = MPEG_SAMPLE_RATES[version_idx][sr_idx];
-        if sample_rate == 0 {
-            i += 1;
-            continue;
-        }
-
-        // Déterminer l'index de la table de débit binaire
-        let is_v1 = version_idx == 3;
-        let bitrate_table_idx = match (is_v1, layer_idx) {
-            (true, 3) => 0,  // V1, Layer I
-            (true, 2) => 1,  // V1, Layer II
-            (true, 1) => 2,  // V1, Layer III
-            (false, 3) => 3, // V2/V2.5, Layer I
-            (false, _) => 4, // V2/V2.5, Layer II & III
-            _ => {
-                i += 1;
-                continue;
-            }
-        };
-
-        let bitrate_kbps = MPEG_BITRATES[bitrate_table_idx][bitrate_idx] as u32;
-
-        let channels = if channel_mode == 3 { 1u8 } else { 2u8 };
-
-        audio_info.sample_rate = Some(sample_rate);
-        audio_info.bit_depth = Some(16); // L'audio MP3 décodé est toujours en PCM 16 bits
-        audio_info.bitrate = Some(bitrate_kbps);
-        audio_info.channels = Some(channels);
-        return;
-    }
+/// `fileSizes` donne la taille du fichier de chaque tampon, dans le même ordre
+#[wasm_bindgen]
+pub fn parse_metadata_batch_with_options(buffers: Array, options: &JsValue) -> Array {
+    let file_sizes: Vec<u64> = get_number_array_prop(options, "fileSizes")
+        .unwrap_or_default()
+        .into_iter()
+        .map(|size| size as u64)
+        .collect();
+    parse_batch_with_options(&buffers, &parse_options_from_js(options), &file_sizes)
 }
 
-fn parse_id3v2(
-    bytes: &[u8],
-    max_text_bytes: usize,
-    max_cover_bytes: usize,
-    title: &mut Option<String>,
-    artist: &mut Option<String>,
-    album: &mut Option<String>,
-    cover_mime: &mut Option<String>,
-    cover_data: &mut Option<Vec<u8>>,
-    cover_type: &mut Option<u8>,
-    lyrics: &mut Option<String>,
-    synced_lyrics: &mut Option<String>,
-) {
-    if bytes.len() < ID3V2_HEADER_SIZE {
-        return;
-    }
-
-    let version = bytes[3];
-    let flags = bytes[5];
-    let size = synchsafe_to_u32(&bytes[6..10]) as usize;
-
-    let mut offset = ID3V2_HEADER_SIZE;
-    if flags & 0x40 != 0 && bytes.len() >= offset + 4 && version >= 3 {
-        let ext_size = if version == 4 {
-            synchsafe_to_u32(&bytes[offset..offset + 4]) as usize
-        } else {
-            be_u32(&bytes[offset..offset + 4]) as usize
-        };
-        offset = offset.saturating_add(ext_size);
-    }
-
-    let end = offset.saturating_add(size);
-    let end = end.min(bytes.len());
-
-    while offset < end {
-        if version == 2 {
-            if offset + 6 > end {
-                break;
-            }
-            let frame_id = &bytes[offset..offset + 3];
-            if frame_id == [0, 0, 0] {
-                break;
-            }
-            let frame_size = ((bytes[offset + 3] as usize) << 16)
-                | ((bytes[offset + 4] as usize) << 8)
-                | (bytes[offset + 5] as usize);
-            let frame_data_offset = offset + 6;
-            if frame_size == 0 || frame_data_offset + frame_size > end {
-                break;
-            }
-            let frame_data = &bytes[frame_data_offset..frame_data_offset + frame_size];
-
-            match frame_id {
-                b"TT2" => {
-                    if title.is_none() {
-                        *title = parse_id3_text_frame(frame_data, max_text_bytes);
-                    }
-                }
-                b"TP1" => {
-                    if artist.is_none() {
-                        *artist = parse_id3_text_frame(frame_data, max_text_bytes);
-                    }
-                }
-                b"TAL" => {
-                    if album.is_none() {
-                        *album = parse_id3_text_frame(frame_data, max_text_bytes);
-                    }
-                }
-                b"PIC" => {
-                    if let Some((mime, data, pic_type)) =
-                        parse_pic_frame(frame_data, max_cover_bytes)
-                    {
-                        let should_replace =
-                            cover_data.is_none() || (*cover_type != Some(3) && pic_type == 3);
-                        if should_replace {
-                            *cover_mime = Some(mime);
-                            *cover_data = Some(data);
-                            *cover_type = Some(pic_type);
-                        }
-                    }
-                }
-                b"ULT" => {
-                    if lyrics.is_none() && synced_lyrics.is_none() {
-                        if let Some(text) = parse_uslt_frame(frame_data, max_text_bytes) {
-                            if text.starts_with('[') && text.contains(']') {
-                                *synced_lyrics = Some(text);
-                            } else {
-                                *lyrics = Some(text);
-                            }
-                        }
-                    }
-                }
-                _ => {}
-            }
-
-            offset = frame_data_offset + frame_size;
-            continue;
-        }
-
-        if offset + 10 > end {
-            break;
-        }
-        let frame_id = &bytes[offset..offset + 4];
-        if frame_id == [0, 0, 0, 0] {
-            break;
-        }
-
-        let frame_size = if version == 4 {
-            synchsafe_to_u32(&bytes[offset + 4..offset + 8]) as usize
-        } else {
-            be_u32(&bytes[offset + 4..offset + 8]) as usize
-        };
-
-        let frame_flags = &bytes[offset + 8..offset + 10];
-        let frame_data_offset = offset + 10;
-
-        if frame_size == 0 || frame_data_offset + frame_size > end {
-            break;
-        }
-
-        if frame_flags[1] & 0x0F != 0 {
-            offset = frame_data_offset + frame_size;
-            continue;
-        }
-
-        let frame_data = &bytes[frame_data_offset..frame_data_offset + frame_size];
-
-        match frame_id {
-            b"TIT2" => {
-                if title.is_none() {
-                    *title = parse_id3_text_frame(frame_data, max_text_bytes);
-                }
-            }
-            b"TPE1" => {
-                if artist.is_none() {
-                    *artist = parse_id3_text_frame(frame_data, max_text_bytes);
-                }
-            }
-            b"TALB" => {
-                if album.is_none() {
-                    *album = parse_id3_text_frame(frame_data, max_text_bytes);
-                }
-            }
-            b"APIC" => {
-                if let Some((mime, data, pic_type)) = parse_apic_frame(frame_data, max_cover_bytes)
-                {
-                    let should_replace =
-                        cover_data.is_none() || (*cover_type != Some(3) && pic_type == 3);
-                    if should_replace {
-                        *cover_mime = Some(mime);
-                        *cover_data = Some(data);
-                        *cover_type = Some(pic_type);
-                    }
-                }
-            }
-            b"USLT" => {
-                if lyrics.is_none() && synced_lyrics.is_none() {
-                    if let Some(text) = parse_uslt_frame(frame_data, max_text_bytes) {
-                        if text.starts_with('[') && text.contains(']') {
-                            *synced_lyrics = Some(text);
-                        } else {
-                            *lyrics = Some(text);
-                        }
-                    }
-                }
-            }
-            _ => {}
-        }
-
-        offset = frame_data_offset + frame_size;
-    }
+/// Palette d'une pochette encodée (JPEG / PNG), typiquement `coverData`.
+/// Renvoie `null` si l'image ne peut pas être décodée.
+#[wasm_bindgen]
+pub fn extract_cover_palette(bytes: &[u8]) -> JsValue {
+    rta_metadata::extract_cover_palette(bytes)
+        .map_or(JsValue::NULL, |palette| to_js_value(&palette))
 }
 
-fn parse_id3_text_frame(frame_data: &[u8], max_text_bytes: usize) -> Option<String> {
-    if frame_data.is_empty() {
-        return None;
-    }
-    let encoding = frame_data[0];
-    let text = &frame_data[1..];
-    let text = if text.len() > max_text_bytes {
-        &text[..max_text_bytes]
-    } else {
-        text
-    };
-
-    match encoding {
-        0 => Some(latin1_to_string(trim_trailing_zeros(text))),
-        1 => decode_utf16_with_bom(trim_trailing_zeros(text)),
-        2 => decode_utf16be(trim_trailing_zeros(text)),
-        3 => String::from_utf8(text.to_vec()).ok().map(trim_string),
-        _ => None,
-    }
+/// Palette d'une image déjà décodée en RVBA 8 bits (par exemple une miniature `rgba`).
+#[wasm_bindgen]
+pub fn extract_palette_rgba(pixels: &[u8], width: u32, height: u32) -> JsValue {
+    rta_metadata::extract_palette_rgba(pixels, width, height)
+        .map_or(JsValue::NULL, |palette| to_js_value(&palette))
 }
 
-fn parse_uslt_frame(frame_data: &[u8], max_text_bytes: usize) -> Option<String> {
-    // Structure de la frame USLT :
-    // encodage (1 octet) | langue (3 octets) | descripteur de contenu (terminé par un null) | texte des paroles
-    if frame_data.len() < 5 {
-        return None;
-    }
-    let encoding = frame_data[0];
-    // Ignorer la langue (3 octets)
-    let mut idx = 4;
-
-    // Ignorer le descripteur de contenu (chaîne terminée par un null)
-    if encoding == 0 || encoding == 3 {
-        // Latin1 ou UTF-8 : chercher un octet null unique
-        match find_zero(frame_data, idx) {
-            Some(end) => idx = end + 1,
-            None => return None,
-        }
-    } else {
-        // UTF-16 : chercher deux octets null consécutifs
-        match find_zero_utf16(frame_data, idx) {
-            Some(end) => idx = end + 2,
-            None => return None,
-        }
-    }
-
-    if idx >= frame_data.len() {
-        return None;
-    }
-
-    let text = &frame_data[idx..];
-    let text = if text.len() > max_text_bytes {
-        &text[..max_text_bytes]
-    } else {
-        text
-    };
-
-    match encoding {
-        0 => Some(latin1_to_string(trim_trailing_zeros(text))),
-        1 => decode_utf16_with_bom(trim_trailing_zeros(text)),
-        2 => decode_utf16be(trim_trailing_zeros(text)),
-        3 => String::from_utf8(text.to_vec()).ok().map(trim_string),
-        _ => None,
-    }
+/// Contrôle qualité d'un MP3 complet : parcours de toutes les trames audio
+/// (débits, modes de canaux, CRC, pertes de synchronisation).
+#[wasm_bindgen]
+pub fn scan_mpeg_frames(bytes: &[u8]) -> JsValue {
+    to_js_value(&rta_metadata::scan_mpeg_frames(bytes))
 }
 
-fn parse_apic_frame(frame_data: &[u8], max_cover_bytes: usize) -> Option<(String, Vec<u8>, u8)> {
-    if frame_data.len() < 4 {
-        return None;
-    }
-    let encoding = frame_data[0];
-    let mut idx = 1;
-
-    let mime_end = find_zero(frame_data, idx)?;
-    let mime = String::from_utf8_lossy(&frame_data[idx..mime_end]).to_string();
-    idx = mime_end + 1;
-
-    if idx >= frame_data.len() {
-        return None;
-    }
-
-    let pic_type = frame_data[idx];
-    idx += 1;
-
-    let (_desc_end, next_idx) = if encoding == 0 || encoding == 3 {
-        let end = find_zero(frame_data, idx)?;
-        (end, end + 1)
-    } else {
-        let end = find_zero_utf16(frame_data, idx)?;
-        (end, end + 2)
+/// Feuille CUE externe (fichier .cue) : même structure que `cueSheet` du résultat.
+/// Options : `{ sampleRate, durationMs, fallbackEncoding }`. Renvoie `null` sans piste.
+/// Un `fallbackEncoding` inconnu de `encoding_for_label` est ignoré.
+#[wasm_bindgen]
+pub fn parse_cue_sheet(bytes: &[u8], options: &JsValue) -> JsValue {
+    let options = CueSheetOptions {
+        sample_rate: get_number_prop(options, "sampleRate")
+            .map_or(CD_SAMPLE_RATE, |rate| rate as u32),
+        duration_ms: get_number_prop(options, "durationMs"),
+        fallback_encoding: get_prop(options, "fallbackEncoding")
+            .and_then(|v| v.as_string())
+            .and_then(|label| encoding_for_label(&label)),
     };
-
-    if next_idx > frame_data.len() {
-        return None;
-    }
-
-    let img_data = &frame_data[next_idx..];
-    if img_data.is_empty() || img_data.len() > max_cover_bytes {
-        return None;
-    }
-
-    Some((mime, img_data.to_vec(), pic_type))
+    rta_metadata::parse_cue_sheet(bytes, &options)
+        .map_or(JsValue::NULL, |sheet| to_js_value(&sheet))
 }
 
-fn parse_id3v1(
-    bytes: &[u8],
-    title: &mut Option<String>,
-    artist: &mut Option<String>,
-    album: &mut Option<String>,
-) {
-    let start = bytes.len() - ID3V1_SIZE;
-    if &bytes[start..start + 3] != b"TAG" {
-        return;
-    }
-    let title_raw = &bytes[start + 3..start + 33];
-    let artist_raw = &bytes[start + 33..start + 63];
-    let album_raw = &bytes[start + 63..start + 93];
-
-    if title.is_none() {
-        let t = latin1_to_string(trim_trailing_zeros(title_raw));
-        if !t.is_empty() {
-            *title = Some(t);
-        }
-    }
-    if artist.is_none() {
-        let a = latin1_to_string(trim_trailing_zeros(artist_raw));
-        if !a.is_empty() {
-            *artist = Some(a);
-        }
-    }
-    if album.is_none() {
-        let al = latin1_to_string(trim_trailing_zeros(album_raw));
-        if !al.is_empty() {
-            *album = Some(al);
-        }
-    }
+/// Écrire une feuille CUE à partir d'une liste de pistes au format de `cueSheet`.
+/// Chaque piste donne ses `indices` (`offsetMs`) ou, à défaut, `startMs` et `preGapMs`.
+#[wasm_bindgen]
+pub fn write_cue_sheet(sheet: &JsValue) -> String {
+    rta_metadata::write_cue_sheet(&cue_sheet_from_js(sheet))
 }
 
-// ---------------------------------------------------------------------------
-// FLAC
-// ---------------------------------------------------------------------------
-
-fn parse_flac(
-    bytes: &[u8],
-    max_text_bytes: usize,
-    max_cover_bytes: usize,
-    title: &mut Option<String>,
-    artist: &mut Option<String>,
-    album: &mut Option<String>,
-    cover_mime: &mut Option<String>,
-    cover_data: &mut Option<Vec<u8>>,
-    cover_type: &mut Option<u8>,
-    lyrics: &mut Option<String>,
-    synced_lyrics: &mut Option<String>,
-    audio_info: &mut AudioInfo,
-) {
-    if bytes.len() < 4 || &bytes[0..4] != FLAC_SIGNATURE {
-        return;
-    }
-    let mut offset = 4;
-    let mut is_last = false;
-
-    while !is_last && offset + 4 <= bytes.len() {
-        let header = bytes[offset];
-        is_last = header & 0x80 != 0;
-        let block_type = header & 0x7F;
-        let length = ((bytes[offset + 1] as usize) << 16)
-            | ((bytes[offset + 2] as usize) << 8)
-            | (bytes[offset + 3] as usize);
-        offset += 4;
-
-        if offset + length > bytes.len() {
-            break;
-        }
-
-        let block = &bytes[offset..offset + length];
-
-        match block_type {
-            0 => parse_flac_streaminfo(block, audio_info),
-            4 => parse_vorbis_comment(
-                block,
-                max_text_bytes,
-                title,
-                artist,
-                album,
-                lyrics,
-                synced_lyrics,
-            ),
-            6 => {
-                if let Some((mime, data, pic_type)) = parse_flac_picture(block, max_cover_bytes) {
-                    let should_replace =
-                        cover_data.is_none() || (*cover_type != Some(3) && pic_type == 3);
-                    if should_replace {
-                        *cover_mime = Some(mime);
-                        *cover_data = Some(data);
-                        *cover_type = Some(pic_type);
-                    }
-                }
-            }
-            _ => {}
-        }
-
-        offset += length;
-    }
+/// Analyse incrémentale d'un fichier lu par morceaux : `request()` et `feed()` renvoient
+/// la plage `{ start, end }` à lire ensuite, ou `null` quand `finish()` peut être appelé.
+/// Pour un MP4/M4A, seuls les en-têtes d'atomes et `moov` sont lus, même en fin de fichier.
+#[wasm_bindgen]
+pub struct MetadataStreamParser {
+    inner: StreamParser,
 }
 
-/// Analyser le bloc STREAMINFO FLAC (34 octets).
-///
-/// Structure (offsets en bits):
-///   0..15   : taille de bloc min
-///   16..31  : taille de bloc max
-///   32..55  : taille de trame min  (24 bits)
-///   56..79  : taille de trame max  (24 bits)
-///   80..99  : taux d'échantillonnage (20 bits)
-///   100..102: canaux - 1           (3 bits)
-///   103..107: bits/échantillon - 1 (5 bits)
-///   108..143: échantillons totaux  (36 bits)
-///   144..271: signature MD5
-fn parse_flac_streaminfo(block: &[u8], audio_info: &mut AudioInfo) {
-    if block.len() < 18 {
-        return;
+#[wasm_bindgen]
+impl MetadataStreamParser {
+    /// Mêmes options que `parse_metadata_with_options` ; `fileSize` est remplacé par `file_size`
+    #[wasm_bindgen(constructor)]
+    pub fn new(file_size: f64, options: &JsValue) -> Self {
+        Self {
+            inner: StreamParser::new(file_size as u64, parse_options_from_js(options)),
+        }
     }
 
-    // Taux d'échantillonnage: 20 bits commençant à l'octet 10
-    let sample_rate =
-        ((block[10] as u32) << 12) | ((block[11] as u32) << 4) | ((block[12] as u32) >> 4);
-
-    // Canaux: 3 bits aux bits 100..102 → octet 12 bits [3..1]
-    let channels = ((block[12] >> 1) & 0x07) + 1;
-
-    // Bits par échantillon: 5 bits aux bits 103..107
-    // le bit 103 est le LSB de l'octet 12, les bits 104..107 sont les 4 bits supérieurs de l'octet 13
-    let bps = (((block[12] & 0x01) as u16) << 4) | ((block[13] >> 4) as u16);
-    let bit_depth = bps + 1;
-
-    if sample_rate > 0 {
-        audio_info.sample_rate = Some(sample_rate);
-    }
-    if bit_depth > 0 && bit_depth <= 32 {
-        audio_info.bit_depth = Some(bit_depth);
+    pub fn request(&self) -> JsValue {
+        range_to_js(self.inner.request())
     }
-    if channels > 0 && channels <= 8 {
-        audio_info.channels = Some(channels);
-    }
-}
 
-fn parse_vorbis_comment(
-    data: &[u8],
-    max_text_bytes: usize,
-    title: &mut Option<String>,
-    artist: &mut Option<String>,
-    album: &mut Option<String>,
-    lyrics: &mut Option<String>,
-    synced_lyrics: &mut Option<String>,
-) {
-    if data.len() < 8 {
-        return;
-    }
-    let mut offset = 0;
-    let vendor_len = le_u32(&data[offset..offset + 4]) as usize;
-    offset += 4 + vendor_len;
-    if offset + 4 > data.len() {
-        return;
+    /// Transmettre les octets lus à la position `offset`, dans n'importe quel ordre
+    pub fn feed(&mut self, offset: f64, chunk: &[u8]) -> JsValue {
+        range_to_js(self.inner.feed(offset as u64, chunk))
     }
-    let count = le_u32(&data[offset..offset + 4]) as usize;
-    offset += 4;
 
-    for _ in 0..count {
-        if offset + 4 > data.len() {
-            break;
-        }
-        let len = le_u32(&data[offset..offset + 4]) as usize;
-        offset += 4;
-        if offset + len > data.len() {
-            break;
-        }
-        let entry = &data[offset..offset + len.min(max_text_bytes)];
-        offset += len;
-
-        if let Some(eq) = entry.iter().position(|&b| b == b'=') {
-            let key = &entry[..eq];
-            let value = &entry[eq + 1..];
-            if let Ok(key_str) = std::str::from_utf8(key) {
-                let value_str = std::str::from_utf8(value)
-                    .ok()
-                    .map(|s| trim_string(s.to_string()));
-                match key_str.to_ascii_uppercase().as_str() {
-                    "TITLE" => {
-                        if title.is_none() {
-                            if let Some(v) = value_str {
-                                *title = Some(v);
-                            }
-                        }
-                    }
-                    "ARTIST" => {
-                        if artist.is_none() {
-                            if let Some(v) = value_str {
-                                *artist = Some(v);
-                            }
-                        }
-                    }
-                    "ALBUM" => {
-                        if album.is_none() {
-                            if let Some(v) = value_str {
-                                *album = Some(v);
-                            }
-                        }
-                    }
-                    "LYRICS" => {
-                        if synced_lyrics.is_none() {
-                            if let Some(v) = value_str {
-                                *synced_lyrics = Some(v);
-                            }
-                        }
-                    }
-                    "UNSYNCEDLYRICS" => {
-                        if lyrics.is_none() {
-                            if let Some(v) = value_str {
-                                *lyrics = Some(v);
-                            }
-                        }
-                    }
-                    _ => {}
-                }
-            }
+    /// Résultat au format de `parse_metadata` ; objet vide si le fichier n'est pas reconnu
+    /// ou si une plage demandée n'a pas été transmise
+    pub fn finish(&self) -> JsValue {
+        match self.inner.finish() {
+            Ok(metadata) => to_js_value(&metadata),
+            Err(_) => Object::new().into(),
         }
     }
 }
 
-fn parse_flac_picture(data: &[u8], max_cover_bytes: usize) -> Option<(String, Vec<u8>, u8)> {
-    if data.len() < 32 {
-        return None;
+/// Options lues depuis un objet JS `MetadataParseOptions`.
+/// Un `fallbackEncoding` inconnu de `encoding_for_label` est ignoré.
+fn parse_options_from_js(value: &JsValue) -> ParseOptions {
+    let mut options = ParseOptions::default();
+    if let Some(v) = get_number_prop(value, "maxTextBytes") {
+        options.max_text_bytes = v as usize;
     }
-    let mut offset = 0;
-    let pic_type_raw = be_u32(&data[offset..offset + 4]);
-    let pic_type = if pic_type_raw > 255 {
-        0
-    } else {
-        pic_type_raw as u8
-    };
-    offset += 4;
-
-    let mime_len = be_u32(&data[offset..offset + 4]) as usize;
-    offset += 4;
-    if offset + mime_len > data.len() {
-        return None;
+    if let Some(v) = get_number_prop(value, "maxCoverBytes") {
+        options.max_cover_bytes = v as usize;
     }
-    let mime = String::from_utf8_lossy(&data[offset..offset + mime_len]).to_string();
-    offset += mime_len;
-
-    let desc_len = be_u32(&data[offset..offset + 4]) as usize;
-    offset += 4;
-    if offset + desc_len > data.len() {
-        return None;
+    if let Some(v) = get_bool_prop(value, "includePictures") {
+        options.include_pictures = v;
     }
-    offset += desc_len;
-
-    // Ignorer largeur, hauteur, profondeur de couleur, couleurs utilisées (4 × 4 octets)
-    offset += 4 * 4;
-    if offset + 4 > data.len() {
-        return None;
+    if let Some(v) = get_bool_prop(value, "includePictureData") {
+        options.include_picture_data = v;
     }
-    let pic_len = be_u32(&data[offset..offset + 4]) as usize;
-    offset += 4;
-    if offset + pic_len > data.len() || pic_len > max_cover_bytes {
-        return None;
+    if let Some(v) = get_bool_prop(value, "includeCoverData") {
+        options.include_cover_data = v;
     }
-    let img = &data[offset..offset + pic_len];
-    if img.is_empty() {
-        return None;
+    if let Some(v) = get_bool_prop(value, "coverRefs") {
+        options.cover_refs = v;
     }
-    Some((mime, img.to_vec(), pic_type))
-}
-
-// ---------------------------------------------------------------------------
-// Trame PIC ID3v2.2 (code de format à 3 caractères au lieu d'une chaîne MIME)
-// ---------------------------------------------------------------------------
-
-fn parse_pic_frame(frame_data: &[u8], max_cover_bytes: usize) -> Option<(String, Vec<u8>, u8)> {
-    if frame_data.len() < 5 {
-        return None;
+    if let Some(sizes) = get_number_array_prop(value, "thumbnailSizes") {
+        options.thumbnail_sizes = sizes
+            .into_iter()
+            .filter(|&size| size >= 1.0)
+            .map(|size| size.min(MAX_THUMBNAIL_SIZE as f64) as u32)
+            .collect();
     }
-    let encoding = frame_data[0];
-    let format = &frame_data[1..4];
-    let mime = match format {
-        b"PNG" => "image/png",
-        b"JPG" => "image/jpeg",
-        b"GIF" => "image/gif",
-        _ => "image/unknown",
+    if let Some(format) = get_prop(value, "thumbnailFormat").and_then(|v| v.as_string()) {
+        options.thumbnail_format = match format.as_str() {
+            "rgba" => ThumbnailFormat::Rgba,
+            _ => ThumbnailFormat::Png,
+        };
     }
-    .to_string();
-
-    let pic_type = frame_data[4];
-    let idx = 5;
-
-    let (_desc_end, next_idx) = if encoding == 0 || encoding == 3 {
-        let end = find_zero(frame_data, idx)?;
-        (end, end + 1)
-    } else {
-        let end = find_zero_utf16(frame_data, idx)?;
-        (end, end + 2)
-    };
-
-    if next_idx > frame_data.len() {
-        return None;
+    if let Some(label) = get_prop(value, "fallbackEncoding").and_then(|v| v.as_string()) {
+        options.fallback_encoding = encoding_for_label(&label);
     }
-
-    let img_data = &frame_data[next_idx..];
-    if img_data.is_empty() || img_data.len() > max_cover_bytes {
-        return None;
+    if let Some(v) = get_number_prop(value, "fileSize") {
+        options.file_size = Some(v as u64);
     }
-
-    Some((mime, img_data.to_vec(), pic_type))
-}
-
-// ---------------------------------------------------------------------------
-// Fonctions utilitaires
-// ---------------------------------------------------------------------------
-
-fn synchsafe_to_u32(bytes: &[u8]) -> u32 {
-    if bytes.len() < 4 {
-        return 0;
+    if let Some(v) = get_bool_prop(value, "includeSeekPoints") {
+        options.include_seek_points = v;
     }
-    ((bytes[0] as u32) << 21)
-        | ((bytes[1] as u32) << 14)
-        | ((bytes[2] as u32) << 7)
-        | (bytes[3] as u32)
-}
-
-fn be_u32(bytes: &[u8]) -> u32 {
-    if bytes.len() < 4 {
-        return 0;
+    if let Some(v) = get_number_prop(value, "mpegScanBytes") {
+        options.mpeg_scan_bytes = v as usize;
     }
-    ((bytes[0] as u32) << 24)
-        | ((bytes[1] as u32) << 16)
-        | ((bytes[2] as u32) << 8)
-        | (bytes[3] as u32)
+    options
 }
 
-fn le_u32(bytes: &[u8]) -> u32 {
-    if bytes.len() < 4 {
-        return 0;
-    }
-    (bytes[0] as u32)
-        | ((bytes[1] as u32) << 8)
-        | ((bytes[2] as u32) << 16)
-        | ((bytes[3] as u32) << 24)
-}
-
-fn find_zero(bytes: &[u8], start: usize) -> Option<usize> {
-    bytes[start..]
-        .iter()
-        .position(|&b| b == 0)
-        .map(|p| start + p)
+fn cue_sheet_from_js(value: &JsValue) -> CueSheet {
+    let mut sheet = CueSheet {
+        catalog: get_string_prop(value, "catalog"),
+        title: get_string_prop(value, "title"),
+        performer: get_string_prop(value, "performer"),
+        file: get_string_prop(value, "file"),
+        genre: get_string_prop(value, "genre"),
+        date: get_string_prop(value, "date"),
+        comment: get_string_prop(value, "comment"),
+        album_gain: get_signed_number_prop(value, "albumGain").map(|v| v as f32),
+        album_peak: get_number_prop(value, "albumPeak").map(|v| v as f32),
+        sample_rate: CD_SAMPLE_RATE,
+        ..CueSheet::default()
+    };
+    let tracks = get_prop(value, "tracks").and_then(|v| v.dyn_into::<Array>().ok());
+    for item in tracks.iter().flat_map(|tracks| tracks.iter()) {
+        let mut indices = Vec::new();
+        let points = get_prop(&item, "indices").and_then(|v| v.dyn_into::<Array>().ok());
+        for point in points.iter().flat_map(|points| points.iter()) {
+            if let (Some(number), Some(ms)) = (
+                get_number_prop(&point, "number"),
+                get_number_prop(&point, "offsetMs"),
+            ) {
+                indices.push(CueIndex {
+                    number: number as u8,
+                    offset: sheet.ms_to_samples(ms),
+                });
+            }
+        }
+        if indices.is_empty()
+            && let Some(start_ms) = get_number_prop(&item, "startMs")
+        {
+            if let Some(gap_ms) = get_number_prop(&item, "preGapMs").filter(|&gap| gap > 0.0) {
+                indices.push(CueIndex {
+                    number: 0,
+                    offset: sheet.ms_to_samples((start_ms - gap_ms).max(0.0)),
+                });
+            }
+            indices.push(CueIndex {
+                number: 1,
+                offset: sheet.ms_to_samples(start_ms),
+            });
+        }
+        let flags = get_prop(&item, "flags")
+            .and_then(|v| v.dyn_into::<Array>().ok())
+            .map(|flags| flags.iter().filter_map(|flag| flag.as_string()).collect())
+            .unwrap_or_default();
+
+        sheet.tracks.push(CueTrack {
+            number: get_number_prop(&item, "number").map_or(0, |n| n as u8),
+            isrc: get_string_prop(&item, "isrc"),
+            title: get_string_prop(&item, "title"),
+            performer: get_string_prop(&item, "performer"),
+            file: get_string_prop(&item, "file"),
+            is_audio: get_bool_prop(&item, "audio").unwrap_or(true),
+            pre_emphasis: get_bool_prop(&item, "preEmphasis").unwrap_or(false),
+            flags,
+            track_gain: get_signed_number_prop(&item, "trackGain").map(|v| v as f32),
+            track_peak: get_number_prop(&item, "trackPeak").map(|v| v as f32),
+            indices,
+        });
+    }
+    sheet
 }
 
-fn find_zero_utf16(bytes: &[u8], start: usize) -> Option<usize> {
-    let mut i = start;
-    while i + 1 < bytes.len() {
-        if bytes[i] == 0 && bytes[i + 1] == 0 {
-            return Some(i);
+fn parse_batch_with_options(buffers: &Array, options: &ParseOptions, file_sizes: &[u64]) -> Array {
+    let out = Array::new();
+    let len = buffers.length();
+    let mut item_options = options.clone();
+    for i in 0..len {
+        let value = buffers.get(i);
+        if let Some(u8a) = value.dyn_ref::<Uint8Array>() {
+            let mut vec = vec![0u8; u8a.length() as usize];
+            u8a.copy_to(&mut vec[..]);
+            // Sans taille propre, un tampon ne doit pas hériter de celle du précédent
+            item_options.file_size = file_sizes.get(i as usize).copied().or(options.file_size);
+            let meta = parse_with_options(&vec, &item_options);
+            out.push(&meta);
+        } else {
+            out.push(&JsValue::NULL);
         }
-        i += 2;
     }
-    None
+    out
 }
 
-fn latin1_to_string(bytes: &[u8]) -> String {
-    bytes.iter().map(|&b| b as char).collect()
+/// Plage à lire, ou `null` quand il n'y en a plus
+fn range_to_js(range: Option<Range<u64>>) -> JsValue {
+    range.map_or(JsValue::NULL, |range| to_js_value(&ByteRange::from(range)))
 }
 
-fn trim_trailing_zeros(bytes: &[u8]) -> &[u8] {
-    let mut end = bytes.len();
-    while end > 0 && (bytes[end - 1] == 0 || bytes[end - 1] == b' ') {
-        end -= 1;
+/// Sérialiser un résultat vers JS : objets simples plutôt que `Map`, octets en `Uint8Array`
+fn to_js_value(value: &impl Serialize) -> JsValue {
+    let serializer = serde_wasm_bindgen::Serializer::new().serialize_maps_as_objects(true);
+    value.serialize(&serializer).unwrap_or(JsValue::NULL)
+}
+
+fn get_prop(obj: &JsValue, key: &str) -> Option<JsValue> {
+    if !obj.is_object() {
+        return None;
     }
-    &bytes[..end]
+    js_sys::Reflect::get(obj, &JsValue::from_str(key))
+        .ok()
+        .filter(|v| !v.is_undefined() && !v.is_null())
 }
 
-fn trim_string(s: String) -> String {
-    s.trim_matches(|c: char| c == '\0' || c.is_whitespace())
-        .to_string()
+fn get_number_prop(obj: &JsValue, key: &str) -> Option<f64> {
+    get_prop(obj, key)?
+        .as_f64()
+        .filter(|v| v.is_finite() && *v >= 0.0)
 }
 
-fn decode_utf16_with_bom(bytes: &[u8]) -> Option<String> {
-    if bytes.len() < 2 {
-        return None;
-    }
-    if bytes[0] == 0xFF && bytes[1] == 0xFE {
-        decode_utf16_le(&bytes[2..])
-    } else if bytes[0] == 0xFE && bytes[1] == 0xFF {
-        decode_utf16_be(&bytes[2..])
-    } else {
-        decode_utf16_le(bytes)
-    }
+fn get_bool_prop(obj: &JsValue, key: &str) -> Option<bool> {
+    get_prop(obj, key)?.as_bool()
 }
 
-fn decode_utf16_le(bytes: &[u8]) -> Option<String> {
-    let mut u16s = Vec::with_capacity(bytes.len() / 2);
-    let mut i = 0;
-    while i + 1 < bytes.len() {
-        let val = (bytes[i] as u16) | ((bytes[i + 1] as u16) << 8);
-        if val == 0 {
-            break;
-        }
-        u16s.push(val);
-        i += 2;
-    }
-    String::from_utf16(&u16s).ok().map(trim_string)
+/// Nombre éventuellement négatif (gain ReplayGain)
+fn get_signed_number_prop(obj: &JsValue, key: &str) -> Option<f64> {
+    get_prop(obj, key)?.as_f64().filter(|v| v.is_finite())
 }
 
-fn decode_utf16_be(bytes: &[u8]) -> Option<String> {
-    let mut u16s = Vec::with_capacity(bytes.len() / 2);
-    let mut i = 0;
-    while i + 1 < bytes.len() {
-        let val = ((bytes[i] as u16) << 8) | (bytes[i + 1] as u16);
-        if val == 0 {
-            break;
-        }
-        u16s.push(val);
-        i += 2;
-    }
-    String::from_utf16(&u16s).ok().map(trim_string)
+fn get_string_prop(obj: &JsValue, key: &str) -> Option<String> {
+    get_prop(obj, key)?.as_string()
 }
 
-fn decode_utf16be(bytes: &[u8]) -> Option<String> {
-    decode_utf16_be(bytes)
+fn get_number_array_prop(obj: &JsValue, key: &str) -> Option<Vec<f64>> {
+    let array = get_prop(obj, key)?.dyn_into::<Array>().ok()?;
+    Some(
+        array
+            .iter()
+            .filter_map(|v| v.as_f64())
+            .filter(|v| v.is_finite())
+            .collect(),
+    )
 }